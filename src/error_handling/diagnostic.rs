@@ -0,0 +1,139 @@
+use crate::error_handling::messages::{Message, MessageId};
+use crate::error_handling::LineInformation;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let id = match self {
+            Severity::Error => MessageId::SeverityError,
+            Severity::Warning => MessageId::SeverityWarning,
+            Severity::Note => MessageId::SeverityNote,
+        };
+        write!(f, "{}", Message::new(id))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Applicability {
+    // The suggestion is certainly what the user meant and can be applied by `--fix`.
+    MachineApplicable,
+    // The suggestion is likely, but not certainly, what the user meant.
+    MaybeIncorrect,
+}
+
+// A suggested edit that replaces the text of a span, rendered as "help: try {message}".
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Suggestion {
+    pub message: String,
+    #[serde(rename = "span")]
+    pub line_information: LineInformation,
+    pub replacement: String,
+    pub applicability: Applicability,
+}
+
+impl Suggestion {
+    pub fn new(
+        message: &str,
+        line_information: &LineInformation,
+        replacement: &str,
+        applicability: Applicability,
+    ) -> Suggestion {
+        Suggestion {
+            message: message.to_string(),
+            line_information: *line_information,
+            replacement: replacement.to_string(),
+            applicability,
+        }
+    }
+}
+
+// A secondary span explaining the diagnostic, e.g. the annotation that caused an expectation. It
+// is rendered as "--- {message}" below the span.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Label {
+    pub message: String,
+    #[serde(rename = "span")]
+    pub line_information: LineInformation,
+}
+
+impl Label {
+    pub fn new(message: &str, line_information: &LineInformation) -> Label {
+        Label {
+            message: message.to_string(),
+            line_information: *line_information,
+        }
+    }
+
+    pub fn from_message(message: &Message, line_information: &LineInformation) -> Label {
+        Label::new(&message.to_string(), line_information)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    // Id of the message in the catalogs, if the message was rendered from one.
+    pub id: Option<&'static str>,
+    pub message: String,
+    #[serde(rename = "span")]
+    pub line_information: LineInformation,
+    // Name of the lint that caused the diagnostic, if any.
+    pub lint: Option<&'static str>,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl Diagnostic {
+    pub fn new(
+        severity: Severity,
+        message: &str,
+        line_information: &LineInformation,
+    ) -> Diagnostic {
+        Diagnostic {
+            severity,
+            id: None,
+            message: message.to_string(),
+            line_information: *line_information,
+            lint: None,
+            labels: vec![],
+            notes: vec![],
+            suggestions: vec![],
+        }
+    }
+
+    pub fn from_message(
+        severity: Severity,
+        message: &Message,
+        line_information: &LineInformation,
+    ) -> Diagnostic {
+        let mut diagnostic = Diagnostic::new(severity, &message.to_string(), line_information);
+        diagnostic.id = Some(message.id().id());
+        diagnostic
+    }
+
+    pub fn with_label(mut self, label: Label) -> Diagnostic {
+        self.labels.push(label);
+        self
+    }
+
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Diagnostic {
+        self.suggestions.push(suggestion);
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Diagnostic {
+        self.notes.push(note.to_string());
+        self
+    }
+}
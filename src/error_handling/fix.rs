@@ -0,0 +1,140 @@
+use crate::error_handling::diagnostic::{Applicability, Suggestion};
+use crate::error_handling::LineInformation;
+
+// Apply a single suggestion to the code.
+pub fn apply_suggestion(code: &str, suggestion: &Suggestion) -> String {
+    let start = suggestion.line_information.offset();
+    let end = suggestion.line_information.end();
+    format!(
+        "{}{}{}",
+        &code[..start],
+        suggestion.replacement,
+        &code[end..]
+    )
+}
+
+// Apply all machine-applicable suggestions that do not conflict with each other. Of two
+// conflicting suggestions, the one starting first wins. Returns the fixed code and the number of
+// applied suggestions.
+pub fn apply_suggestions(code: &str, suggestions: &[Suggestion]) -> (String, usize) {
+    let mut applicable: Vec<&Suggestion> = suggestions
+        .iter()
+        .filter(|it| it.applicability == Applicability::MachineApplicable)
+        .collect();
+    applicable.sort_by_key(|it| (it.line_information.offset(), it.line_information.end()));
+
+    let mut accepted: Vec<&Suggestion> = vec![];
+    for suggestion in applicable {
+        if let Some(previous) = accepted.last() {
+            // Two insertions at the same position conflict as well, since their order is unclear.
+            if suggestion.line_information.offset() < previous.line_information.end()
+                || suggestion.line_information.offset() == previous.line_information.offset()
+            {
+                continue;
+            }
+        }
+        accepted.push(suggestion);
+    }
+
+    // Apply back to front, s.t. offsets of earlier suggestions stay valid.
+    let mut result = code.to_string();
+    for suggestion in accepted.iter().rev() {
+        result = apply_suggestion(&result, suggestion);
+    }
+    (result, accepted.len())
+}
+
+// Move suggestions for the code with `\r\n` replaced by `\n` to the original code, s.t. applying
+// them keeps its line endings.
+pub fn with_original_line_endings(original: &str, suggestions: &[Suggestion]) -> Vec<Suggestion> {
+    // The offsets of the `\n`s in the normalized code that followed a `\r`.
+    let crlf: Vec<usize> = original
+        .match_indices("\r\n")
+        .enumerate()
+        .map(|(removed, (offset, _))| offset - removed)
+        .collect();
+    let original_offset = |offset: usize| offset + crlf.partition_point(|it| *it < offset);
+    suggestions
+        .iter()
+        .map(|suggestion| {
+            let start = original_offset(suggestion.line_information.offset());
+            let end = original_offset(suggestion.line_information.end());
+            Suggestion {
+                line_information: LineInformation::new(start, end - start),
+                ..suggestion.clone()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggestion(offset: usize, length: usize, replacement: &str) -> Suggestion {
+        Suggestion::new(
+            "",
+            &LineInformation::new(offset, length),
+            replacement,
+            Applicability::MachineApplicable,
+        )
+    }
+
+    #[test]
+    fn test_apply_suggestions() {
+        let code = "if (a = b) print \"x;";
+        let suggestions = vec![suggestion(19, 0, "\""), suggestion(6, 1, "==")];
+
+        let (fixed, applied) = apply_suggestions(code, &suggestions);
+
+        assert_eq!(fixed, "if (a == b) print \"x\";");
+        assert_eq!(applied, 2);
+    }
+
+    #[test]
+    fn test_conflicting_suggestions_are_skipped() {
+        let code = "abcdef";
+        let suggestions = vec![
+            suggestion(2, 2, "X"),
+            suggestion(3, 2, "Y"),
+            suggestion(0, 0, "1"),
+            suggestion(0, 0, "2"),
+        ];
+
+        let (fixed, applied) = apply_suggestions(code, &suggestions);
+
+        assert_eq!(fixed, "1abXef");
+        assert_eq!(applied, 2);
+    }
+
+    #[test]
+    fn test_maybe_incorrect_suggestions_are_not_applied() {
+        let code = "abc";
+        let suggestions = vec![Suggestion::new(
+            "",
+            &LineInformation::new(0, 1),
+            "",
+            Applicability::MaybeIncorrect,
+        )];
+
+        let (fixed, applied) = apply_suggestions(code, &suggestions);
+
+        assert_eq!(fixed, "abc");
+        assert_eq!(applied, 0);
+    }
+
+    #[test]
+    fn test_original_line_endings() {
+        let original = "a\r\nb\nc\r\n";
+        let suggestions = vec![
+            suggestion(1, 0, ";"),
+            suggestion(3, 0, "d"),
+            suggestion(4, 2, "e"),
+        ];
+
+        let moved = with_original_line_endings(original, &suggestions);
+        let (fixed, _) = apply_suggestions(original, &moved);
+
+        assert_eq!(fixed, "a;\r\nbd\ne");
+    }
+}
@@ -0,0 +1,181 @@
+// Reporting of internal compiler errors (ICEs), i.e. panics caused by bugs in rlox itself.
+use crate::error_handling::render::get_line_index_and_start;
+use std::cell::{Cell, RefCell};
+use std::fmt::{Display, Formatter};
+use std::panic::PanicHookInfo;
+
+// Exit code of a run that ended with an internal error, distinct from the exit code 1 of runs
+// that found errors in the input.
+pub const ICE_EXIT_CODE: i32 = 70;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Startup,
+    Scanning,
+    Parsing,
+    TypeChecking,
+    Reporting,
+    Fixing,
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Phase::Startup => "startup",
+            Phase::Scanning => "scanning",
+            Phase::Parsing => "parsing",
+            Phase::TypeChecking => "type checking",
+            Phase::Reporting => "reporting diagnostics",
+            Phase::Fixing => "applying fixes",
+        };
+        write!(f, "{name}")
+    }
+}
+
+// What the compiler is working on. Panics are reported on the thread they occur on, so the state
+// is kept per thread.
+thread_local! {
+    static PHASE: Cell<Phase> = const { Cell::new(Phase::Startup) };
+    static LOCATION: Cell<Option<usize>> = const { Cell::new(None) };
+    static INPUT: RefCell<Option<(String, String)>> = const { RefCell::new(None) };
+    static REPORT: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+pub fn enter_phase(phase: Phase) {
+    PHASE.with(|it| it.set(phase));
+    LOCATION.with(|it| it.set(None));
+}
+
+// Record the offset in the input that is currently being processed.
+pub fn set_location(offset: usize) {
+    LOCATION.with(|it| it.set(Some(offset)));
+}
+
+// Replace the default panic message by an internal error report for the given input file. The
+// report is kept until `take_report`, s.t. diagnostics reported before the panic can be shown
+// ahead of it.
+pub fn install_panic_hook(file_name: &str, code: &str) {
+    INPUT.with(|it| *it.borrow_mut() = Some((file_name.to_string(), code.to_string())));
+    std::panic::set_hook(Box::new(|info| {
+        let report = get_report(&panic_message(info));
+        REPORT.with(|it| *it.borrow_mut() = Some(report));
+    }));
+}
+
+// The report of the last internal error, if there was one since the last call.
+pub fn take_report() -> Option<String> {
+    REPORT.with(|it| it.borrow_mut().take())
+}
+
+fn panic_message(info: &PanicHookInfo) -> String {
+    let payload = info.payload();
+    let message = match (
+        payload.downcast_ref::<&str>(),
+        payload.downcast_ref::<String>(),
+    ) {
+        (Some(message), _) => message.to_string(),
+        (_, Some(message)) => message.clone(),
+        _ => "unknown cause".to_string(),
+    };
+    match info.location() {
+        None => message,
+        Some(location) => format!("{} ({}:{})", message, location.file(), location.line()),
+    }
+}
+
+fn get_report(message: &str) -> String {
+    let phase = PHASE.with(|it| it.get());
+    let location = LOCATION.with(|it| it.get());
+    INPUT.with(|it| match it.borrow().as_ref() {
+        None => format_report(message, phase, None),
+        Some((file_name, code)) => {
+            let location = location.map(|offset| describe_location(file_name, code, offset));
+            format_report(message, phase, location.as_deref())
+        }
+    })
+}
+
+// Describes an offset as `file:line:column` followed by the line itself.
+fn describe_location(file_name: &str, code: &str, offset: usize) -> String {
+    let offset = offset.min(code.len());
+    let (line_index, line_start) = get_line_index_and_start(code, offset);
+    let line = code[line_start..].split('\n').next().unwrap_or("");
+    format!(
+        "{}:{}:{}: `{}`",
+        file_name,
+        line_index + 1,
+        offset - line_start + 1,
+        line.trim()
+    )
+}
+
+// The report is not localized, since it is meant to be pasted into bug reports.
+fn format_report(message: &str, phase: Phase, location: Option<&str>) -> String {
+    let location = location.unwrap_or("unknown");
+    format!(
+        "internal error: {message}\n\
+         \x20  = phase: {phase}\n\
+         \x20  = location: {location}\n\
+         \x20  = note: this is a bug in rlox {VERSION}, not in your program.\n\
+         \x20  = note: please report it with the following information:\n\
+         \n\
+         ### Description\n\
+         <What were you trying to do?>\n\
+         \n\
+         ### Internal error\n\
+         rlox version: {VERSION}\n\
+         phase: {phase}\n\
+         location: {location}\n\
+         message: {message}\n\
+         \n\
+         ### Input\n\
+         <A minimal program that reproduces the error.>\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_handling::ErrorHandler;
+    use crate::frontend::parser::parse;
+    use crate::frontend::scanner::scan;
+    use crate::frontend::typecheck::check;
+
+    #[test]
+    fn test_describe_location() {
+        let code = "var a = 1;\n  var b = $;\n";
+        assert_eq!(
+            describe_location("test.lox", code, 21),
+            "test.lox:2:11: `var b = $;`"
+        );
+    }
+
+    #[test]
+    fn test_report_location_while_type_checking() {
+        let code = "var a: int = 1;\nprint a + 1;";
+        INPUT.with(|it| *it.borrow_mut() = Some(("test.lox".to_string(), code.to_string())));
+        let result = std::panic::catch_unwind(|| {
+            let error_handler = ErrorHandler::new(code);
+            let tokens = scan(code, &error_handler).unwrap();
+            let ast = parse(tokens, &error_handler).unwrap();
+            enter_phase(Phase::TypeChecking);
+            check(&ast, &error_handler).unwrap();
+            panic!("type checker bug");
+        });
+        assert!(result.is_err());
+        let report = get_report("type checker bug");
+        assert!(report.contains("   = phase: type checking\n"));
+        assert!(report.contains("   = location: test.lox:2:11: `print a + 1;`\n"));
+    }
+
+    #[test]
+    fn test_format_report() {
+        let report = format_report("index out of bounds", Phase::Scanning, None);
+        assert!(report.starts_with(
+            "internal error: index out of bounds\n   = phase: scanning\n   = location: unknown\n"
+        ));
+        assert!(report.contains(&format!("rlox version: {VERSION}\n")));
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+// Name that addresses every lint whose level is `Warn` at once, e.g. `-D warnings`.
+pub const WARNINGS: &str = "warnings";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl LintLevel {
+    pub fn from_name(name: &str) -> Option<LintLevel> {
+        match name {
+            "allow" => Some(LintLevel::Allow),
+            "warn" => Some(LintLevel::Warn),
+            "deny" => Some(LintLevel::Deny),
+            _ => None,
+        }
+    }
+}
+
+pub struct Lint {
+    pub name: &'static str,
+    pub default_level: LintLevel,
+}
+
+pub static NESTED_BLOCK_COMMENT: Lint = Lint {
+    name: "nested_block_comment",
+    default_level: LintLevel::Warn,
+};
+
+pub static AMBIGUOUS_CONTINUATION: Lint = Lint {
+    name: "ambiguous_continuation",
+    default_level: LintLevel::Warn,
+};
+
+pub static UNKNOWN_LINTS: Lint = Lint {
+    name: "unknown_lints",
+    default_level: LintLevel::Warn,
+};
+
+pub static LINTS: &[&Lint] = &[
+    &NESTED_BLOCK_COMMENT,
+    &AMBIGUOUS_CONTINUATION,
+    &UNKNOWN_LINTS,
+];
+
+pub fn find_lint(name: &str) -> Option<&'static Lint> {
+    LINTS.iter().find(|lint| lint.name == name).copied()
+}
+
+// Parse an in-source lint attribute such as `#[allow(nested_block_comment)]`.
+// Returns None if text is not a lint attribute at all.
+pub fn parse_attribute(text: &str) -> Option<(LintLevel, Vec<&str>)> {
+    let inner = text.trim().strip_prefix("#[")?.strip_suffix(']')?;
+    let (level, names) = inner.split_once('(')?;
+    let level = LintLevel::from_name(level.trim())?;
+    let names = names.trim_end().strip_suffix(')')?;
+    Some((
+        level,
+        names
+            .split(',')
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .collect(),
+    ))
+}
+
+// Lint levels requested by the user, e.g. through `-W`, `-D` and `-A` on the command line.
+#[derive(Default)]
+pub struct LintLevels {
+    levels: HashMap<&'static str, LintLevel>,
+    warnings_level: Option<LintLevel>,
+}
+
+impl LintLevels {
+    pub fn new() -> LintLevels {
+        LintLevels::default()
+    }
+
+    // Returns false if no lint with the given name exists.
+    pub fn set(&mut self, name: &str, level: LintLevel) -> bool {
+        if name == WARNINGS {
+            self.warnings_level = Some(level);
+            return true;
+        }
+        match find_lint(name) {
+            None => false,
+            Some(lint) => {
+                self.levels.insert(lint.name, level);
+                true
+            }
+        }
+    }
+
+    pub fn level(&self, lint: &Lint) -> LintLevel {
+        let level = *self.levels.get(lint.name).unwrap_or(&lint.default_level);
+        self.apply_warnings_level(level)
+    }
+
+    // `-D warnings` and `-A warnings` apply to every lint that would otherwise only warn.
+    pub fn apply_warnings_level(&self, level: LintLevel) -> LintLevel {
+        match (level, self.warnings_level) {
+            (LintLevel::Warn, Some(warnings_level)) => warnings_level,
+            (level, _) => level,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_level() {
+        let levels = LintLevels::new();
+        assert_eq!(levels.level(&NESTED_BLOCK_COMMENT), LintLevel::Warn);
+    }
+
+    #[test]
+    fn test_set_level() {
+        let mut levels = LintLevels::new();
+        assert!(levels.set("nested_block_comment", LintLevel::Allow));
+        assert_eq!(levels.level(&NESTED_BLOCK_COMMENT), LintLevel::Allow);
+        assert_eq!(levels.level(&UNKNOWN_LINTS), LintLevel::Warn);
+    }
+
+    #[test]
+    fn test_set_unknown_lint() {
+        let mut levels = LintLevels::new();
+        assert!(!levels.set("no_such_lint", LintLevel::Deny));
+    }
+
+    #[test]
+    fn test_deny_warnings() {
+        let mut levels = LintLevels::new();
+        levels.set("nested_block_comment", LintLevel::Allow);
+        levels.set(WARNINGS, LintLevel::Deny);
+        assert_eq!(levels.level(&NESTED_BLOCK_COMMENT), LintLevel::Allow);
+        assert_eq!(levels.level(&UNKNOWN_LINTS), LintLevel::Deny);
+    }
+
+    #[test]
+    fn test_parse_attribute() {
+        assert_eq!(
+            parse_attribute(" #[deny(nested_block_comment, unknown_lints)]"),
+            Some((
+                LintLevel::Deny,
+                vec!["nested_block_comment", "unknown_lints"]
+            ))
+        );
+        assert_eq!(parse_attribute(" just a comment"), None);
+        assert_eq!(parse_attribute("#[forbid(nested_block_comment)]"), None);
+    }
+}
@@ -0,0 +1,298 @@
+use once_cell::sync::{Lazy, OnceCell};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Locale {
+    English,
+    German,
+}
+
+impl Locale {
+    pub const ALL: &'static [Locale] = &[Locale::English, Locale::German];
+
+    // Accepts language names like `de` as well as values of `LANG` like `de_DE.UTF-8`.
+    pub fn from_name(name: &str) -> Option<Locale> {
+        let language = name.split(['_', '.', '-']).next().unwrap_or("");
+        match language.to_lowercase().as_str() {
+            "en" | "c" | "posix" => Some(Locale::English),
+            "de" => Some(Locale::German),
+            _ => None,
+        }
+    }
+
+    fn catalog(&self) -> &'static HashMap<&'static str, &'static str> {
+        match self {
+            Locale::English => &ENGLISH,
+            Locale::German => &GERMAN,
+        }
+    }
+}
+
+static LOCALE: OnceCell<Locale> = OnceCell::new();
+
+// Select the locale messages are rendered in. Can only be set once, later calls are ignored.
+// Messages are rendered in English if no locale is set.
+pub fn set_locale(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+fn locale() -> Locale {
+    *LOCALE.get().unwrap_or(&Locale::English)
+}
+
+static ENGLISH: Lazy<HashMap<&str, &str>> =
+    Lazy::new(|| parse_catalog(include_str!("locales/en.txt")));
+static GERMAN: Lazy<HashMap<&str, &str>> =
+    Lazy::new(|| parse_catalog(include_str!("locales/de.txt")));
+
+// Catalogs contain one `id = text` entry per line. Texts refer to arguments as `{name}`. An entry
+// `id[one] = text` is used instead of `id` if the argument `count` is 1. Lines starting with `#`
+// are comments.
+fn parse_catalog(text: &'static str) -> HashMap<&'static str, &'static str> {
+    text.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once(" = "))
+        .map(|(id, text)| (id.trim(), text.trim()))
+        .collect()
+}
+
+macro_rules! message_ids {
+    ($($variant:ident => $id:literal,)*) => {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub enum MessageId {
+            $($variant,)*
+        }
+
+        impl MessageId {
+            pub const ALL: &'static [MessageId] = &[$(MessageId::$variant,)*];
+
+            pub fn id(&self) -> &'static str {
+                match self {
+                    $(MessageId::$variant => $id,)*
+                }
+            }
+        }
+    };
+}
+
+message_ids! {
+    SeverityError => "severity-error",
+    SeverityWarning => "severity-warning",
+    SeverityNote => "severity-note",
+    SnippetContinues => "snippet-continues",
+    HelpTry => "help-try",
+    Summary => "summary",
+    SummaryErrors => "summary-errors",
+    SummaryWarnings => "summary-warnings",
+    ErrorsSuppressed => "errors-suppressed",
+    FixesApplied => "fixes-applied",
+    CliError => "cli-error",
+    InputNotAscii => "input-not-ascii",
+    UnknownLanguage => "unknown-language",
+    UnknownLint => "unknown-lint",
+    LintLevelNote => "lint-level-note",
+    Token => "token",
+    UnexpectedCharacter => "unexpected-character",
+    UnterminatedComment => "unterminated-comment",
+    UnterminatedString => "unterminated-string",
+    InvalidInteger => "invalid-integer",
+    InvalidFloat => "invalid-float",
+    NestedBlockComment => "nested-block-comment",
+    AmbiguousContinuation => "ambiguous-continuation",
+    CloseComment => "close-comment",
+    CloseString => "close-string",
+    UnknownIdentifier => "unknown-identifier",
+    UnknownIdentifierKeyword => "unknown-identifier-keyword",
+    UnknownIdentifierSimilar => "unknown-identifier-similar",
+    UseReplacement => "use-replacement",
+    EndOfFile => "end-of-file",
+    ExpectedExpression => "expected-expression",
+    ExpectedToken => "expected-token",
+    ExpectedPropertyName => "expected-property-name",
+    InvalidAssignmentTarget => "invalid-assignment-target",
+    ExpectedName => "expected-name",
+    ExpectedType => "expected-type",
+    AssignmentInCondition => "assignment-in-condition",
+    UseComparison => "use-comparison",
+    TypeMismatch => "type-mismatch",
+    ExpectedDueToAnnotation => "expected-due-to-annotation",
+    InvalidUnaryOperand => "invalid-unary-operand",
+    InvalidBinaryOperands => "invalid-binary-operands",
+    HasType => "has-type",
+    NotCallable => "not-callable",
+    ArgumentCount => "argument-count",
+    DeclaredHere => "declared-here",
+    UnknownType => "unknown-type",
+    MissingTypeAnnotation => "missing-type-annotation",
+    CannotInferFromNil => "cannot-infer-from-nil",
+    InferredFrom => "inferred-from",
+    NotAClass => "not-a-class",
+    InheritanceCycle => "inheritance-cycle",
+    MemberRedeclared => "member-redeclared",
+    IncompatibleOverride => "incompatible-override",
+    NoMembers => "no-members",
+    UnknownMember => "unknown-member",
+    AvailableMembers => "available-members",
+    AssignToMethod => "assign-to-method",
+    ThisOutsideClass => "this-outside-class",
+    SuperWithoutSuperclass => "super-without-superclass",
+    Uninitialized => "uninitialized",
+    UninitializedField => "uninitialized-field",
+    NullableMember => "nullable-member",
+    NullableCall => "nullable-call",
+    CheckForNil => "check-for-nil",
+    TypeArgumentCount => "type-argument-count",
+    TypeArgumentBound => "type-argument-bound",
+    CannotInferTypeArgument => "cannot-infer-type-argument",
+    InferFromAnnotation => "infer-from-annotation",
+    GenericSuperclass => "generic-superclass",
+    InvalidConversion => "invalid-conversion",
+    LossyConversion => "lossy-conversion",
+    ConvertExplicitly => "convert-explicitly",
+    ReturnValueWithoutReturnType => "return-value-without-return-type",
+    MissingReturnValue => "missing-return-value",
+    MayNotReturn => "may-not-return",
+    AssignToImmutable => "assign-to-immutable",
+    DeclaredImmutable => "declared-immutable",
+}
+
+// A message id together with the arguments its text refers to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message {
+    id: MessageId,
+    args: Vec<(&'static str, String)>,
+}
+
+impl Message {
+    pub fn new(id: MessageId) -> Message {
+        Message { id, args: vec![] }
+    }
+
+    pub fn arg(mut self, name: &'static str, value: impl Display) -> Message {
+        self.args.push((name, value.to_string()));
+        self
+    }
+
+    pub fn id(&self) -> MessageId {
+        self.id
+    }
+
+    pub fn render_in(&self, locale: Locale) -> String {
+        let id = self.id.id();
+        let is_one = self
+            .args
+            .iter()
+            .any(|(name, value)| *name == "count" && value == "1");
+        let singular = format!("{id}[one]");
+
+        // Fall back to English for messages missing in a catalog, and to the id if even that fails.
+        let text = [locale, Locale::English]
+            .iter()
+            .find_map(|locale| {
+                let catalog = locale.catalog();
+                match is_one {
+                    true => catalog.get(singular.as_str()).or(catalog.get(id)),
+                    false => catalog.get(id),
+                }
+            })
+            .copied()
+            .unwrap_or(id);
+
+        let mut result = text.to_string();
+        for (name, value) in self.args.iter() {
+            result = result.replace(&format!("{{{name}}}"), value);
+        }
+        result
+    }
+}
+
+impl Display for Message {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render_in(locale()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn placeholders(text: &str) -> HashSet<&str> {
+        text.split('{')
+            .skip(1)
+            .filter_map(|it| it.split_once('}'))
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    #[test]
+    fn test_every_message_exists_in_every_catalog() {
+        for locale in Locale::ALL {
+            for id in MessageId::ALL {
+                assert!(
+                    locale.catalog().contains_key(id.id()),
+                    "Message `{}` is missing in the catalog of {:?}.",
+                    id.id(),
+                    locale
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_catalogs_only_contain_known_messages() {
+        let ids: HashSet<&str> = MessageId::ALL.iter().map(|id| id.id()).collect();
+        for locale in Locale::ALL {
+            for key in locale.catalog().keys() {
+                let id = key.strip_suffix("[one]").unwrap_or(key);
+                assert!(ids.contains(id), "Unknown message `{key}` in {:?}.", locale);
+            }
+        }
+    }
+
+    #[test]
+    fn test_translations_use_the_same_arguments() {
+        for locale in Locale::ALL {
+            for (key, text) in locale.catalog().iter() {
+                assert_eq!(
+                    placeholders(text),
+                    placeholders(ENGLISH.get(key).unwrap()),
+                    "Arguments of `{key}` differ between {:?} and English.",
+                    locale
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let message = Message::new(MessageId::UnexpectedCharacter).arg("character", '$');
+        assert_eq!(
+            message.render_in(Locale::English),
+            "Unexpected character '$'."
+        );
+        assert_eq!(
+            message.render_in(Locale::German),
+            "Unerwartetes Zeichen '$'."
+        );
+    }
+
+    #[test]
+    fn test_render_plural() {
+        let one = Message::new(MessageId::SummaryErrors).arg("count", 1);
+        let two = Message::new(MessageId::SummaryErrors).arg("count", 2);
+        assert_eq!(one.render_in(Locale::English), "1 error");
+        assert_eq!(two.render_in(Locale::English), "2 errors");
+        assert_eq!(one.render_in(Locale::German), "1 Fehler");
+    }
+
+    #[test]
+    fn test_locale_from_name() {
+        assert_eq!(Locale::from_name("de_DE.UTF-8"), Some(Locale::German));
+        assert_eq!(Locale::from_name("en"), Some(Locale::English));
+        assert_eq!(Locale::from_name("C"), Some(Locale::English));
+        assert_eq!(Locale::from_name("fr_FR"), None);
+    }
+}
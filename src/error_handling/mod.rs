@@ -1,6 +1,25 @@
-use log::error;
-use std::cmp::min;
-
+pub mod diagnostic;
+pub mod fix;
+pub mod ice;
+pub mod lint;
+pub mod messages;
+mod organize;
+pub mod render;
+pub mod sink;
+
+pub use crate::error_handling::diagnostic::{
+    Applicability, Diagnostic, Label, Severity, Suggestion,
+};
+use crate::error_handling::lint::{
+    find_lint, Lint, LintLevel, LintLevels, UNKNOWN_LINTS, WARNINGS,
+};
+use crate::error_handling::messages::{Message, MessageId};
+use crate::error_handling::organize::organize;
+use crate::error_handling::sink::{DiagnosticSink, MemorySink};
+use serde::Serialize;
+use std::cell::{Cell, RefCell};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
 pub struct LineInformation {
     offset: usize,
     length: usize,
@@ -10,201 +29,328 @@ impl LineInformation {
     pub fn new(offset: usize, length: usize) -> LineInformation {
         LineInformation { offset, length }
     }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    // Offset of the first character after the span.
+    pub fn end(&self) -> usize {
+        self.offset + self.length
+    }
+
+    // The smallest span covering both self and other.
+    pub fn join(&self, other: &LineInformation) -> LineInformation {
+        let offset = self.offset.min(other.offset);
+        LineInformation::new(offset, self.end().max(other.end()) - offset)
+    }
 }
 
+// A lint level set by an attribute comment in the source. It applies from its offset onwards.
+struct SourceLintLevel {
+    offset: usize,
+    name: String,
+    level: LintLevel,
+}
+
+// Collects reported diagnostics and passes them on to its sink when flushed.
 pub struct ErrorHandler {
     code: String,
+    sink: RefCell<Box<dyn DiagnosticSink>>,
+    pending: RefCell<Vec<Diagnostic>>,
+    max_errors: Option<usize>,
+    lint_levels: LintLevels,
+    source_lint_levels: RefCell<Vec<SourceLintLevel>>,
+    error_count: Cell<usize>,
+    warning_count: Cell<usize>,
+    suggestions: RefCell<Vec<Suggestion>>,
 }
 
 impl ErrorHandler {
-    pub fn init_logging() -> Result<(), fern::InitError> {
-        fern::Dispatch::new()
-            .format(|out, msg, record| out.finish(format_args!("{}: {}", record.level(), msg)))
-            .level(log::LevelFilter::Debug)
-            .chain(std::io::stdout())
-            .apply()?;
-        Ok(())
-    }
-
+    // Diagnostics are collected in a MemorySink unless another sink is set with `with_sink`.
     pub fn new(code: &str) -> ErrorHandler {
         ErrorHandler {
             code: code.to_string().clone(),
+            sink: RefCell::new(Box::new(MemorySink::new())),
+            pending: RefCell::new(vec![]),
+            max_errors: None,
+            lint_levels: LintLevels::new(),
+            source_lint_levels: RefCell::new(vec![]),
+            error_count: Cell::new(0),
+            warning_count: Cell::new(0),
+            suggestions: RefCell::new(vec![]),
         }
     }
 
-    pub fn report_error(&self, error_msg: &str, line_information: &LineInformation) {
-        assert!(self.code.len() >= line_information.offset + line_information.length);
+    pub fn with_sink(mut self, sink: Box<dyn DiagnosticSink>) -> ErrorHandler {
+        self.sink = RefCell::new(sink);
+        self
+    }
 
-        let msg = self.get_error_message(error_msg, line_information);
-        error!("{}", msg);
+    pub fn with_lint_levels(mut self, lint_levels: LintLevels) -> ErrorHandler {
+        self.lint_levels = lint_levels;
+        self
     }
 
-    fn get_error_message(&self, error_msg: &str, line_information: &LineInformation) -> String {
-        let mut result = format!("{error_msg}\n").to_string();
+    // Stop emitting diagnostics after max_errors errors.
+    pub fn with_max_errors(mut self, max_errors: Option<usize>) -> ErrorHandler {
+        self.max_errors = max_errors;
+        self
+    }
 
-        let line = &self.code[..=line_information.offset]
-            .chars()
-            .filter(|it| it == &'\n')
-            .count()
-            + 1;
-        let indentation = (line.checked_ilog10().unwrap_or(0) + 3) as usize;
-        let (code_line, column_offset, column_end) =
-            self.get_line_content_and_column_offset(line_information.offset);
+    pub fn report_error(&self, message: &Message, line_information: &LineInformation) {
+        self.report(Diagnostic::from_message(
+            Severity::Error,
+            message,
+            line_information,
+        ));
+    }
 
-        result += &format!("{}|\n", " ".repeat(indentation));
-        result += &format!(" {} | {}\n", line, code_line);
+    pub fn report_note(&self, message: &Message, line_information: &LineInformation) {
+        self.report(Diagnostic::from_message(
+            Severity::Note,
+            message,
+            line_information,
+        ));
+    }
 
-        let marker_start = " ".repeat(column_offset);
+    // Report a lint at the level in effect at the given location. Returns the severity the lint
+    // was reported with, or None if the lint is allowed.
+    pub fn report_lint(
+        &self,
+        lint: &Lint,
+        message: &Message,
+        line_information: &LineInformation,
+    ) -> Option<Severity> {
+        let level = self.lint_level_at(lint, line_information.offset);
+        let severity = match level {
+            LintLevel::Allow => return None,
+            LintLevel::Warn => Severity::Warning,
+            LintLevel::Deny => Severity::Error,
+        };
+        let mut diagnostic = Diagnostic::from_message(severity, message, line_information);
+        diagnostic.lint = Some(lint.name);
+        let note = Message::new(MessageId::LintLevelNote)
+            .arg("name", lint.name)
+            .arg(
+                "level",
+                match level {
+                    LintLevel::Warn => "warn",
+                    _ => "deny",
+                },
+            );
+        diagnostic.notes.push(note.to_string());
+        self.report(diagnostic);
+        Some(severity)
+    }
 
-        // Only mark until end of line if error goes over multiple lines.
-        let max_marker_length = min(
-            line_information.length,
-            column_end - line_information.offset,
-        );
-        let marker = "^".repeat(max_marker_length);
-        result += &format!("{}| {}{}\n", " ".repeat(indentation), marker_start, marker);
-
-        // If error goes over multiple lines, we report this to the user.
-        if max_marker_length < line_information.length {
-            result += &format!(
-                "{}| --> Error continues in next line.\n",
-                " ".repeat(indentation)
+    // Apply a lint attribute found in the source at the given location, e.g.
+    // `// #[allow(nested_block_comment)]`. Unknown lint names are reported.
+    pub fn set_source_lint_level(
+        &self,
+        name: &str,
+        level: LintLevel,
+        line_information: &LineInformation,
+    ) -> Option<Severity> {
+        if name != WARNINGS && find_lint(name).is_none() {
+            return self.report_lint(
+                &UNKNOWN_LINTS,
+                &Message::new(MessageId::UnknownLint).arg("name", name),
+                line_information,
             );
         }
-        result
+        self.source_lint_levels.borrow_mut().push(SourceLintLevel {
+            offset: line_information.offset,
+            name: name.to_string(),
+            level,
+        });
+        None
     }
 
-    fn get_line_content_and_column_offset(&self, offset: usize) -> (String, usize, usize) {
-        // Left boundary of the code line.
-        let mut left = 0;
-        for (idx, c) in self.code.chars().enumerate() {
-            if idx == offset {
-                break;
-            }
-            if c == '\n' {
-                left = idx + 1;
-            }
+    pub fn error_count(&self) -> usize {
+        self.error_count.get()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.warning_count.get()
+    }
+
+    pub fn summary(&self) -> String {
+        let errors = Message::new(MessageId::SummaryErrors).arg("count", self.error_count());
+        let warnings = Message::new(MessageId::SummaryWarnings).arg("count", self.warning_count());
+        Message::new(MessageId::Summary)
+            .arg("errors", errors)
+            .arg("warnings", warnings)
+            .to_string()
+    }
+
+    // Attributes in the source take precedence over the command line. `warnings` attributes only
+    // affect lints that would warn otherwise.
+    fn lint_level_at(&self, lint: &Lint, offset: usize) -> LintLevel {
+        let source_lint_levels = self.source_lint_levels.borrow();
+        let in_effect = |name: &str| {
+            source_lint_levels
+                .iter()
+                .rev()
+                .find(|it| it.offset <= offset && it.name == name)
+                .map(|it| it.level)
+        };
+
+        let level = match in_effect(lint.name) {
+            Some(level) => level,
+            None => self.lint_levels.level(lint),
+        };
+        match (level, in_effect(WARNINGS)) {
+            (LintLevel::Warn, Some(warnings_level)) => warnings_level,
+            (level, _) => level,
         }
+    }
 
-        // The offset where in the line the marked error is located.
-        let column_offset = offset - left;
+    // All suggestions of reported diagnostics, e.g. to be applied by `--fix`.
+    pub fn suggestions(&self) -> Vec<Suggestion> {
+        self.suggestions.borrow().clone()
+    }
 
-        // Right boundary of the code line.
-        let mut right = self.code.len();
-        for (idx, c) in self.code.chars().skip(offset).enumerate() {
-            if c == '\n' {
-                right = offset + idx;
-                break;
-            }
+    // Diagnostics are buffered until the next flush.
+    pub fn report(&self, diagnostic: Diagnostic) {
+        let line_information = &diagnostic.line_information;
+        if line_information.end() > self.code.len() {
+            ice::set_location(line_information.offset.min(self.code.len()));
+            panic!(
+                "Diagnostic span {}..{} exceeds the input of length {}.",
+                line_information.offset,
+                line_information.end(),
+                self.code.len()
+            );
         }
 
-        (self.code[left..right].to_string(), column_offset, right)
+        self.suggestions
+            .borrow_mut()
+            .extend(diagnostic.suggestions.iter().cloned());
+        self.pending.borrow_mut().push(diagnostic);
+    }
+
+    // Emit all buffered diagnostics to the sink, ordered by source position and without
+    // repetitions. Error and warning counts only include diagnostics emitted this way.
+    pub fn flush(&self) {
+        let pending = self.pending.take();
+        let mut sink = self.sink.borrow_mut();
+        for diagnostic in organize(pending, self.max_errors) {
+            match diagnostic.severity {
+                Severity::Error => self.error_count.set(self.error_count.get() + 1),
+                Severity::Warning => self.warning_count.set(self.warning_count.get() + 1),
+                Severity::Note => {}
+            }
+            sink.emit(&diagnostic, &self.code);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error_handling::sink::MemorySink;
 
     fn input() -> String {
         "fn my_function() -> usize {\n    10 + 10\n}  // A function".to_string()
     }
 
-    #[test]
-    fn test_get_error_message_first_token() {
-        let input = input();
-        let error_handler = ErrorHandler::new(&input);
-        let li = LineInformation::new(0, 2);
-
-        let msg = error_handler.get_error_message("An error occurred.", &li);
-
-        assert_eq!(
-            msg,
-            "An error occurred.\n   |\n 1 | fn my_function() -> usize {\n   | ^^\n"
-        )
+    fn message(character: char) -> Message {
+        Message::new(MessageId::UnexpectedCharacter).arg("character", character)
     }
 
     #[test]
-    fn test_get_error_message_first_line() {
+    fn test_diagnostics_reach_sink() {
         let input = input();
-        let error_handler = ErrorHandler::new(&input);
-        let li = LineInformation::new(3, 13);
-
-        let msg = error_handler.get_error_message("An error occurred.", &li);
-
-        assert_eq!(
-            msg,
-            "An error occurred.\n   |\n 1 | fn my_function() -> usize {\n   |    ^^^^^^^^^^^^^\n"
-        )
-    }
-
-    #[test]
-    fn test_get_error_message_first_token_second_line() {
-        let input = input();
-        let error_handler = ErrorHandler::new(&input);
-        let li = LineInformation::new(28, 4);
+        let sink = MemorySink::new();
+        let error_handler = ErrorHandler::new(&input).with_sink(Box::new(sink.clone()));
+        let li = LineInformation::new(0, 2);
 
-        let msg = error_handler.get_error_message("An error occurred.", &li);
+        error_handler.report_error(&message('a'), &li);
+        error_handler.report_lint(&lint::NESTED_BLOCK_COMMENT, &message('b'), &li);
+        assert!(sink.diagnostics().is_empty());
+        error_handler.flush();
 
+        let diagnostics = sink.diagnostics();
+        assert_eq!(diagnostics.len(), 2);
         assert_eq!(
-            msg,
-            "An error occurred.\n   |\n 2 |     10 + 10\n   | ^^^^\n"
-        )
+            diagnostics[0],
+            Diagnostic::from_message(Severity::Error, &message('a'), &li)
+        );
+        assert_eq!(diagnostics[1].severity, Severity::Warning);
+        assert_eq!(diagnostics[1].lint, Some("nested_block_comment"));
     }
 
     #[test]
-    fn test_get_error_message_second_line() {
+    fn test_report_lint_levels() {
         let input = input();
-        let error_handler = ErrorHandler::new(&input);
-        let li = LineInformation::new(35, 1);
+        let mut lint_levels = LintLevels::new();
+        lint_levels.set(WARNINGS, LintLevel::Deny);
+        let error_handler = ErrorHandler::new(&input).with_lint_levels(lint_levels);
+        let li = LineInformation::new(0, 2);
 
-        let msg = error_handler.get_error_message("An error occurred.", &li);
+        let severity = error_handler.report_lint(&lint::NESTED_BLOCK_COMMENT, &message('b'), &li);
 
-        assert_eq!(
-            msg,
-            "An error occurred.\n   |\n 2 |     10 + 10\n   |        ^\n"
-        )
+        assert_eq!(severity, Some(Severity::Error));
+        error_handler.flush();
+        assert_eq!(error_handler.summary(), "1 error, 0 warnings");
     }
 
     #[test]
-    fn test_get_error_message_last_line() {
+    fn test_source_lint_levels() {
         let input = input();
         let error_handler = ErrorHandler::new(&input);
-        let li = LineInformation::new(43, 2);
 
-        let msg = error_handler.get_error_message("An error occurred.", &li);
+        error_handler.set_source_lint_level(
+            "nested_block_comment",
+            LintLevel::Allow,
+            &LineInformation::new(28, 4),
+        );
+        let before = error_handler.report_lint(
+            &lint::NESTED_BLOCK_COMMENT,
+            &message('b'),
+            &LineInformation::new(0, 2),
+        );
+        let after = error_handler.report_lint(
+            &lint::NESTED_BLOCK_COMMENT,
+            &message('b'),
+            &LineInformation::new(43, 2),
+        );
 
-        assert_eq!(
-            msg,
-            "An error occurred.\n   |\n 3 | }  // A function\n   |    ^^\n"
-        )
+        assert_eq!(before, Some(Severity::Warning));
+        assert_eq!(after, None);
+        error_handler.flush();
+        assert_eq!(error_handler.summary(), "0 errors, 1 warning");
     }
 
     #[test]
-    fn test_get_error_message_last_token_last_line() {
+    fn test_unknown_source_lint() {
         let input = input();
         let error_handler = ErrorHandler::new(&input);
-        let li = LineInformation::new(48, 8);
 
-        let msg = error_handler.get_error_message("An error occurred.", &li);
+        let severity = error_handler.set_source_lint_level(
+            "no_such_lint",
+            LintLevel::Allow,
+            &LineInformation::new(0, 2),
+        );
 
-        assert_eq!(
-            msg,
-            "An error occurred.\n   |\n 3 | }  // A function\n   |         ^^^^^^^^\n"
-        )
+        assert_eq!(severity, Some(Severity::Warning));
     }
 
     #[test]
-    fn test_multiple_lines_error() {
+    fn test_flush_counts_only_emitted_errors() {
         let input = input();
-        let error_handler = ErrorHandler::new(&input);
-        let li = LineInformation::new(37, 3);
+        let error_handler = ErrorHandler::new(&input).with_max_errors(Some(1));
 
-        let msg = error_handler.get_error_message("An error occurred.", &li);
+        error_handler.report_error(&message('a'), &LineInformation::new(0, 1));
+        error_handler.report_error(&message('a'), &LineInformation::new(0, 1));
+        error_handler.report_error(&message('b'), &LineInformation::new(5, 1));
+        error_handler.flush();
 
-        assert_eq!(
-            msg,
-            "An error occurred.\n   |\n 2 |     10 + 10\n   |          ^^\n   | --> Error continues in next line.\n"
-        )
+        assert_eq!(error_handler.summary(), "1 error, 0 warnings");
     }
 }
@@ -0,0 +1,173 @@
+use crate::error_handling::diagnostic::{Diagnostic, Severity};
+use crate::error_handling::messages::{Message, MessageId};
+use crate::error_handling::LineInformation;
+
+// Prepare buffered diagnostics for emission: sort them by source position, merge identical
+// diagnostics on adjacent spans into one, drop exact duplicates and, if max_errors is set, stop
+// after that many errors with a note on how many further errors were suppressed.
+pub fn organize(mut diagnostics: Vec<Diagnostic>, max_errors: Option<usize>) -> Vec<Diagnostic> {
+    // The sort is stable, s.t. diagnostics at the same position stay in the order of reporting.
+    diagnostics.sort_by_key(|it| (it.line_information.offset(), it.line_information.length()));
+
+    let mut merged: Vec<Diagnostic> = vec![];
+    for diagnostic in diagnostics {
+        let offset = diagnostic.line_information.offset();
+        let is_duplicate = merged
+            .iter()
+            .rev()
+            .take_while(|it| it.line_information.offset() == offset)
+            .any(|it| is_duplicate(it, &diagnostic));
+        if is_duplicate {
+            continue;
+        }
+        if let Some(previous) = merged.last_mut() {
+            if is_adjacent_repetition(previous, &diagnostic) {
+                let span = &previous.line_information;
+                previous.line_information = LineInformation::new(
+                    span.offset(),
+                    diagnostic.line_information.end() - span.offset(),
+                );
+                previous.suggestions.extend(diagnostic.suggestions);
+                continue;
+            }
+        }
+        merged.push(diagnostic);
+    }
+
+    let max_errors = match max_errors {
+        None => return merged,
+        Some(max_errors) => max_errors,
+    };
+    let mut result = vec![];
+    let mut error_count = 0;
+    let mut suppressed: Option<(usize, LineInformation)> = None;
+    for diagnostic in merged {
+        if let Some((count, _)) = suppressed.as_mut() {
+            if diagnostic.severity == Severity::Error {
+                *count += 1;
+            }
+            continue;
+        }
+        if diagnostic.severity == Severity::Error {
+            if error_count == max_errors {
+                suppressed = Some((1, diagnostic.line_information));
+                continue;
+            }
+            error_count += 1;
+        }
+        result.push(diagnostic);
+    }
+
+    if let Some((count, line_information)) = suppressed {
+        let message = Message::new(MessageId::ErrorsSuppressed)
+            .arg("count", count)
+            .arg("max", max_errors);
+        result.push(Diagnostic::from_message(
+            Severity::Note,
+            &message,
+            &line_information,
+        ));
+    }
+    result
+}
+
+fn is_duplicate(a: &Diagnostic, b: &Diagnostic) -> bool {
+    a.line_information == b.line_information && is_same_report(a, b)
+}
+
+fn is_adjacent_repetition(a: &Diagnostic, b: &Diagnostic) -> bool {
+    a.line_information.end() == b.line_information.offset() && is_same_report(a, b)
+}
+
+fn is_same_report(a: &Diagnostic, b: &Diagnostic) -> bool {
+    a.severity == b.severity
+        && a.message == b.message
+        && a.lint == b.lint
+        && a.labels == b.labels
+        && a.notes == b.notes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(message: &str, offset: usize, length: usize) -> Diagnostic {
+        Diagnostic::new(
+            Severity::Error,
+            message,
+            &LineInformation::new(offset, length),
+        )
+    }
+
+    #[test]
+    fn test_sort_by_position() {
+        let diagnostics = vec![error("b", 5, 1), error("a", 1, 1), error("c", 5, 2)];
+
+        let result = organize(diagnostics, None);
+
+        assert_eq!(
+            result,
+            vec![error("a", 1, 1), error("b", 5, 1), error("c", 5, 2)]
+        );
+    }
+
+    #[test]
+    fn test_merge_adjacent_identical_errors() {
+        let diagnostics = vec![
+            error("Unexpected character '$'.", 4, 1),
+            error("Unexpected character '$'.", 5, 1),
+            error("Unexpected character '$'.", 6, 1),
+            error("Unexpected character '$'.", 9, 1),
+        ];
+
+        let result = organize(diagnostics, None);
+
+        assert_eq!(
+            result,
+            vec![
+                error("Unexpected character '$'.", 4, 3),
+                error("Unexpected character '$'.", 9, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deduplicate() {
+        let diagnostics = vec![error("a", 1, 1), error("b", 1, 1), error("a", 1, 1)];
+
+        let result = organize(diagnostics, None);
+
+        assert_eq!(result, vec![error("a", 1, 1), error("b", 1, 1)]);
+    }
+
+    #[test]
+    fn test_max_errors() {
+        let mut warning = error("w", 3, 1);
+        warning.severity = Severity::Warning;
+        let diagnostics = vec![
+            error("a", 0, 1),
+            warning.clone(),
+            error("b", 2, 1),
+            error("c", 4, 1),
+            error("d", 6, 1),
+        ];
+
+        let result = organize(diagnostics, Some(2));
+
+        assert_eq!(
+            result,
+            vec![
+                error("a", 0, 1),
+                error("b", 2, 1),
+                warning,
+                Diagnostic::from_message(
+                    Severity::Note,
+                    &Message::new(MessageId::ErrorsSuppressed)
+                        .arg("count", 2)
+                        .arg("max", 2),
+                    &LineInformation::new(4, 1)
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,525 @@
+use crate::error_handling::diagnostic::{Diagnostic, Label, Severity, Suggestion};
+use crate::error_handling::fix::apply_suggestion;
+use crate::error_handling::messages::{Message, MessageId};
+use crate::error_handling::LineInformation;
+use std::cmp::{max, min};
+
+const DEFAULT_TAB_WIDTH: usize = 4;
+
+// Renders diagnostics as human readable source snippets with the erroneous span marked.
+pub struct SnippetRenderer {
+    context_lines_before: usize,
+    context_lines_after: usize,
+    tab_width: usize,
+}
+
+impl SnippetRenderer {
+    pub fn new() -> SnippetRenderer {
+        SnippetRenderer {
+            context_lines_before: 0,
+            context_lines_after: 0,
+            tab_width: DEFAULT_TAB_WIDTH,
+        }
+    }
+
+    // Number of source lines shown before and after the marked line of a snippet.
+    pub fn with_context_lines(mut self, before: usize, after: usize) -> SnippetRenderer {
+        self.context_lines_before = before;
+        self.context_lines_after = after;
+        self
+    }
+
+    // Tab stops used when expanding tabs in rendered snippets. A width of 0 is treated as 1.
+    pub fn with_tab_width(mut self, tab_width: usize) -> SnippetRenderer {
+        self.tab_width = max(tab_width, 1);
+        self
+    }
+
+    pub fn render(&self, code: &str, diagnostic: &Diagnostic) -> String {
+        let header = format!("{}: {}", diagnostic.severity, diagnostic.message);
+        let mut result = self.get_error_message_with_notes(
+            code,
+            &header,
+            &diagnostic.line_information,
+            &diagnostic.labels,
+            &diagnostic.notes,
+        );
+        for suggestion in diagnostic.suggestions.iter() {
+            result += &self.get_suggestion_preview(code, suggestion);
+        }
+        result
+    }
+
+    // Render the line of the suggestion as it would look like after applying it. Inserted text is
+    // marked with `+`, replaced text with `~` and removed text with `-`.
+    fn get_suggestion_preview(&self, code: &str, suggestion: &Suggestion) -> String {
+        let span = &suggestion.line_information;
+        let (preview_code, marked, marker) = match (span.length(), suggestion.replacement.len()) {
+            (_, 0) => (code.to_string(), *span, '-'),
+            (length, replacement_length) => (
+                apply_suggestion(code, suggestion),
+                LineInformation::new(span.offset(), replacement_length),
+                if length == 0 { '+' } else { '~' },
+            ),
+        };
+
+        let lines: Vec<&str> = preview_code.split('\n').collect();
+        let (line_index, line_start) = get_line_index_and_start(&preview_code, marked.offset());
+        let code_line = lines[line_index];
+        let number_width = ((line_index + 1).ilog10() + 1) as usize;
+        let indentation = " ".repeat(number_width + 2);
+
+        let column_offset = marked.offset() - line_start;
+        let marked_length = min(marked.length(), code_line.len() - column_offset);
+        let marker_start = self.expand_tabs(&code_line[..column_offset]).len();
+        let marker_end = self
+            .expand_tabs(&code_line[..column_offset + marked_length])
+            .len();
+
+        let help = Message::new(MessageId::HelpTry).arg("suggestion", &suggestion.message);
+        let mut result = format!("{}= {}\n", indentation, help);
+        result += &format!("{}|\n", indentation);
+        result += &format!(" {} | {}\n", line_index + 1, self.expand_tabs(code_line));
+        result += &format!(
+            "{}| {}{}\n",
+            indentation,
+            " ".repeat(marker_start),
+            marker.to_string().repeat(max(marker_end - marker_start, 1))
+        );
+        result
+    }
+
+    #[cfg(test)]
+    fn get_error_message(
+        &self,
+        code: &str,
+        error_msg: &str,
+        line_information: &LineInformation,
+    ) -> String {
+        self.get_error_message_with_notes(code, error_msg, line_information, &[], &[])
+    }
+
+    fn get_error_message_with_notes(
+        &self,
+        code: &str,
+        error_msg: &str,
+        line_information: &LineInformation,
+        labels: &[Label],
+        notes: &[String],
+    ) -> String {
+        let mut result = format!("{error_msg}\n").to_string();
+
+        let lines: Vec<&str> = code.split('\n').collect();
+        let (line_index, line_start) = get_line_index_and_start(code, line_information.offset);
+        let first_line = line_index.saturating_sub(self.context_lines_before);
+        let last_line = min(line_index + self.context_lines_after, lines.len() - 1);
+
+        // Labels on the marked line are shown below its marker, the others below the snippet.
+        let label_line =
+            |label: &Label| get_line_index_and_start(code, label.line_information.offset);
+        let (inline_labels, other_labels): (Vec<&Label>, Vec<&Label>) = labels
+            .iter()
+            .partition(|label| label_line(label).0 == line_index);
+
+        // The gutter has to fit the largest line number that is shown.
+        let largest_line = other_labels
+            .iter()
+            .map(|label| label_line(label).0)
+            .fold(last_line, max);
+        let number_width = ((largest_line + 1).ilog10() + 1) as usize;
+        let indentation = " ".repeat(number_width + 2);
+
+        result += &format!("{}|\n", indentation);
+        for (idx, code_line) in lines
+            .iter()
+            .enumerate()
+            .take(last_line + 1)
+            .skip(first_line)
+        {
+            result += &format!(
+                " {:>number_width$} | {}\n",
+                idx + 1,
+                self.expand_tabs(code_line)
+            );
+            if idx != line_index {
+                continue;
+            }
+
+            result += &self.get_marker(&indentation, code_line, line_start, line_information, '^');
+            result += "\n";
+            for label in inline_labels.iter() {
+                result += &self.get_label(&indentation, code_line, line_start, label);
+            }
+
+            // If error goes over multiple lines, we report this to the user.
+            if line_information.end() > line_start + code_line.len() {
+                result += &format!(
+                    "{}| {}\n",
+                    indentation,
+                    Message::new(MessageId::SnippetContinues)
+                );
+            }
+        }
+        for label in other_labels {
+            let (label_index, label_start) = label_line(label);
+            let code_line = lines[label_index];
+            result += &format!(
+                " {:>number_width$} | {}\n",
+                label_index + 1,
+                self.expand_tabs(code_line)
+            );
+            result += &self.get_label(&indentation, code_line, label_start, label);
+        }
+        for note in notes {
+            result += &format!("{}= {}: {}\n", indentation, Severity::Note, note);
+        }
+        result
+    }
+
+    fn get_label(
+        &self,
+        indentation: &str,
+        code_line: &str,
+        line_start: usize,
+        label: &Label,
+    ) -> String {
+        let marker = self.get_marker(
+            indentation,
+            code_line,
+            line_start,
+            &label.line_information,
+            '-',
+        );
+        format!("{} {}\n", marker, label.message)
+    }
+
+    // Mark the part of span on code_line, which starts at offset line_start, with marker
+    // characters. Only mark until end of line if the span goes over multiple lines.
+    fn get_marker(
+        &self,
+        indentation: &str,
+        code_line: &str,
+        line_start: usize,
+        span: &LineInformation,
+        marker: char,
+    ) -> String {
+        let column_offset = span.offset - line_start;
+        let marked_length = min(span.length, code_line.len() - column_offset);
+
+        // Columns are measured after tab expansion, s.t. the marker lines up with the code.
+        let marker_start = self.expand_tabs(&code_line[..column_offset]).len();
+        let marker_end = self
+            .expand_tabs(&code_line[..column_offset + marked_length])
+            .len();
+        format!(
+            "{}| {}{}",
+            indentation,
+            " ".repeat(marker_start),
+            marker.to_string().repeat(max(marker_end - marker_start, 1))
+        )
+    }
+
+    // Replace tabs by spaces up to the next tab stop.
+    fn expand_tabs(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '\t' => {
+                    let width = self.tab_width - result.len() % self.tab_width;
+                    result += &" ".repeat(width);
+                }
+                _ => result.push(c),
+            }
+        }
+        result
+    }
+}
+
+impl Default for SnippetRenderer {
+    fn default() -> Self {
+        SnippetRenderer::new()
+    }
+}
+
+// Returns the zero-based index of the line containing offset and the offset the line starts at.
+pub fn get_line_index_and_start(code: &str, offset: usize) -> (usize, usize) {
+    let preceding = &code[..offset];
+    let line_index = preceding.matches('\n').count();
+    let line_start = preceding.rfind('\n').map_or(0, |idx| idx + 1);
+    (line_index, line_start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_handling::diagnostic::Applicability;
+
+    fn input() -> String {
+        "fn my_function() -> usize {\n    10 + 10\n}  // A function".to_string()
+    }
+
+    #[test]
+    fn test_get_error_message_first_token() {
+        let input = input();
+        let renderer = SnippetRenderer::new();
+        let li = LineInformation::new(0, 2);
+
+        let msg = renderer.get_error_message(&input, "An error occurred.", &li);
+
+        assert_eq!(
+            msg,
+            "An error occurred.\n   |\n 1 | fn my_function() -> usize {\n   | ^^\n"
+        )
+    }
+
+    #[test]
+    fn test_get_error_message_first_line() {
+        let input = input();
+        let renderer = SnippetRenderer::new();
+        let li = LineInformation::new(3, 13);
+
+        let msg = renderer.get_error_message(&input, "An error occurred.", &li);
+
+        assert_eq!(
+            msg,
+            "An error occurred.\n   |\n 1 | fn my_function() -> usize {\n   |    ^^^^^^^^^^^^^\n"
+        )
+    }
+
+    #[test]
+    fn test_get_error_message_first_token_second_line() {
+        let input = input();
+        let renderer = SnippetRenderer::new();
+        let li = LineInformation::new(28, 4);
+
+        let msg = renderer.get_error_message(&input, "An error occurred.", &li);
+
+        assert_eq!(
+            msg,
+            "An error occurred.\n   |\n 2 |     10 + 10\n   | ^^^^\n"
+        )
+    }
+
+    #[test]
+    fn test_get_error_message_second_line() {
+        let input = input();
+        let renderer = SnippetRenderer::new();
+        let li = LineInformation::new(35, 1);
+
+        let msg = renderer.get_error_message(&input, "An error occurred.", &li);
+
+        assert_eq!(
+            msg,
+            "An error occurred.\n   |\n 2 |     10 + 10\n   |        ^\n"
+        )
+    }
+
+    #[test]
+    fn test_get_error_message_last_line() {
+        let input = input();
+        let renderer = SnippetRenderer::new();
+        let li = LineInformation::new(43, 2);
+
+        let msg = renderer.get_error_message(&input, "An error occurred.", &li);
+
+        assert_eq!(
+            msg,
+            "An error occurred.\n   |\n 3 | }  // A function\n   |    ^^\n"
+        )
+    }
+
+    #[test]
+    fn test_get_error_message_last_token_last_line() {
+        let input = input();
+        let renderer = SnippetRenderer::new();
+        let li = LineInformation::new(48, 8);
+
+        let msg = renderer.get_error_message(&input, "An error occurred.", &li);
+
+        assert_eq!(
+            msg,
+            "An error occurred.\n   |\n 3 | }  // A function\n   |         ^^^^^^^^\n"
+        )
+    }
+
+    #[test]
+    fn test_multiple_lines_error() {
+        let input = input();
+        let renderer = SnippetRenderer::new();
+        let li = LineInformation::new(37, 3);
+
+        let msg = renderer.get_error_message(&input, "An error occurred.", &li);
+
+        assert_eq!(
+            msg,
+            "An error occurred.\n   |\n 2 |     10 + 10\n   |          ^^\n   | --> Error continues in next line.\n"
+        )
+    }
+
+    #[test]
+    fn test_render_insertion_suggestion() {
+        let code = "var x = \"abc;";
+        let diagnostic = Diagnostic::new(
+            Severity::Error,
+            "Unterminated string.",
+            &LineInformation::new(8, 5),
+        )
+        .with_suggestion(Suggestion::new(
+            "closing the string with `\"`",
+            &LineInformation::new(12, 0),
+            "\"",
+            Applicability::MachineApplicable,
+        ));
+
+        let msg = SnippetRenderer::new().render(code, &diagnostic);
+
+        assert_eq!(
+            msg,
+            "error: Unterminated string.\n   |\n 1 | var x = \"abc;\n   |         ^^^^^\n   \
+             = help: try closing the string with `\"`\n   |\n 1 | var x = \"abc\";\n   |             +\n"
+        )
+    }
+
+    #[test]
+    fn test_render_replacement_suggestion() {
+        let code = "if (a = b)";
+        let suggestion = Suggestion::new(
+            "comparing with `==`",
+            &LineInformation::new(6, 1),
+            "==",
+            Applicability::MaybeIncorrect,
+        );
+
+        let msg = SnippetRenderer::new().get_suggestion_preview(code, &suggestion);
+
+        assert_eq!(
+            msg,
+            "   = help: try comparing with `==`\n   |\n 1 | if (a == b)\n   |       ~~\n"
+        )
+    }
+
+    #[test]
+    fn test_render_removal_suggestion() {
+        let code = "var x = 1;;";
+        let suggestion = Suggestion::new(
+            "removing the `;`",
+            &LineInformation::new(10, 1),
+            "",
+            Applicability::MachineApplicable,
+        );
+
+        let msg = SnippetRenderer::new().get_suggestion_preview(code, &suggestion);
+
+        assert_eq!(
+            msg,
+            "   = help: try removing the `;`\n   |\n 1 | var x = 1;;\n   |           -\n"
+        )
+    }
+
+    #[test]
+    fn test_context_lines() {
+        let input = input();
+        let renderer = SnippetRenderer::new().with_context_lines(1, 1);
+        let li = LineInformation::new(35, 1);
+
+        let msg = renderer.get_error_message(&input, "An error occurred.", &li);
+
+        assert_eq!(
+            msg,
+            "An error occurred.\n   |\n 1 | fn my_function() -> usize {\n 2 |     10 + 10\n   |        ^\n 3 | }  // A function\n"
+        )
+    }
+
+    #[test]
+    fn test_context_lines_are_clamped_to_file() {
+        let input = input();
+        let renderer = SnippetRenderer::new().with_context_lines(5, 5);
+        let li = LineInformation::new(0, 2);
+
+        let msg = renderer.get_error_message(&input, "An error occurred.", &li);
+
+        assert_eq!(
+            msg,
+            "An error occurred.\n   |\n 1 | fn my_function() -> usize {\n   | ^^\n 2 |     10 + 10\n 3 | }  // A function\n"
+        )
+    }
+
+    #[test]
+    fn test_context_lines_widen_gutter() {
+        let input = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk".to_string();
+        let renderer = SnippetRenderer::new().with_context_lines(0, 1);
+        let li = LineInformation::new(16, 1);
+
+        let msg = renderer.get_error_message(&input, "An error occurred.", &li);
+
+        assert_eq!(
+            msg,
+            "An error occurred.\n    |\n  9 | i\n    | ^\n 10 | j\n"
+        )
+    }
+
+    #[test]
+    fn test_tabs_are_expanded() {
+        let input = "\tx = 1;\n\t\tab\tc".to_string();
+        let renderer = SnippetRenderer::new();
+        let li = LineInformation::new(10, 4);
+
+        let msg = renderer.get_error_message(&input, "An error occurred.", &li);
+
+        assert_eq!(
+            msg,
+            "An error occurred.\n   |\n 2 |         ab  c\n   |         ^^^^^\n"
+        )
+    }
+
+    #[test]
+    fn test_tab_width() {
+        let input = "\tx = 1;".to_string();
+        let renderer = SnippetRenderer::new().with_tab_width(2);
+        let li = LineInformation::new(1, 1);
+
+        let msg = renderer.get_error_message(&input, "An error occurred.", &li);
+
+        assert_eq!(msg, "An error occurred.\n   |\n 1 |   x = 1;\n   |   ^\n")
+    }
+
+    #[test]
+    fn test_labels() {
+        let code = "var x: int = 1;\nx = \"a\";";
+        let diagnostic = Diagnostic::new(
+            Severity::Error,
+            "Mismatched types.",
+            &LineInformation::new(20, 3),
+        )
+        .with_label(Label::new("declared here", &LineInformation::new(4, 1)))
+        .with_label(Label::new("assigned here", &LineInformation::new(16, 1)));
+
+        let msg = SnippetRenderer::new().render(code, &diagnostic);
+
+        assert_eq!(
+            msg,
+            "error: Mismatched types.\n   |\n 2 | x = \"a\";\n   |     ^^^\n   | - assigned here\n \
+             1 | var x: int = 1;\n   |     - declared here\n"
+        )
+    }
+
+    #[test]
+    fn test_lint_note() {
+        let input = input();
+        let renderer = SnippetRenderer::new();
+        let li = LineInformation::new(0, 2);
+
+        let msg = renderer.get_error_message_with_notes(
+            &input,
+            "A warning.",
+            &li,
+            &[],
+            &["lint `nested_block_comment` is set to warn".to_string()],
+        );
+
+        assert_eq!(
+            msg,
+            "A warning.\n   |\n 1 | fn my_function() -> usize {\n   | ^^\n   = note: lint `nested_block_comment` is set to warn\n"
+        )
+    }
+}
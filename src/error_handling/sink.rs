@@ -0,0 +1,142 @@
+use crate::error_handling::diagnostic::Diagnostic;
+use crate::error_handling::render::{get_line_index_and_start, SnippetRenderer};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::io::Write;
+use std::rc::Rc;
+
+// Receives every diagnostic reported through the ErrorHandler, together with the source code
+// the diagnostic's span refers to.
+pub trait DiagnosticSink {
+    fn emit(&mut self, diagnostic: &Diagnostic, code: &str);
+}
+
+// Renders diagnostics as source snippets for humans.
+pub struct TerminalSink {
+    renderer: SnippetRenderer,
+    out: Box<dyn Write>,
+}
+
+impl TerminalSink {
+    pub fn new(renderer: SnippetRenderer, out: Box<dyn Write>) -> TerminalSink {
+        TerminalSink { renderer, out }
+    }
+
+    pub fn stderr(renderer: SnippetRenderer) -> TerminalSink {
+        TerminalSink::new(renderer, Box::new(std::io::stderr()))
+    }
+}
+
+impl DiagnosticSink for TerminalSink {
+    fn emit(&mut self, diagnostic: &Diagnostic, code: &str) {
+        // There is nowhere left to report a failing diagnostics stream to.
+        let _ = writeln!(self.out, "{}", self.renderer.render(code, diagnostic));
+    }
+}
+
+// Collects diagnostics in memory. Clones share the collected diagnostics, s.t. a clone can be
+// kept to inspect what was reported after the original sink was handed to an ErrorHandler.
+#[derive(Clone, Default)]
+pub struct MemorySink {
+    diagnostics: Rc<RefCell<Vec<Diagnostic>>>,
+}
+
+impl MemorySink {
+    pub fn new() -> MemorySink {
+        MemorySink::default()
+    }
+
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.diagnostics.borrow().clone()
+    }
+}
+
+impl DiagnosticSink for MemorySink {
+    fn emit(&mut self, diagnostic: &Diagnostic, _code: &str) {
+        self.diagnostics.borrow_mut().push(diagnostic.clone());
+    }
+}
+
+// Writes one JSON object per diagnostic and line, for consumption by other tools.
+pub struct JsonSink {
+    out: Box<dyn Write>,
+}
+
+#[derive(Serialize)]
+struct JsonDiagnostic<'a> {
+    #[serde(flatten)]
+    diagnostic: &'a Diagnostic,
+    // One-based line and column of the start of the span.
+    line: usize,
+    column: usize,
+}
+
+impl JsonSink {
+    pub fn new(out: Box<dyn Write>) -> JsonSink {
+        JsonSink { out }
+    }
+
+    pub fn stderr() -> JsonSink {
+        JsonSink::new(Box::new(std::io::stderr()))
+    }
+
+    fn to_json(diagnostic: &Diagnostic, code: &str) -> String {
+        let offset = diagnostic.line_information.offset();
+        let (line_index, line_start) = get_line_index_and_start(code, offset);
+        let json_diagnostic = JsonDiagnostic {
+            diagnostic,
+            line: line_index + 1,
+            column: offset - line_start + 1,
+        };
+        serde_json::to_string(&json_diagnostic).expect("Diagnostics are always serializable.")
+    }
+}
+
+impl DiagnosticSink for JsonSink {
+    fn emit(&mut self, diagnostic: &Diagnostic, code: &str) {
+        // There is nowhere left to report a failing diagnostics stream to.
+        let _ = writeln!(self.out, "{}", JsonSink::to_json(diagnostic, code));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_handling::diagnostic::Severity;
+    use crate::error_handling::LineInformation;
+
+    #[test]
+    fn test_memory_sink_clones_share_diagnostics() {
+        let sink = MemorySink::new();
+        let mut clone = sink.clone();
+        let diagnostic = Diagnostic::new(Severity::Error, "An error.", &LineInformation::new(0, 1));
+
+        clone.emit(&diagnostic, "a");
+
+        assert_eq!(sink.diagnostics(), vec![diagnostic]);
+    }
+
+    #[test]
+    fn test_json_diagnostic() {
+        let mut diagnostic =
+            Diagnostic::new(Severity::Warning, "A warning.", &LineInformation::new(4, 2));
+        diagnostic.lint = Some("nested_block_comment");
+
+        let json = JsonSink::to_json(&diagnostic, "a\nb\n  cd");
+
+        assert_eq!(
+            json,
+            "{\"severity\":\"warning\",\"id\":null,\"message\":\"A warning.\",\"span\":{\"offset\":4,\"length\":2},\
+             \"lint\":\"nested_block_comment\",\"labels\":[],\"notes\":[],\"suggestions\":[],\"line\":3,\"column\":1}"
+        );
+    }
+
+    #[test]
+    fn test_terminal_sink() {
+        let diagnostic = Diagnostic::new(Severity::Error, "An error.", &LineInformation::new(0, 1));
+
+        let rendered = SnippetRenderer::new().render("a", &diagnostic);
+
+        assert_eq!(rendered, "error: An error.\n   |\n 1 | a\n   | ^\n");
+    }
+}
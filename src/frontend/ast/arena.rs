@@ -0,0 +1,199 @@
+// Storage of the AST. Expressions and statements live in one arena and refer to each other by
+// id. Information about nodes, like their span or the type of an expression, is kept in side
+// tables indexed by the same ids instead of in the nodes themselves.
+use crate::error_handling::LineInformation;
+use crate::frontend::ast::{ExprKind, StmtKind};
+use std::fmt::{Debug, Formatter};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExprId(NodeId);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StmtId(NodeId);
+
+impl NodeId {
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl Debug for NodeId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+impl From<ExprId> for NodeId {
+    fn from(id: ExprId) -> NodeId {
+        id.0
+    }
+}
+
+impl From<StmtId> for NodeId {
+    fn from(id: StmtId) -> NodeId {
+        id.0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Expr(ExprKind),
+    Stmt(StmtKind),
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Ast {
+    nodes: Vec<Node>,
+    spans: Vec<LineInformation>,
+    declarations: Vec<StmtId>,
+}
+
+impl Ast {
+    pub fn new() -> Ast {
+        Ast::default()
+    }
+
+    pub fn alloc_expr(&mut self, kind: ExprKind, line_information: LineInformation) -> ExprId {
+        ExprId(self.alloc(Node::Expr(kind), line_information))
+    }
+
+    pub fn alloc_stmt(&mut self, kind: StmtKind, line_information: LineInformation) -> StmtId {
+        StmtId(self.alloc(Node::Stmt(kind), line_information))
+    }
+
+    fn alloc(&mut self, node: Node, line_information: LineInformation) -> NodeId {
+        let id = NodeId(self.nodes.len() as u32);
+        self.nodes.push(node);
+        self.spans.push(line_information);
+        id
+    }
+
+    // Ids are only created by this arena for nodes of their kind, so the lookups cannot fail.
+    pub fn expr(&self, id: ExprId) -> &ExprKind {
+        match &self.nodes[id.0.index()] {
+            Node::Expr(kind) => kind,
+            Node::Stmt(_) => unreachable!("{:?} is a statement", id.0),
+        }
+    }
+
+    pub fn expr_mut(&mut self, id: ExprId) -> &mut ExprKind {
+        match &mut self.nodes[id.0.index()] {
+            Node::Expr(kind) => kind,
+            Node::Stmt(_) => unreachable!("{:?} is a statement", id.0),
+        }
+    }
+
+    pub fn stmt(&self, id: StmtId) -> &StmtKind {
+        match &self.nodes[id.0.index()] {
+            Node::Stmt(kind) => kind,
+            Node::Expr(_) => unreachable!("{:?} is an expression", id.0),
+        }
+    }
+
+    pub fn stmt_mut(&mut self, id: StmtId) -> &mut StmtKind {
+        match &mut self.nodes[id.0.index()] {
+            Node::Stmt(kind) => kind,
+            Node::Expr(_) => unreachable!("{:?} is an expression", id.0),
+        }
+    }
+
+    pub fn span(&self, id: impl Into<NodeId>) -> LineInformation {
+        self.spans[id.into().index()]
+    }
+
+    pub fn set_span(&mut self, id: impl Into<NodeId>, line_information: LineInformation) {
+        self.spans[id.into().index()] = line_information;
+    }
+
+    // Number of nodes, which is the size side tables need to have.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    // The top-level declarations of the program.
+    pub fn declarations(&self) -> &[StmtId] {
+        &self.declarations
+    }
+
+    pub fn push_declaration(&mut self, declaration: StmtId) {
+        self.declarations.push(declaration);
+    }
+
+    pub fn set_declarations(&mut self, declarations: Vec<StmtId>) {
+        self.declarations = declarations;
+    }
+}
+
+// A side table with optional information per node, e.g. the types of expressions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeMap<T> {
+    entries: Vec<Option<T>>,
+}
+
+impl<T> NodeMap<T> {
+    pub fn new() -> NodeMap<T> {
+        NodeMap { entries: vec![] }
+    }
+
+    pub fn insert(&mut self, id: impl Into<NodeId>, value: T) -> Option<T> {
+        let index = id.into().index();
+        if index >= self.entries.len() {
+            self.entries.resize_with(index + 1, || None);
+        }
+        self.entries[index].replace(value)
+    }
+
+    pub fn get(&self, id: impl Into<NodeId>) -> Option<&T> {
+        self.entries.get(id.into().index())?.as_ref()
+    }
+
+    pub fn contains(&self, id: impl Into<NodeId>) -> bool {
+        self.get(id).is_some()
+    }
+}
+
+impl<T> Default for NodeMap<T> {
+    fn default() -> NodeMap<T> {
+        NodeMap::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::ast::Literal;
+
+    #[test]
+    fn test_ids_are_shared_by_expressions_and_statements() {
+        let mut ast = Ast::new();
+        let literal = ast.alloc_expr(
+            ExprKind::Literal(Literal::Integer(1)),
+            LineInformation::new(6, 1),
+        );
+        let print = ast.alloc_stmt(StmtKind::Print(literal), LineInformation::new(0, 8));
+        assert_ne!(NodeId::from(literal), NodeId::from(print));
+        assert_eq!(ast.len(), 2);
+        assert_eq!(ast.stmt(print), &StmtKind::Print(literal));
+        assert_eq!(ast.span(literal), LineInformation::new(6, 1));
+    }
+
+    #[test]
+    fn test_node_map() {
+        let mut ast = Ast::new();
+        let a = ast.alloc_expr(ExprKind::This, LineInformation::new(0, 4));
+        let b = ast.alloc_expr(ExprKind::This, LineInformation::new(5, 4));
+        let mut names = NodeMap::new();
+        assert_eq!(names.insert(b, "b"), None);
+        assert_eq!(names.get(a), None);
+        assert_eq!(names.get(b), Some(&"b"));
+        assert_eq!(names.insert(b, "c"), Some("b"));
+        assert!(names.contains(b));
+    }
+}
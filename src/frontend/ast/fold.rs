@@ -0,0 +1,357 @@
+// Transformation of the AST. Passes implement `Fold` and override the methods for the nodes they
+// rewrite. The default methods call the matching `walk_*` function, which folds the children of a
+// node and stores the rebuilt node under its old id. Passes replace a node by returning the id of
+// another one, e.g. a newly allocated node.
+use crate::frontend::ast::{
+    Ast, ClassDecl, ExprId, ExprKind, FunctionDecl, Identifier, Literal, Parameter, StmtId,
+    StmtKind, TypeExpr, TypeExprKind, TypeParameter, VarDecl,
+};
+
+pub trait Fold: Sized {
+    fn fold_stmt(&mut self, ast: &mut Ast, stmt: StmtId) -> StmtId {
+        walk_stmt(self, ast, stmt)
+    }
+
+    fn fold_expr(&mut self, ast: &mut Ast, expr: ExprId) -> ExprId {
+        walk_expr(self, ast, expr)
+    }
+
+    fn fold_var_decl(&mut self, ast: &mut Ast, decl: VarDecl) -> VarDecl {
+        walk_var_decl(self, ast, decl)
+    }
+
+    fn fold_function(&mut self, ast: &mut Ast, function: FunctionDecl) -> FunctionDecl {
+        walk_function(self, ast, function)
+    }
+
+    fn fold_class(&mut self, ast: &mut Ast, class: ClassDecl) -> ClassDecl {
+        walk_class(self, ast, class)
+    }
+
+    fn fold_parameter(&mut self, parameter: Parameter) -> Parameter {
+        walk_parameter(self, parameter)
+    }
+
+    fn fold_type_parameter(&mut self, type_parameter: TypeParameter) -> TypeParameter {
+        walk_type_parameter(self, type_parameter)
+    }
+
+    fn fold_type_expr(&mut self, type_expr: TypeExpr) -> TypeExpr {
+        walk_type_expr(self, type_expr)
+    }
+
+    fn fold_literal(&mut self, literal: Literal) -> Literal {
+        literal
+    }
+
+    fn fold_identifier(&mut self, identifier: Identifier) -> Identifier {
+        identifier
+    }
+}
+
+// Fold all declarations of a program.
+pub fn fold_declarations<F: Fold>(folder: &mut F, ast: &mut Ast) {
+    let declarations = ast
+        .declarations()
+        .to_vec()
+        .into_iter()
+        .map(|declaration| folder.fold_stmt(ast, declaration))
+        .collect();
+    ast.set_declarations(declarations);
+}
+
+// The node is taken out of the arena while its children are folded.
+pub fn walk_stmt<F: Fold>(folder: &mut F, ast: &mut Ast, stmt: StmtId) -> StmtId {
+    let kind = match std::mem::replace(ast.stmt_mut(stmt), StmtKind::Error) {
+        StmtKind::Expression(expr) => StmtKind::Expression(folder.fold_expr(ast, expr)),
+        StmtKind::Print(expr) => StmtKind::Print(folder.fold_expr(ast, expr)),
+        StmtKind::Var(decl) => StmtKind::Var(folder.fold_var_decl(ast, decl)),
+        StmtKind::Function(function) => StmtKind::Function(folder.fold_function(ast, function)),
+        StmtKind::Class(class) => StmtKind::Class(folder.fold_class(ast, class)),
+        StmtKind::Block(statements) => StmtKind::Block(
+            statements
+                .into_iter()
+                .map(|stmt| folder.fold_stmt(ast, stmt))
+                .collect(),
+        ),
+        StmtKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => StmtKind::If {
+            condition: folder.fold_expr(ast, condition),
+            then_branch: folder.fold_stmt(ast, then_branch),
+            else_branch: else_branch.map(|it| folder.fold_stmt(ast, it)),
+        },
+        StmtKind::While { condition, body } => StmtKind::While {
+            condition: folder.fold_expr(ast, condition),
+            body: folder.fold_stmt(ast, body),
+        },
+        StmtKind::For {
+            initializer,
+            condition,
+            increment,
+            body,
+        } => StmtKind::For {
+            initializer: initializer.map(|it| folder.fold_stmt(ast, it)),
+            condition: condition.map(|it| folder.fold_expr(ast, it)),
+            increment: increment.map(|it| folder.fold_expr(ast, it)),
+            body: folder.fold_stmt(ast, body),
+        },
+        StmtKind::Return(value) => StmtKind::Return(value.map(|it| folder.fold_expr(ast, it))),
+        StmtKind::Error => StmtKind::Error,
+    };
+    *ast.stmt_mut(stmt) = kind;
+    stmt
+}
+
+pub fn walk_expr<F: Fold>(folder: &mut F, ast: &mut Ast, expr: ExprId) -> ExprId {
+    let kind = match std::mem::replace(ast.expr_mut(expr), ExprKind::Error) {
+        ExprKind::Literal(literal) => ExprKind::Literal(folder.fold_literal(literal)),
+        ExprKind::Grouping(inner) => ExprKind::Grouping(folder.fold_expr(ast, inner)),
+        ExprKind::Unary { operator, operand } => ExprKind::Unary {
+            operator,
+            operand: folder.fold_expr(ast, operand),
+        },
+        ExprKind::Binary {
+            left,
+            operator,
+            right,
+        } => ExprKind::Binary {
+            left: folder.fold_expr(ast, left),
+            operator,
+            right: folder.fold_expr(ast, right),
+        },
+        ExprKind::Logical {
+            left,
+            operator,
+            right,
+        } => ExprKind::Logical {
+            left: folder.fold_expr(ast, left),
+            operator,
+            right: folder.fold_expr(ast, right),
+        },
+        ExprKind::Variable(identifier) => ExprKind::Variable(folder.fold_identifier(identifier)),
+        ExprKind::Assign {
+            target,
+            operator,
+            value,
+        } => ExprKind::Assign {
+            target: folder.fold_identifier(target),
+            operator,
+            value: folder.fold_expr(ast, value),
+        },
+        ExprKind::Call { callee, arguments } => ExprKind::Call {
+            callee: folder.fold_expr(ast, callee),
+            arguments: arguments
+                .into_iter()
+                .map(|argument| folder.fold_expr(ast, argument))
+                .collect(),
+        },
+        ExprKind::Get { object, name } => ExprKind::Get {
+            object: folder.fold_expr(ast, object),
+            name: folder.fold_identifier(name),
+        },
+        ExprKind::Set {
+            object,
+            name,
+            operator,
+            value,
+        } => ExprKind::Set {
+            object: folder.fold_expr(ast, object),
+            name: folder.fold_identifier(name),
+            operator,
+            value: folder.fold_expr(ast, value),
+        },
+        ExprKind::This => ExprKind::This,
+        ExprKind::Super { method } => ExprKind::Super {
+            method: folder.fold_identifier(method),
+        },
+        ExprKind::Conversion { target, value } => ExprKind::Conversion {
+            target: folder.fold_type_expr(target),
+            value: folder.fold_expr(ast, value),
+        },
+        ExprKind::Error => ExprKind::Error,
+    };
+    *ast.expr_mut(expr) = kind;
+    expr
+}
+
+pub fn walk_var_decl<F: Fold>(folder: &mut F, ast: &mut Ast, decl: VarDecl) -> VarDecl {
+    let VarDecl {
+        mutable,
+        name,
+        type_annotation,
+        initializer,
+        line_information,
+    } = decl;
+    VarDecl {
+        mutable,
+        name: folder.fold_identifier(name),
+        type_annotation: type_annotation.map(|it| folder.fold_type_expr(it)),
+        initializer: initializer.map(|it| folder.fold_expr(ast, it)),
+        line_information,
+    }
+}
+
+pub fn walk_function<F: Fold>(
+    folder: &mut F,
+    ast: &mut Ast,
+    function: FunctionDecl,
+) -> FunctionDecl {
+    let FunctionDecl {
+        name,
+        type_parameters,
+        parameters,
+        return_type,
+        body,
+        line_information,
+    } = function;
+    FunctionDecl {
+        name: folder.fold_identifier(name),
+        type_parameters: type_parameters
+            .into_iter()
+            .map(|it| folder.fold_type_parameter(it))
+            .collect(),
+        parameters: parameters
+            .into_iter()
+            .map(|parameter| folder.fold_parameter(parameter))
+            .collect(),
+        return_type: return_type.map(|it| folder.fold_type_expr(it)),
+        body: body
+            .into_iter()
+            .map(|stmt| folder.fold_stmt(ast, stmt))
+            .collect(),
+        line_information,
+    }
+}
+
+pub fn walk_class<F: Fold>(folder: &mut F, ast: &mut Ast, class: ClassDecl) -> ClassDecl {
+    let ClassDecl {
+        name,
+        type_parameters,
+        superclass,
+        fields,
+        methods,
+    } = class;
+    ClassDecl {
+        name: folder.fold_identifier(name),
+        type_parameters: type_parameters
+            .into_iter()
+            .map(|it| folder.fold_type_parameter(it))
+            .collect(),
+        superclass: superclass.map(|it| folder.fold_identifier(it)),
+        fields: fields
+            .into_iter()
+            .map(|field| folder.fold_var_decl(ast, field))
+            .collect(),
+        methods: methods
+            .into_iter()
+            .map(|method| folder.fold_function(ast, method))
+            .collect(),
+    }
+}
+
+pub fn walk_parameter<F: Fold>(folder: &mut F, parameter: Parameter) -> Parameter {
+    let Parameter {
+        name,
+        type_annotation,
+        line_information,
+    } = parameter;
+    Parameter {
+        name: folder.fold_identifier(name),
+        type_annotation: folder.fold_type_expr(type_annotation),
+        line_information,
+    }
+}
+
+pub fn walk_type_parameter<F: Fold>(
+    folder: &mut F,
+    type_parameter: TypeParameter,
+) -> TypeParameter {
+    let TypeParameter { name, bound } = type_parameter;
+    TypeParameter {
+        name: folder.fold_identifier(name),
+        bound: bound.map(|it| folder.fold_type_expr(it)),
+    }
+}
+
+pub fn walk_type_expr<F: Fold>(folder: &mut F, type_expr: TypeExpr) -> TypeExpr {
+    let kind = match type_expr.kind {
+        TypeExprKind::Int => TypeExprKind::Int,
+        TypeExprKind::Float => TypeExprKind::Float,
+        TypeExprKind::Bool => TypeExprKind::Bool,
+        TypeExprKind::String => TypeExprKind::String,
+        TypeExprKind::Named(name) => TypeExprKind::Named(folder.fold_identifier(name)),
+        TypeExprKind::Generic { name, arguments } => TypeExprKind::Generic {
+            name: folder.fold_identifier(name),
+            arguments: arguments
+                .into_iter()
+                .map(|it| folder.fold_type_expr(it))
+                .collect(),
+        },
+        TypeExprKind::Nullable(inner) => {
+            TypeExprKind::Nullable(Box::new(folder.fold_type_expr(*inner)))
+        }
+        TypeExprKind::Function {
+            parameters,
+            return_type,
+        } => TypeExprKind::Function {
+            parameters: parameters
+                .into_iter()
+                .map(|it| folder.fold_type_expr(it))
+                .collect(),
+            return_type: return_type.map(|it| Box::new(folder.fold_type_expr(*it))),
+        },
+    };
+    TypeExpr::new(kind, type_expr.line_information)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_handling::ErrorHandler;
+    use crate::frontend::ast::{sexpr, BinaryOperator};
+    use crate::frontend::parser::parse;
+    use crate::frontend::scanner::scan;
+
+    // Folds additions of integer literals, bottom up.
+    struct ConstantFolder;
+
+    impl Fold for ConstantFolder {
+        fn fold_expr(&mut self, ast: &mut Ast, expr: ExprId) -> ExprId {
+            let expr = walk_expr(self, ast, expr);
+            let ExprKind::Binary {
+                left,
+                operator: BinaryOperator::Add,
+                right,
+            } = ast.expr(expr)
+            else {
+                return expr;
+            };
+            match (ast.expr(*left), ast.expr(*right)) {
+                (
+                    ExprKind::Literal(Literal::Integer(a)),
+                    ExprKind::Literal(Literal::Integer(b)),
+                ) => {
+                    let sum = ExprKind::Literal(Literal::Integer(a + b));
+                    ast.alloc_expr(sum, ast.span(expr))
+                }
+                _ => expr,
+            }
+        }
+    }
+
+    #[test]
+    fn test_constant_folding() {
+        let input = "fun f(): int { return 1 + 2 + 3 + a; }\nprint g(4 + 5);";
+        let error_handler = ErrorHandler::new(input);
+        let tokens = scan(input, &error_handler).unwrap();
+        let mut program = parse(tokens, &error_handler).unwrap();
+        fold_declarations(&mut ConstantFolder, &mut program);
+        assert_eq!(
+            sexpr::print(&program),
+            "(fun f ():int (return (+ 6 a)))\n(print (call g 9))\n"
+        );
+    }
+}
@@ -0,0 +1,316 @@
+// JSON rendering of the AST for external tools. The schema is independent of how the AST is
+// represented internally. Every change to it has to increase SCHEMA_VERSION.
+//
+// The document is `{"version": 6, "declarations": [...]}`. Every node is an object with a `kind`
+// and a `span` of the form `{"offset": 0, "length": 1}`, plus fields depending on its kind. Absent
+// optional children are `null`. The `operator` of assignments is `null` unless they are compound
+// assignments like `a += 1`. Nullable types wrap the `type` that also admits `nil`. Function
+// types have `parameters` and a `return_type`, which is `null` for functions that do not return a
+// value. Generic functions and classes list their `type_parameters`, each with a `name` and a
+// `bound`, and generic types have the `name` of the class and its type `arguments`. Conversions
+// like `int(x)` have the `type` converted to and the converted `value`.
+use crate::frontend::ast::{
+    Ast, ClassDecl, ExprId, ExprKind, FunctionDecl, Identifier, Literal, StmtId, StmtKind,
+    TypeExpr, TypeExprKind, TypeParameter, VarDecl,
+};
+use serde_json::{json, Value};
+
+pub const SCHEMA_VERSION: u32 = 6;
+
+pub fn to_json(ast: &Ast) -> Value {
+    json!({
+        "version": SCHEMA_VERSION,
+        "declarations": statement_list(ast, ast.declarations()),
+    })
+}
+
+fn statement_list(ast: &Ast, statements: &[StmtId]) -> Vec<Value> {
+    statements.iter().map(|it| statement(ast, *it)).collect()
+}
+
+fn expression_list(ast: &Ast, expressions: &[ExprId]) -> Vec<Value> {
+    expressions.iter().map(|it| expression(ast, *it)).collect()
+}
+
+fn statement(ast: &Ast, stmt: StmtId) -> Value {
+    let span = json!(ast.span(stmt));
+    match ast.stmt(stmt) {
+        StmtKind::Expression(expr) => {
+            json!({"kind": "expression", "span": span, "expression": expression(ast, *expr)})
+        }
+        StmtKind::Print(expr) => {
+            json!({"kind": "print", "span": span, "expression": expression(ast, *expr)})
+        }
+        StmtKind::Var(decl) => var_declaration(ast, decl, span),
+        StmtKind::Function(function) => function_declaration(ast, function, span),
+        StmtKind::Class(class) => class_declaration(ast, class, span),
+        StmtKind::Block(statements) => json!({
+            "kind": "block",
+            "span": span,
+            "statements": statement_list(ast, statements),
+        }),
+        StmtKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => json!({
+            "kind": "if",
+            "span": span,
+            "condition": expression(ast, *condition),
+            "then": statement(ast, *then_branch),
+            "else": else_branch.as_ref().map(|it| statement(ast, *it)),
+        }),
+        StmtKind::While { condition, body } => json!({
+            "kind": "while",
+            "span": span,
+            "condition": expression(ast, *condition),
+            "body": statement(ast, *body),
+        }),
+        StmtKind::For {
+            initializer,
+            condition,
+            increment,
+            body,
+        } => json!({
+            "kind": "for",
+            "span": span,
+            "initializer": initializer.as_ref().map(|it| statement(ast, *it)),
+            "condition": condition.as_ref().map(|it| expression(ast, *it)),
+            "increment": increment.as_ref().map(|it| expression(ast, *it)),
+            "body": statement(ast, *body),
+        }),
+        StmtKind::Return(value) => {
+            json!({"kind": "return", "span": span, "value": value.as_ref().map(|it| expression(ast, *it))})
+        }
+        StmtKind::Error => json!({"kind": "error", "span": span}),
+    }
+}
+
+fn expression(ast: &Ast, expr: ExprId) -> Value {
+    let span = json!(ast.span(expr));
+    match ast.expr(expr) {
+        ExprKind::Literal(literal) => {
+            let (literal_type, value) = match literal {
+                Literal::Integer(value) => ("integer", json!(value)),
+                Literal::Float(value) => ("float", json!(value)),
+                Literal::String(value) => ("string", json!(value)),
+                Literal::Bool(value) => ("bool", json!(value)),
+                Literal::Nil => ("nil", Value::Null),
+            };
+            json!({"kind": "literal", "span": span, "type": literal_type, "value": value})
+        }
+        ExprKind::Grouping(inner) => {
+            json!({"kind": "grouping", "span": span, "expression": expression(ast, *inner)})
+        }
+        ExprKind::Unary { operator, operand } => json!({
+            "kind": "unary",
+            "span": span,
+            "operator": operator.to_string(),
+            "operand": expression(ast, *operand),
+        }),
+        ExprKind::Binary {
+            left,
+            operator,
+            right,
+        } => json!({
+            "kind": "binary",
+            "span": span,
+            "operator": operator.to_string(),
+            "left": expression(ast, *left),
+            "right": expression(ast, *right),
+        }),
+        ExprKind::Logical {
+            left,
+            operator,
+            right,
+        } => json!({
+            "kind": "logical",
+            "span": span,
+            "operator": operator.to_string(),
+            "left": expression(ast, *left),
+            "right": expression(ast, *right),
+        }),
+        ExprKind::Variable(name) => {
+            json!({"kind": "variable", "span": span, "name": identifier(name)})
+        }
+        ExprKind::Assign {
+            target,
+            operator,
+            value,
+        } => json!({
+            "kind": "assign",
+            "span": span,
+            "target": identifier(target),
+            "operator": operator.map(|it| it.to_string()),
+            "value": expression(ast, *value),
+        }),
+        ExprKind::Call { callee, arguments } => json!({
+            "kind": "call",
+            "span": span,
+            "callee": expression(ast, *callee),
+            "arguments": expression_list(ast, arguments),
+        }),
+        ExprKind::Get { object, name } => json!({
+            "kind": "get",
+            "span": span,
+            "object": expression(ast, *object),
+            "name": identifier(name),
+        }),
+        ExprKind::Set {
+            object,
+            name,
+            operator,
+            value,
+        } => json!({
+            "kind": "set",
+            "span": span,
+            "object": expression(ast, *object),
+            "name": identifier(name),
+            "operator": operator.map(|it| it.to_string()),
+            "value": expression(ast, *value),
+        }),
+        ExprKind::This => json!({"kind": "this", "span": span}),
+        ExprKind::Super { method } => {
+            json!({"kind": "super", "span": span, "method": identifier(method)})
+        }
+        ExprKind::Conversion { target, value } => json!({
+            "kind": "conversion",
+            "span": span,
+            "type": type_expr(target),
+            "value": expression(ast, *value),
+        }),
+        ExprKind::Error => json!({"kind": "error", "span": span}),
+    }
+}
+
+fn var_declaration(ast: &Ast, decl: &VarDecl, span: Value) -> Value {
+    json!({
+        "kind": "var",
+        "span": span,
+        "mutable": decl.mutable,
+        "name": identifier(&decl.name),
+        "type": decl.type_annotation.as_ref().map(type_expr),
+        "initializer": decl.initializer.as_ref().map(|it| expression(ast, *it)),
+    })
+}
+
+fn function_declaration(ast: &Ast, function: &FunctionDecl, span: Value) -> Value {
+    let parameters: Vec<Value> = function
+        .parameters
+        .iter()
+        .map(|parameter| {
+            json!({
+                "name": identifier(&parameter.name),
+                "type": type_expr(&parameter.type_annotation),
+            })
+        })
+        .collect();
+    json!({
+        "kind": "function",
+        "span": span,
+        "name": identifier(&function.name),
+        "type_parameters": type_parameters(&function.type_parameters),
+        "parameters": parameters,
+        "return_type": function.return_type.as_ref().map(type_expr),
+        "body": statement_list(ast, &function.body),
+    })
+}
+
+fn class_declaration(ast: &Ast, class: &ClassDecl, span: Value) -> Value {
+    let fields: Vec<Value> = class
+        .fields
+        .iter()
+        .map(|field| var_declaration(ast, field, json!(field.line_information)))
+        .collect();
+    let methods: Vec<Value> = class
+        .methods
+        .iter()
+        .map(|method| function_declaration(ast, method, json!(method.line_information)))
+        .collect();
+    json!({
+        "kind": "class",
+        "span": span,
+        "name": identifier(&class.name),
+        "type_parameters": type_parameters(&class.type_parameters),
+        "superclass": class.superclass.as_ref().map(identifier),
+        "fields": fields,
+        "methods": methods,
+    })
+}
+
+fn type_parameters(type_parameters: &[TypeParameter]) -> Vec<Value> {
+    type_parameters
+        .iter()
+        .map(|it| json!({"name": identifier(&it.name), "bound": it.bound.as_ref().map(type_expr)}))
+        .collect()
+}
+
+fn type_expr(annotation: &TypeExpr) -> Value {
+    let span = json!(annotation.line_information);
+    match &annotation.kind {
+        TypeExprKind::Named(name) => json!({"kind": "named", "span": span, "name": name.name}),
+        TypeExprKind::Generic { name, arguments } => json!({
+            "kind": "generic",
+            "span": span,
+            "name": name.name,
+            "arguments": arguments.iter().map(type_expr).collect::<Vec<_>>(),
+        }),
+        TypeExprKind::Nullable(inner) => {
+            json!({"kind": "nullable", "span": span, "type": type_expr(inner)})
+        }
+        TypeExprKind::Function {
+            parameters,
+            return_type,
+        } => json!({
+            "kind": "function",
+            "span": span,
+            "parameters": parameters.iter().map(type_expr).collect::<Vec<_>>(),
+            "return_type": return_type.as_deref().map(type_expr),
+        }),
+        _ => json!({"kind": annotation.to_string(), "span": span}),
+    }
+}
+
+fn identifier(identifier: &Identifier) -> Value {
+    json!({"name": identifier.name, "span": identifier.line_information})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_handling::ErrorHandler;
+    use crate::frontend::parser::parse;
+    use crate::frontend::scanner::scan;
+
+    #[test]
+    fn test_to_json() {
+        let input = "val a: int = -1;";
+        let error_handler = ErrorHandler::new(input);
+        let tokens = scan(input, &error_handler).unwrap();
+        let json = to_json(&parse(tokens, &error_handler).unwrap());
+        assert_eq!(
+            json,
+            json!({
+                "version": 6,
+                "declarations": [{
+                    "kind": "var",
+                    "span": {"offset": 0, "length": 16},
+                    "mutable": false,
+                    "name": {"name": "a", "span": {"offset": 4, "length": 1}},
+                    "type": {"kind": "int", "span": {"offset": 7, "length": 3}},
+                    "initializer": {
+                        "kind": "unary",
+                        "span": {"offset": 13, "length": 2},
+                        "operator": "-",
+                        "operand": {
+                            "kind": "literal",
+                            "span": {"offset": 14, "length": 1},
+                            "type": "integer",
+                            "value": 1,
+                        },
+                    },
+                }],
+            })
+        );
+    }
+}
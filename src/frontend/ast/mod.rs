@@ -0,0 +1,318 @@
+pub mod arena;
+pub mod fold;
+pub mod json;
+pub mod sexpr;
+pub mod tree;
+pub mod visit;
+pub mod visit_mut;
+
+pub use crate::frontend::ast::arena::{Ast, ExprId, NodeId, NodeMap, StmtId};
+
+use crate::error_handling::LineInformation;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Identifier {
+    pub name: String,
+    pub line_information: LineInformation,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Literal {
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Bool(bool),
+    Nil,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnaryOperator {
+    Negate,
+    Not,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogicalOperator {
+    And,
+    Or,
+}
+
+// Operators are displayed as they are written in code.
+impl Display for UnaryOperator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            UnaryOperator::Negate => "-",
+            UnaryOperator::Not => "!",
+        };
+        write!(f, "{text}")
+    }
+}
+
+impl Display for BinaryOperator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            BinaryOperator::Add => "+",
+            BinaryOperator::Subtract => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+            BinaryOperator::Equal => "==",
+            BinaryOperator::NotEqual => "!=",
+            BinaryOperator::Less => "<",
+            BinaryOperator::LessEqual => "<=",
+            BinaryOperator::Greater => ">",
+            BinaryOperator::GreaterEqual => ">=",
+        };
+        write!(f, "{text}")
+    }
+}
+
+impl Display for LogicalOperator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            LogicalOperator::And => "and",
+            LogicalOperator::Or => "or",
+        };
+        write!(f, "{text}")
+    }
+}
+
+// Literals are displayed as they are written in code.
+impl Display for Literal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Literal::Integer(value) => write!(f, "{value}"),
+            Literal::Float(value) => write!(f, "{value:?}"),
+            Literal::String(value) => write!(f, "\"{value}\""),
+            Literal::Bool(value) => write!(f, "{value}"),
+            Literal::Nil => write!(f, "nil"),
+        }
+    }
+}
+
+// Expressions and statements are stored in an `Ast` and refer to their children by id.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExprKind {
+    Literal(Literal),
+    Grouping(ExprId),
+    Unary {
+        operator: UnaryOperator,
+        operand: ExprId,
+    },
+    Binary {
+        left: ExprId,
+        operator: BinaryOperator,
+        right: ExprId,
+    },
+    Logical {
+        left: ExprId,
+        operator: LogicalOperator,
+        right: ExprId,
+    },
+    Variable(Identifier),
+    // The operator is set for compound assignments like `a += 1`.
+    Assign {
+        target: Identifier,
+        operator: Option<BinaryOperator>,
+        value: ExprId,
+    },
+    Call {
+        callee: ExprId,
+        arguments: Vec<ExprId>,
+    },
+    Get {
+        object: ExprId,
+        name: Identifier,
+    },
+    Set {
+        object: ExprId,
+        name: Identifier,
+        operator: Option<BinaryOperator>,
+        value: ExprId,
+    },
+    This,
+    Super {
+        method: Identifier,
+    },
+    // An explicit conversion between numeric types like `int(x)`, where the target is the type
+    // keyword.
+    Conversion {
+        target: TypeExpr,
+        value: ExprId,
+    },
+    // Stands in for an expression that could not be parsed.
+    Error,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeExpr {
+    pub kind: TypeExprKind,
+    pub line_information: LineInformation,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TypeExprKind {
+    Int,
+    Float,
+    Bool,
+    String,
+    // A class name or type parameter.
+    Named(Identifier),
+    // A generic class applied to type arguments, e.g. `List<int>`.
+    Generic {
+        name: Identifier,
+        arguments: Vec<TypeExpr>,
+    },
+    // `T?`, which also admits `nil`.
+    Nullable(Box<TypeExpr>),
+    // E.g. `fun(int, string): bool`. Like declared functions, function types without return type
+    // do not return a value.
+    Function {
+        parameters: Vec<TypeExpr>,
+        return_type: Option<Box<TypeExpr>>,
+    },
+}
+
+impl TypeExpr {
+    pub fn new(kind: TypeExprKind, line_information: LineInformation) -> TypeExpr {
+        TypeExpr {
+            kind,
+            line_information,
+        }
+    }
+}
+
+// Types are displayed as they are written in code.
+impl Display for TypeExpr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            TypeExprKind::Int => write!(f, "int"),
+            TypeExprKind::Float => write!(f, "float"),
+            TypeExprKind::Bool => write!(f, "bool"),
+            TypeExprKind::String => write!(f, "string"),
+            TypeExprKind::Named(name) => write!(f, "{}", name.name),
+            TypeExprKind::Generic { name, arguments } => {
+                let arguments: Vec<String> = arguments.iter().map(|it| it.to_string()).collect();
+                write!(f, "{}<{}>", name.name, arguments.join(", "))
+            }
+            TypeExprKind::Nullable(inner) => write!(f, "{inner}?"),
+            TypeExprKind::Function {
+                parameters,
+                return_type,
+            } => {
+                let parameters: Vec<String> = parameters.iter().map(|it| it.to_string()).collect();
+                write!(f, "fun({})", parameters.join(", "))?;
+                match return_type {
+                    Some(return_type) => write!(f, ": {return_type}"),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StmtKind {
+    Expression(ExprId),
+    Print(ExprId),
+    Var(VarDecl),
+    Function(FunctionDecl),
+    Class(ClassDecl),
+    Block(Vec<StmtId>),
+    If {
+        condition: ExprId,
+        then_branch: StmtId,
+        else_branch: Option<StmtId>,
+    },
+    While {
+        condition: ExprId,
+        body: StmtId,
+    },
+    For {
+        initializer: Option<StmtId>,
+        condition: Option<ExprId>,
+        increment: Option<ExprId>,
+        body: StmtId,
+    },
+    Return(Option<ExprId>),
+    // Stands in for a statement that could not be parsed.
+    Error,
+}
+
+// A `var` or `val` declaration. Only `var` bindings can be assigned to after their declaration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VarDecl {
+    pub mutable: bool,
+    pub name: Identifier,
+    pub type_annotation: Option<TypeExpr>,
+    pub initializer: Option<ExprId>,
+    pub line_information: LineInformation,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Parameter {
+    pub name: Identifier,
+    pub type_annotation: TypeExpr,
+    pub line_information: LineInformation,
+}
+
+// A type parameter of a generic function or class, e.g. `T` or `T: Animal`. The type arguments
+// it is instantiated with have to be assignable to its bound.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeParameter {
+    pub name: Identifier,
+    pub bound: Option<TypeExpr>,
+}
+
+impl Display for TypeParameter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.bound {
+            Some(bound) => write!(f, "{}: {bound}", self.name.name),
+            None => write!(f, "{}", self.name.name),
+        }
+    }
+}
+
+// The type parameters of a declaration as written after its name, e.g. `<T, U: A>`, or nothing if
+// it is not generic.
+pub fn type_parameter_list(type_parameters: &[TypeParameter]) -> String {
+    if type_parameters.is_empty() {
+        return String::new();
+    }
+    let type_parameters: Vec<String> = type_parameters.iter().map(|it| it.to_string()).collect();
+    format!("<{}>", type_parameters.join(", "))
+}
+
+// A function or method. Functions without return type do not return a value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionDecl {
+    pub name: Identifier,
+    pub type_parameters: Vec<TypeParameter>,
+    pub parameters: Vec<Parameter>,
+    pub return_type: Option<TypeExpr>,
+    pub body: Vec<StmtId>,
+    pub line_information: LineInformation,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassDecl {
+    pub name: Identifier,
+    pub type_parameters: Vec<TypeParameter>,
+    pub superclass: Option<Identifier>,
+    pub fields: Vec<VarDecl>,
+    pub methods: Vec<FunctionDecl>,
+}
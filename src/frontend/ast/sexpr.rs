@@ -0,0 +1,233 @@
+// Compact S-expression rendering of the AST, with one line per declaration.
+use crate::frontend::ast::{
+    type_parameter_list, Ast, ClassDecl, ExprId, ExprKind, FunctionDecl, Identifier, StmtId,
+    StmtKind, TypeExpr, VarDecl,
+};
+
+pub fn print(ast: &Ast) -> String {
+    ast.declarations()
+        .iter()
+        .map(|declaration| statement(ast, *declaration) + "\n")
+        .collect()
+}
+
+pub fn statement(ast: &Ast, stmt: StmtId) -> String {
+    match ast.stmt(stmt) {
+        StmtKind::Expression(expr) => list("expr", [expression(ast, *expr)]),
+        StmtKind::Print(expr) => list("print", [expression(ast, *expr)]),
+        StmtKind::Var(decl) => var_declaration(ast, decl),
+        StmtKind::Function(function) => function_declaration(ast, "fun", function),
+        StmtKind::Class(class) => class_declaration(ast, class),
+        StmtKind::Block(statements) => {
+            list("block", statements.iter().map(|it| statement(ast, *it)))
+        }
+        StmtKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => list(
+            "if",
+            [expression(ast, *condition), statement(ast, *then_branch)]
+                .into_iter()
+                .chain(else_branch.iter().map(|it| statement(ast, *it))),
+        ),
+        StmtKind::While { condition, body } => list(
+            "while",
+            [expression(ast, *condition), statement(ast, *body)],
+        ),
+        StmtKind::For {
+            initializer,
+            condition,
+            increment,
+            body,
+        } => list(
+            "for",
+            [
+                initializer
+                    .as_ref()
+                    .map_or("_".to_string(), |it| statement(ast, *it)),
+                condition
+                    .as_ref()
+                    .map_or("_".to_string(), |it| expression(ast, *it)),
+                increment
+                    .as_ref()
+                    .map_or("_".to_string(), |it| expression(ast, *it)),
+                statement(ast, *body),
+            ],
+        ),
+        StmtKind::Return(value) => list("return", value.iter().map(|it| expression(ast, *it))),
+        StmtKind::Error => "<error>".to_string(),
+    }
+}
+
+pub fn expression(ast: &Ast, expr: ExprId) -> String {
+    match ast.expr(expr) {
+        ExprKind::Literal(literal) => literal.to_string(),
+        ExprKind::Grouping(inner) => list("group", [expression(ast, *inner)]),
+        ExprKind::Unary { operator, operand } => {
+            list(&operator.to_string(), [expression(ast, *operand)])
+        }
+        ExprKind::Binary {
+            left,
+            operator,
+            right,
+        } => list(
+            &operator.to_string(),
+            [expression(ast, *left), expression(ast, *right)],
+        ),
+        ExprKind::Logical {
+            left,
+            operator,
+            right,
+        } => list(
+            &operator.to_string(),
+            [expression(ast, *left), expression(ast, *right)],
+        ),
+        ExprKind::Variable(identifier) => identifier.name.clone(),
+        ExprKind::Assign {
+            target,
+            operator,
+            value,
+        } => list(
+            &format!("{}=", operator.map_or(String::new(), |it| it.to_string())),
+            [target.name.clone(), expression(ast, *value)],
+        ),
+        ExprKind::Call { callee, arguments } => list(
+            "call",
+            [expression(ast, *callee)]
+                .into_iter()
+                .chain(arguments.iter().map(|it| expression(ast, *it))),
+        ),
+        ExprKind::Get { object, name } => list(".", [expression(ast, *object), name.name.clone()]),
+        ExprKind::Set {
+            object,
+            name,
+            operator,
+            value,
+        } => list(
+            &format!(".{}=", operator.map_or(String::new(), |it| it.to_string())),
+            [
+                expression(ast, *object),
+                name.name.clone(),
+                expression(ast, *value),
+            ],
+        ),
+        ExprKind::This => "this".to_string(),
+        ExprKind::Super { method } => list("super", [method.name.clone()]),
+        ExprKind::Conversion { target, value } => {
+            list(&target.to_string(), [expression(ast, *value)])
+        }
+        ExprKind::Error => "<error>".to_string(),
+    }
+}
+
+fn var_declaration(ast: &Ast, decl: &VarDecl) -> String {
+    let keyword = match decl.mutable {
+        true => "var",
+        false => "val",
+    };
+    list(
+        keyword,
+        [binding(&decl.name, decl.type_annotation.as_ref())]
+            .into_iter()
+            .chain(decl.initializer.iter().map(|it| expression(ast, *it))),
+    )
+}
+
+// Functions are rendered as `(fun name<T> (a:int b:T):int body...)`.
+fn function_declaration(ast: &Ast, keyword: &str, function: &FunctionDecl) -> String {
+    let parameters: Vec<String> = function
+        .parameters
+        .iter()
+        .map(|parameter| binding(&parameter.name, Some(&parameter.type_annotation)))
+        .collect();
+    let mut signature = format!("({})", parameters.join(" "));
+    if let Some(return_type) = &function.return_type {
+        signature += &format!(":{return_type}");
+    }
+    let name = function.name.name.clone() + &type_parameter_list(&function.type_parameters);
+    list(
+        keyword,
+        [name, signature]
+            .into_iter()
+            .chain(function.body.iter().map(|it| statement(ast, *it))),
+    )
+}
+
+fn class_declaration(ast: &Ast, class: &ClassDecl) -> String {
+    let superclass = class
+        .superclass
+        .iter()
+        .flat_map(|superclass| ["<".to_string(), superclass.name.clone()]);
+    let name = class.name.name.clone() + &type_parameter_list(&class.type_parameters);
+    list(
+        "class",
+        [name]
+            .into_iter()
+            .chain(superclass)
+            .chain(class.fields.iter().map(|field| var_declaration(ast, field)))
+            .chain(
+                class
+                    .methods
+                    .iter()
+                    .map(|method| function_declaration(ast, "method", method)),
+            ),
+    )
+}
+
+fn binding(name: &Identifier, type_annotation: Option<&TypeExpr>) -> String {
+    match type_annotation {
+        None => name.name.clone(),
+        Some(type_annotation) => format!("{}:{}", name.name, type_annotation),
+    }
+}
+
+fn list(head: &str, items: impl IntoIterator<Item = String>) -> String {
+    let mut result = format!("({head}");
+    for item in items {
+        result.push(' ');
+        result.push_str(&item);
+    }
+    result.push(')');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_handling::ErrorHandler;
+    use crate::frontend::parser::parse;
+    use crate::frontend::scanner::scan;
+
+    fn print_program(input: &str) -> String {
+        let error_handler = ErrorHandler::new(input);
+        let tokens = scan(input, &error_handler).unwrap();
+        print(&parse(tokens, &error_handler).unwrap())
+    }
+
+    #[test]
+    fn test_print_declarations() {
+        let input = "var a: int = 1 + 2;\nval b = \"x\";\n\
+                     fun f(x: int, y: float): bool { return x < y; }\n\
+                     class B < A { var x: int; get(): int { return this.x; } }\n\
+                     class Box<T: A> { map<U>(f: fun(T): U): Box<U> {} }";
+        assert_eq!(
+            print_program(input),
+            "(var a:int (+ 1 2))\n\
+             (val b \"x\")\n\
+             (fun f (x:int y:float):bool (return (< x y)))\n\
+             (class B < A (var x:int) (method get ():int (return (. this x))))\n\
+             (class Box<T: A> (method map<U> (f:fun(T): U):Box<U>))\n"
+        );
+    }
+
+    #[test]
+    fn test_print_statements() {
+        let input = "if (a) print 1; else { f(); }\nfor (;;) while (b) return;";
+        assert_eq!(
+            print_program(input),
+            "(if a (print 1) (block (expr (call f))))\n\
+             (for _ _ _ (while b (return)))\n"
+        );
+    }
+}
@@ -0,0 +1,307 @@
+// Indented rendering of the AST, with one node per line followed by its span.
+use crate::error_handling::LineInformation;
+use crate::frontend::ast::{
+    type_parameter_list, Ast, BinaryOperator, ClassDecl, ExprId, ExprKind, FunctionDecl, StmtId,
+    StmtKind, VarDecl,
+};
+
+pub fn print(ast: &Ast) -> String {
+    let mut printer = TreePrinter {
+        ast,
+        output: String::new(),
+        depth: 0,
+    };
+    for declaration in ast.declarations() {
+        printer.statement(None, *declaration);
+    }
+    printer.output
+}
+
+struct TreePrinter<'a> {
+    ast: &'a Ast,
+    output: String,
+    depth: usize,
+}
+
+impl TreePrinter<'_> {
+    // Children whose position does not tell what they are, like the parts of a `for` loop, are
+    // labeled with their role.
+    fn line(&mut self, role: Option<&str>, label: &str, line_information: &LineInformation) {
+        self.output += &"  ".repeat(self.depth);
+        if let Some(role) = role {
+            self.output += &format!("{role}: ");
+        }
+        self.output += &format!(
+            "{} [{}..{}]\n",
+            label,
+            line_information.offset(),
+            line_information.end()
+        );
+    }
+
+    fn nested(&mut self, print: impl FnOnce(&mut Self)) {
+        self.depth += 1;
+        print(self);
+        self.depth -= 1;
+    }
+
+    fn statement(&mut self, role: Option<&str>, stmt: StmtId) {
+        let ast = self.ast;
+        let li = &ast.span(stmt);
+        match ast.stmt(stmt) {
+            StmtKind::Expression(expr) => {
+                self.line(role, "Expression", li);
+                self.nested(|it| it.expression(None, *expr));
+            }
+            StmtKind::Print(expr) => {
+                self.line(role, "Print", li);
+                self.nested(|it| it.expression(None, *expr));
+            }
+            StmtKind::Var(decl) => self.var_declaration(role, decl, li),
+            StmtKind::Function(function) => self.function("Function", role, function, li),
+            StmtKind::Class(class) => self.class_declaration(role, class, li),
+            StmtKind::Block(statements) => {
+                self.line(role, "Block", li);
+                self.nested(|it| {
+                    for stmt in statements {
+                        it.statement(None, *stmt);
+                    }
+                });
+            }
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.line(role, "If", li);
+                self.nested(|it| {
+                    it.expression(Some("condition"), *condition);
+                    it.statement(Some("then"), *then_branch);
+                    if let Some(else_branch) = else_branch {
+                        it.statement(Some("else"), *else_branch);
+                    }
+                });
+            }
+            StmtKind::While { condition, body } => {
+                self.line(role, "While", li);
+                self.nested(|it| {
+                    it.expression(Some("condition"), *condition);
+                    it.statement(Some("body"), *body);
+                });
+            }
+            StmtKind::For {
+                initializer,
+                condition,
+                increment,
+                body,
+            } => {
+                self.line(role, "For", li);
+                self.nested(|it| {
+                    if let Some(initializer) = initializer {
+                        it.statement(Some("initializer"), *initializer);
+                    }
+                    if let Some(condition) = condition {
+                        it.expression(Some("condition"), *condition);
+                    }
+                    if let Some(increment) = increment {
+                        it.expression(Some("increment"), *increment);
+                    }
+                    it.statement(Some("body"), *body);
+                });
+            }
+            StmtKind::Return(value) => {
+                self.line(role, "Return", li);
+                self.nested(|it| {
+                    if let Some(value) = value {
+                        it.expression(None, *value);
+                    }
+                });
+            }
+            StmtKind::Error => self.line(role, "Error", li),
+        }
+    }
+
+    fn var_declaration(&mut self, role: Option<&str>, decl: &VarDecl, li: &LineInformation) {
+        let mut label = match decl.mutable {
+            true => format!("Var {}", decl.name.name),
+            false => format!("Val {}", decl.name.name),
+        };
+        if let Some(type_annotation) = &decl.type_annotation {
+            label += &format!(": {type_annotation}");
+        }
+        self.line(role, &label, li);
+        self.nested(|it| {
+            if let Some(initializer) = &decl.initializer {
+                it.expression(None, *initializer);
+            }
+        });
+    }
+
+    fn function(
+        &mut self,
+        kind: &str,
+        role: Option<&str>,
+        function: &FunctionDecl,
+        li: &LineInformation,
+    ) {
+        let parameters: Vec<String> = function
+            .parameters
+            .iter()
+            .map(|parameter| format!("{}: {}", parameter.name.name, parameter.type_annotation))
+            .collect();
+        let mut label = format!(
+            "{} {}{}({})",
+            kind,
+            function.name.name,
+            type_parameter_list(&function.type_parameters),
+            parameters.join(", ")
+        );
+        if let Some(return_type) = &function.return_type {
+            label += &format!(": {return_type}");
+        }
+        self.line(role, &label, li);
+        self.nested(|it| {
+            for stmt in &function.body {
+                it.statement(None, *stmt);
+            }
+        });
+    }
+
+    fn class_declaration(&mut self, role: Option<&str>, class: &ClassDecl, li: &LineInformation) {
+        let mut label = format!(
+            "Class {}{}",
+            class.name.name,
+            type_parameter_list(&class.type_parameters)
+        );
+        if let Some(superclass) = &class.superclass {
+            label += &format!(" < {}", superclass.name);
+        }
+        self.line(role, &label, li);
+        self.nested(|it| {
+            for field in &class.fields {
+                it.var_declaration(Some("field"), field, &field.line_information);
+            }
+            for method in &class.methods {
+                it.function("Method", None, method, &method.line_information);
+            }
+        });
+    }
+
+    fn expression(&mut self, role: Option<&str>, expr: ExprId) {
+        let ast = self.ast;
+        let li = &ast.span(expr);
+        match ast.expr(expr) {
+            ExprKind::Literal(literal) => self.line(role, &format!("Literal {literal}"), li),
+            ExprKind::Grouping(inner) => {
+                self.line(role, "Grouping", li);
+                self.nested(|it| it.expression(None, *inner));
+            }
+            ExprKind::Unary { operator, operand } => {
+                self.line(role, &format!("Unary {operator}"), li);
+                self.nested(|it| it.expression(None, *operand));
+            }
+            ExprKind::Binary {
+                left,
+                operator,
+                right,
+            } => {
+                self.line(role, &format!("Binary {operator}"), li);
+                self.nested(|it| {
+                    it.expression(None, *left);
+                    it.expression(None, *right);
+                });
+            }
+            ExprKind::Logical {
+                left,
+                operator,
+                right,
+            } => {
+                self.line(role, &format!("Logical {operator}"), li);
+                self.nested(|it| {
+                    it.expression(None, *left);
+                    it.expression(None, *right);
+                });
+            }
+            ExprKind::Variable(identifier) => {
+                self.line(role, &format!("Variable {}", identifier.name), li)
+            }
+            ExprKind::Assign {
+                target,
+                operator,
+                value,
+            } => {
+                let label = format!("Assign{} {}", compound(operator), target.name);
+                self.line(role, &label, li);
+                self.nested(|it| it.expression(None, *value));
+            }
+            ExprKind::Call { callee, arguments } => {
+                self.line(role, "Call", li);
+                self.nested(|it| {
+                    it.expression(Some("callee"), *callee);
+                    for argument in arguments {
+                        it.expression(None, *argument);
+                    }
+                });
+            }
+            ExprKind::Get { object, name } => {
+                self.line(role, &format!("Get {}", name.name), li);
+                self.nested(|it| it.expression(None, *object));
+            }
+            ExprKind::Set {
+                object,
+                name,
+                operator,
+                value,
+            } => {
+                let label = format!("Set{} {}", compound(operator), name.name);
+                self.line(role, &label, li);
+                self.nested(|it| {
+                    it.expression(Some("object"), *object);
+                    it.expression(Some("value"), *value);
+                });
+            }
+            ExprKind::This => self.line(role, "This", li),
+            ExprKind::Super { method } => self.line(role, &format!("Super {}", method.name), li),
+            ExprKind::Conversion { target, value } => {
+                self.line(role, &format!("Conversion {target}"), li);
+                self.nested(|it| it.expression(None, *value));
+            }
+            ExprKind::Error => self.line(role, "Error", li),
+        }
+    }
+}
+
+// The operator of a compound assignment as shown after `Assign` and `Set`, e.g. ` +=`.
+fn compound(operator: &Option<BinaryOperator>) -> String {
+    operator.map_or(String::new(), |it| format!(" {it}="))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_handling::ErrorHandler;
+    use crate::frontend::parser::parse;
+    use crate::frontend::scanner::scan;
+
+    #[test]
+    fn test_print() {
+        let input = "fun f(a: int): int {\n  for (;a < 3;) a = a + 1;\n  return a;\n}";
+        let error_handler = ErrorHandler::new(input);
+        let tokens = scan(input, &error_handler).unwrap();
+        assert_eq!(
+            print(&parse(tokens, &error_handler).unwrap()),
+            "Function f(a: int): int [0..61]\n  \
+               For [23..47]\n    \
+                 condition: Binary < [29..34]\n      \
+                   Variable a [29..30]\n      \
+                   Literal 3 [33..34]\n    \
+                 body: Expression [37..47]\n      \
+                   Assign a [37..46]\n        \
+                     Binary + [41..46]\n          \
+                       Variable a [41..42]\n          \
+                       Literal 1 [45..46]\n  \
+               Return [50..59]\n    \
+                 Variable a [57..58]\n"
+        );
+    }
+}
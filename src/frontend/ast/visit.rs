@@ -0,0 +1,348 @@
+// Read-only traversal of the AST. Passes implement `Visitor` and override the methods for the
+// nodes they are interested in. The default methods call the matching `walk_*` function, which
+// visits the children of a node. Overriding methods call it themselves to keep descending.
+use crate::frontend::ast::{
+    Ast, ClassDecl, ExprId, ExprKind, FunctionDecl, Identifier, Literal, Parameter, StmtId,
+    StmtKind, TypeExpr, TypeExprKind, TypeParameter, VarDecl,
+};
+
+pub trait Visitor<'ast>: Sized {
+    // The arena the visited nodes are stored in.
+    fn ast(&self) -> &'ast Ast;
+
+    fn visit_stmt(&mut self, stmt: StmtId) {
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: ExprId) {
+        walk_expr(self, expr);
+    }
+
+    fn visit_var_decl(&mut self, decl: &'ast VarDecl) {
+        walk_var_decl(self, decl);
+    }
+
+    fn visit_function(&mut self, function: &'ast FunctionDecl) {
+        walk_function(self, function);
+    }
+
+    fn visit_class(&mut self, class: &'ast ClassDecl) {
+        walk_class(self, class);
+    }
+
+    fn visit_parameter(&mut self, parameter: &'ast Parameter) {
+        walk_parameter(self, parameter);
+    }
+
+    fn visit_type_parameter(&mut self, type_parameter: &'ast TypeParameter) {
+        walk_type_parameter(self, type_parameter);
+    }
+
+    fn visit_type_expr(&mut self, type_expr: &'ast TypeExpr) {
+        walk_type_expr(self, type_expr);
+    }
+
+    fn visit_literal(&mut self, _literal: &'ast Literal) {}
+
+    fn visit_identifier(&mut self, _identifier: &'ast Identifier) {}
+}
+
+pub fn walk_stmt<'ast, V: Visitor<'ast>>(visitor: &mut V, stmt: StmtId) {
+    match visitor.ast().stmt(stmt) {
+        StmtKind::Expression(expr) | StmtKind::Print(expr) => visitor.visit_expr(*expr),
+        StmtKind::Var(decl) => visitor.visit_var_decl(decl),
+        StmtKind::Function(function) => visitor.visit_function(function),
+        StmtKind::Class(class) => visitor.visit_class(class),
+        StmtKind::Block(statements) => {
+            for stmt in statements {
+                visitor.visit_stmt(*stmt);
+            }
+        }
+        StmtKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            visitor.visit_expr(*condition);
+            visitor.visit_stmt(*then_branch);
+            if let Some(else_branch) = else_branch {
+                visitor.visit_stmt(*else_branch);
+            }
+        }
+        StmtKind::While { condition, body } => {
+            visitor.visit_expr(*condition);
+            visitor.visit_stmt(*body);
+        }
+        StmtKind::For {
+            initializer,
+            condition,
+            increment,
+            body,
+        } => {
+            if let Some(initializer) = initializer {
+                visitor.visit_stmt(*initializer);
+            }
+            if let Some(condition) = condition {
+                visitor.visit_expr(*condition);
+            }
+            if let Some(increment) = increment {
+                visitor.visit_expr(*increment);
+            }
+            visitor.visit_stmt(*body);
+        }
+        StmtKind::Return(value) => {
+            if let Some(value) = value {
+                visitor.visit_expr(*value);
+            }
+        }
+        StmtKind::Error => {}
+    }
+}
+
+pub fn walk_expr<'ast, V: Visitor<'ast>>(visitor: &mut V, expr: ExprId) {
+    match visitor.ast().expr(expr) {
+        ExprKind::Literal(literal) => visitor.visit_literal(literal),
+        ExprKind::Grouping(inner) => visitor.visit_expr(*inner),
+        ExprKind::Unary {
+            operator: _,
+            operand,
+        } => visitor.visit_expr(*operand),
+        ExprKind::Binary {
+            left,
+            operator: _,
+            right,
+        }
+        | ExprKind::Logical {
+            left,
+            operator: _,
+            right,
+        } => {
+            visitor.visit_expr(*left);
+            visitor.visit_expr(*right);
+        }
+        ExprKind::Variable(identifier) => visitor.visit_identifier(identifier),
+        ExprKind::Assign { target, value, .. } => {
+            visitor.visit_identifier(target);
+            visitor.visit_expr(*value);
+        }
+        ExprKind::Call { callee, arguments } => {
+            visitor.visit_expr(*callee);
+            for argument in arguments {
+                visitor.visit_expr(*argument);
+            }
+        }
+        ExprKind::Get { object, name } => {
+            visitor.visit_expr(*object);
+            visitor.visit_identifier(name);
+        }
+        ExprKind::Set {
+            object,
+            name,
+            value,
+            ..
+        } => {
+            visitor.visit_expr(*object);
+            visitor.visit_identifier(name);
+            visitor.visit_expr(*value);
+        }
+        ExprKind::This | ExprKind::Error => {}
+        ExprKind::Super { method } => visitor.visit_identifier(method),
+        ExprKind::Conversion { target, value } => {
+            visitor.visit_type_expr(target);
+            visitor.visit_expr(*value);
+        }
+    }
+}
+
+pub fn walk_var_decl<'ast, V: Visitor<'ast>>(visitor: &mut V, decl: &'ast VarDecl) {
+    let VarDecl {
+        mutable: _,
+        name,
+        type_annotation,
+        initializer,
+        line_information: _,
+    } = decl;
+    visitor.visit_identifier(name);
+    if let Some(type_annotation) = type_annotation {
+        visitor.visit_type_expr(type_annotation);
+    }
+    if let Some(initializer) = initializer {
+        visitor.visit_expr(*initializer);
+    }
+}
+
+pub fn walk_function<'ast, V: Visitor<'ast>>(visitor: &mut V, function: &'ast FunctionDecl) {
+    let FunctionDecl {
+        name,
+        type_parameters,
+        parameters,
+        return_type,
+        body,
+        line_information: _,
+    } = function;
+    visitor.visit_identifier(name);
+    for type_parameter in type_parameters {
+        visitor.visit_type_parameter(type_parameter);
+    }
+    for parameter in parameters {
+        visitor.visit_parameter(parameter);
+    }
+    if let Some(return_type) = return_type {
+        visitor.visit_type_expr(return_type);
+    }
+    for stmt in body {
+        visitor.visit_stmt(*stmt);
+    }
+}
+
+pub fn walk_class<'ast, V: Visitor<'ast>>(visitor: &mut V, class: &'ast ClassDecl) {
+    let ClassDecl {
+        name,
+        type_parameters,
+        superclass,
+        fields,
+        methods,
+    } = class;
+    visitor.visit_identifier(name);
+    for type_parameter in type_parameters {
+        visitor.visit_type_parameter(type_parameter);
+    }
+    if let Some(superclass) = superclass {
+        visitor.visit_identifier(superclass);
+    }
+    for field in fields {
+        visitor.visit_var_decl(field);
+    }
+    for method in methods {
+        visitor.visit_function(method);
+    }
+}
+
+pub fn walk_parameter<'ast, V: Visitor<'ast>>(visitor: &mut V, parameter: &'ast Parameter) {
+    let Parameter {
+        name,
+        type_annotation,
+        line_information: _,
+    } = parameter;
+    visitor.visit_identifier(name);
+    visitor.visit_type_expr(type_annotation);
+}
+
+pub fn walk_type_parameter<'ast, V: Visitor<'ast>>(
+    visitor: &mut V,
+    type_parameter: &'ast TypeParameter,
+) {
+    let TypeParameter { name, bound } = type_parameter;
+    visitor.visit_identifier(name);
+    if let Some(bound) = bound {
+        visitor.visit_type_expr(bound);
+    }
+}
+
+pub fn walk_type_expr<'ast, V: Visitor<'ast>>(visitor: &mut V, type_expr: &'ast TypeExpr) {
+    match &type_expr.kind {
+        TypeExprKind::Int | TypeExprKind::Float | TypeExprKind::Bool | TypeExprKind::String => {}
+        TypeExprKind::Named(name) => visitor.visit_identifier(name),
+        TypeExprKind::Generic { name, arguments } => {
+            visitor.visit_identifier(name);
+            for argument in arguments {
+                visitor.visit_type_expr(argument);
+            }
+        }
+        TypeExprKind::Nullable(inner) => visitor.visit_type_expr(inner),
+        TypeExprKind::Function {
+            parameters,
+            return_type,
+        } => {
+            for parameter in parameters {
+                visitor.visit_type_expr(parameter);
+            }
+            if let Some(return_type) = return_type {
+                visitor.visit_type_expr(return_type);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_handling::ErrorHandler;
+    use crate::frontend::parser::parse;
+    use crate::frontend::scanner::scan;
+
+    // Collects the names of all identifiers in the order they are visited.
+    struct IdentifierCollector<'ast> {
+        ast: &'ast Ast,
+        names: Vec<&'ast str>,
+    }
+
+    impl<'ast> Visitor<'ast> for IdentifierCollector<'ast> {
+        fn ast(&self) -> &'ast Ast {
+            self.ast
+        }
+
+        fn visit_identifier(&mut self, identifier: &'ast Identifier) {
+            self.names.push(&identifier.name);
+        }
+    }
+
+    // Counts function calls, but does not descend into classes.
+    struct CallCounter<'ast> {
+        ast: &'ast Ast,
+        calls: usize,
+    }
+
+    impl<'ast> Visitor<'ast> for CallCounter<'ast> {
+        fn ast(&self) -> &'ast Ast {
+            self.ast
+        }
+
+        fn visit_expr(&mut self, expr: ExprId) {
+            if let ExprKind::Call { .. } = self.ast.expr(expr) {
+                self.calls += 1;
+            }
+            walk_expr(self, expr);
+        }
+
+        fn visit_class(&mut self, _class: &'ast ClassDecl) {}
+    }
+
+    fn parse_program(input: &str) -> Ast {
+        let error_handler = ErrorHandler::new(input);
+        let tokens = scan(input, &error_handler).unwrap();
+        parse(tokens, &error_handler).unwrap()
+    }
+
+    #[test]
+    fn test_visits_every_identifier() {
+        let program = parse_program(
+            "class B < A { var x: C; m(p: D): E { return super.n(this.y); } }\n\
+             for (var i: int = f; i < g; i = h) print j;",
+        );
+        let mut collector = IdentifierCollector {
+            ast: &program,
+            names: vec![],
+        };
+        for stmt in program.declarations() {
+            collector.visit_stmt(*stmt);
+        }
+        assert_eq!(
+            collector.names,
+            ["B", "A", "x", "C", "m", "p", "D", "E", "n", "y", "i", "f", "i", "g", "i", "h", "j"]
+        );
+    }
+
+    #[test]
+    fn test_overridden_methods_control_descent() {
+        let program = parse_program("f(g(1));\nclass A { m() { h(); } }\nif (a) { i(); }");
+        let mut counter = CallCounter {
+            ast: &program,
+            calls: 0,
+        };
+        for stmt in program.declarations() {
+            counter.visit_stmt(*stmt);
+        }
+        assert_eq!(counter.calls, 3);
+    }
+}
@@ -0,0 +1,306 @@
+// In-place traversal of the AST. Works like `Visitor`, but passes may modify the nodes they visit.
+use crate::frontend::ast::{
+    Ast, ClassDecl, ExprId, ExprKind, FunctionDecl, Identifier, Literal, Parameter, StmtId,
+    StmtKind, TypeExpr, TypeExprKind, TypeParameter, VarDecl,
+};
+
+pub trait VisitorMut: Sized {
+    fn visit_stmt_mut(&mut self, ast: &mut Ast, stmt: StmtId) {
+        walk_stmt_mut(self, ast, stmt);
+    }
+
+    fn visit_expr_mut(&mut self, ast: &mut Ast, expr: ExprId) {
+        walk_expr_mut(self, ast, expr);
+    }
+
+    fn visit_var_decl_mut(&mut self, ast: &mut Ast, decl: &mut VarDecl) {
+        walk_var_decl_mut(self, ast, decl);
+    }
+
+    fn visit_function_mut(&mut self, ast: &mut Ast, function: &mut FunctionDecl) {
+        walk_function_mut(self, ast, function);
+    }
+
+    fn visit_class_mut(&mut self, ast: &mut Ast, class: &mut ClassDecl) {
+        walk_class_mut(self, ast, class);
+    }
+
+    fn visit_parameter_mut(&mut self, parameter: &mut Parameter) {
+        walk_parameter_mut(self, parameter);
+    }
+
+    fn visit_type_parameter_mut(&mut self, type_parameter: &mut TypeParameter) {
+        walk_type_parameter_mut(self, type_parameter);
+    }
+
+    fn visit_type_expr_mut(&mut self, type_expr: &mut TypeExpr) {
+        walk_type_expr_mut(self, type_expr);
+    }
+
+    fn visit_literal_mut(&mut self, _literal: &mut Literal) {}
+
+    fn visit_identifier_mut(&mut self, _identifier: &mut Identifier) {}
+}
+
+// Nodes are taken out of the arena while their children are visited, s.t. both can be modified.
+pub fn walk_stmt_mut<V: VisitorMut>(visitor: &mut V, ast: &mut Ast, stmt: StmtId) {
+    let mut kind = std::mem::replace(ast.stmt_mut(stmt), StmtKind::Error);
+    match &mut kind {
+        StmtKind::Expression(expr) | StmtKind::Print(expr) => visitor.visit_expr_mut(ast, *expr),
+        StmtKind::Var(decl) => visitor.visit_var_decl_mut(ast, decl),
+        StmtKind::Function(function) => visitor.visit_function_mut(ast, function),
+        StmtKind::Class(class) => visitor.visit_class_mut(ast, class),
+        StmtKind::Block(statements) => {
+            for stmt in statements {
+                visitor.visit_stmt_mut(ast, *stmt);
+            }
+        }
+        StmtKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            visitor.visit_expr_mut(ast, *condition);
+            visitor.visit_stmt_mut(ast, *then_branch);
+            if let Some(else_branch) = else_branch {
+                visitor.visit_stmt_mut(ast, *else_branch);
+            }
+        }
+        StmtKind::While { condition, body } => {
+            visitor.visit_expr_mut(ast, *condition);
+            visitor.visit_stmt_mut(ast, *body);
+        }
+        StmtKind::For {
+            initializer,
+            condition,
+            increment,
+            body,
+        } => {
+            if let Some(initializer) = initializer {
+                visitor.visit_stmt_mut(ast, *initializer);
+            }
+            if let Some(condition) = condition {
+                visitor.visit_expr_mut(ast, *condition);
+            }
+            if let Some(increment) = increment {
+                visitor.visit_expr_mut(ast, *increment);
+            }
+            visitor.visit_stmt_mut(ast, *body);
+        }
+        StmtKind::Return(value) => {
+            if let Some(value) = value {
+                visitor.visit_expr_mut(ast, *value);
+            }
+        }
+        StmtKind::Error => {}
+    }
+    *ast.stmt_mut(stmt) = kind;
+}
+
+pub fn walk_expr_mut<V: VisitorMut>(visitor: &mut V, ast: &mut Ast, expr: ExprId) {
+    let mut kind = std::mem::replace(ast.expr_mut(expr), ExprKind::Error);
+    match &mut kind {
+        ExprKind::Literal(literal) => visitor.visit_literal_mut(literal),
+        ExprKind::Grouping(inner) => visitor.visit_expr_mut(ast, *inner),
+        ExprKind::Unary {
+            operator: _,
+            operand,
+        } => visitor.visit_expr_mut(ast, *operand),
+        ExprKind::Binary {
+            left,
+            operator: _,
+            right,
+        }
+        | ExprKind::Logical {
+            left,
+            operator: _,
+            right,
+        } => {
+            visitor.visit_expr_mut(ast, *left);
+            visitor.visit_expr_mut(ast, *right);
+        }
+        ExprKind::Variable(identifier) => visitor.visit_identifier_mut(identifier),
+        ExprKind::Assign { target, value, .. } => {
+            visitor.visit_identifier_mut(target);
+            visitor.visit_expr_mut(ast, *value);
+        }
+        ExprKind::Call { callee, arguments } => {
+            visitor.visit_expr_mut(ast, *callee);
+            for argument in arguments {
+                visitor.visit_expr_mut(ast, *argument);
+            }
+        }
+        ExprKind::Get { object, name } => {
+            visitor.visit_expr_mut(ast, *object);
+            visitor.visit_identifier_mut(name);
+        }
+        ExprKind::Set {
+            object,
+            name,
+            value,
+            ..
+        } => {
+            visitor.visit_expr_mut(ast, *object);
+            visitor.visit_identifier_mut(name);
+            visitor.visit_expr_mut(ast, *value);
+        }
+        ExprKind::This | ExprKind::Error => {}
+        ExprKind::Super { method } => visitor.visit_identifier_mut(method),
+        ExprKind::Conversion { target, value } => {
+            visitor.visit_type_expr_mut(target);
+            visitor.visit_expr_mut(ast, *value);
+        }
+    }
+    *ast.expr_mut(expr) = kind;
+}
+
+pub fn walk_var_decl_mut<V: VisitorMut>(visitor: &mut V, ast: &mut Ast, decl: &mut VarDecl) {
+    let VarDecl {
+        mutable: _,
+        name,
+        type_annotation,
+        initializer,
+        line_information: _,
+    } = decl;
+    visitor.visit_identifier_mut(name);
+    if let Some(type_annotation) = type_annotation {
+        visitor.visit_type_expr_mut(type_annotation);
+    }
+    if let Some(initializer) = initializer {
+        visitor.visit_expr_mut(ast, *initializer);
+    }
+}
+
+pub fn walk_function_mut<V: VisitorMut>(
+    visitor: &mut V,
+    ast: &mut Ast,
+    function: &mut FunctionDecl,
+) {
+    let FunctionDecl {
+        name,
+        type_parameters,
+        parameters,
+        return_type,
+        body,
+        line_information: _,
+    } = function;
+    visitor.visit_identifier_mut(name);
+    for type_parameter in type_parameters {
+        visitor.visit_type_parameter_mut(type_parameter);
+    }
+    for parameter in parameters {
+        visitor.visit_parameter_mut(parameter);
+    }
+    if let Some(return_type) = return_type {
+        visitor.visit_type_expr_mut(return_type);
+    }
+    for stmt in body {
+        visitor.visit_stmt_mut(ast, *stmt);
+    }
+}
+
+pub fn walk_class_mut<V: VisitorMut>(visitor: &mut V, ast: &mut Ast, class: &mut ClassDecl) {
+    let ClassDecl {
+        name,
+        type_parameters,
+        superclass,
+        fields,
+        methods,
+    } = class;
+    visitor.visit_identifier_mut(name);
+    for type_parameter in type_parameters {
+        visitor.visit_type_parameter_mut(type_parameter);
+    }
+    if let Some(superclass) = superclass {
+        visitor.visit_identifier_mut(superclass);
+    }
+    for field in fields {
+        visitor.visit_var_decl_mut(ast, field);
+    }
+    for method in methods {
+        visitor.visit_function_mut(ast, method);
+    }
+}
+
+pub fn walk_parameter_mut<V: VisitorMut>(visitor: &mut V, parameter: &mut Parameter) {
+    let Parameter {
+        name,
+        type_annotation,
+        line_information: _,
+    } = parameter;
+    visitor.visit_identifier_mut(name);
+    visitor.visit_type_expr_mut(type_annotation);
+}
+
+pub fn walk_type_parameter_mut<V: VisitorMut>(visitor: &mut V, type_parameter: &mut TypeParameter) {
+    let TypeParameter { name, bound } = type_parameter;
+    visitor.visit_identifier_mut(name);
+    if let Some(bound) = bound {
+        visitor.visit_type_expr_mut(bound);
+    }
+}
+
+pub fn walk_type_expr_mut<V: VisitorMut>(visitor: &mut V, type_expr: &mut TypeExpr) {
+    match &mut type_expr.kind {
+        TypeExprKind::Int | TypeExprKind::Float | TypeExprKind::Bool | TypeExprKind::String => {}
+        TypeExprKind::Named(name) => visitor.visit_identifier_mut(name),
+        TypeExprKind::Generic { name, arguments } => {
+            visitor.visit_identifier_mut(name);
+            for argument in arguments {
+                visitor.visit_type_expr_mut(argument);
+            }
+        }
+        TypeExprKind::Nullable(inner) => visitor.visit_type_expr_mut(inner),
+        TypeExprKind::Function {
+            parameters,
+            return_type,
+        } => {
+            for parameter in parameters {
+                visitor.visit_type_expr_mut(parameter);
+            }
+            if let Some(return_type) = return_type {
+                visitor.visit_type_expr_mut(return_type);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_handling::ErrorHandler;
+    use crate::frontend::ast::sexpr;
+    use crate::frontend::parser::parse;
+    use crate::frontend::scanner::scan;
+
+    // Renames every occurrence of one name, including declarations and types.
+    struct Renamer {
+        from: &'static str,
+        to: &'static str,
+    }
+
+    impl VisitorMut for Renamer {
+        fn visit_identifier_mut(&mut self, identifier: &mut Identifier) {
+            if identifier.name == self.from {
+                identifier.name = self.to.to_string();
+            }
+        }
+    }
+
+    #[test]
+    fn test_rename() {
+        let input = "class A { m(a: A): A { return a.a; } }\nvar a: A = a(a);";
+        let error_handler = ErrorHandler::new(input);
+        let tokens = scan(input, &error_handler).unwrap();
+        let mut program = parse(tokens, &error_handler).unwrap();
+
+        let mut renamer = Renamer { from: "A", to: "B" };
+        for stmt in program.declarations().to_vec() {
+            renamer.visit_stmt_mut(&mut program, stmt);
+        }
+        assert_eq!(
+            sexpr::print(&program),
+            "(class B (method m (a:B):B (return (. a a))))\n(var a:B (call a a))\n"
+        );
+    }
+}
@@ -0,0 +1,104 @@
+// Green trees are immutable and position independent: nodes only know their kind, their width
+// and their children. This allows sharing unchanged subtrees between versions of a file.
+use crate::frontend::cst::SyntaxKind;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct GreenToken {
+    kind: SyntaxKind,
+    text: String,
+}
+
+impl GreenToken {
+    pub fn new(kind: SyntaxKind, text: &str) -> Rc<GreenToken> {
+        Rc::new(GreenToken {
+            kind,
+            text: text.to_string(),
+        })
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn width(&self) -> usize {
+        self.text.len()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenElement {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            GreenElement::Node(node) => node.kind(),
+            GreenElement::Token(token) => token.kind(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.width(),
+            GreenElement::Token(token) => token.width(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct GreenNode {
+    kind: SyntaxKind,
+    width: usize,
+    children: Vec<GreenElement>,
+}
+
+impl GreenNode {
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> Rc<GreenNode> {
+        let width = children.iter().map(|child| child.width()).sum();
+        Rc::new(GreenNode {
+            kind,
+            width,
+            children,
+        })
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn children(&self) -> &[GreenElement] {
+        &self.children
+    }
+
+    // A copy of this node with the child at index replaced. All other children are shared.
+    pub fn replace_child(&self, index: usize, element: GreenElement) -> Rc<GreenNode> {
+        let mut children = self.children.clone();
+        children[index] = element;
+        GreenNode::new(self.kind, children)
+    }
+}
+
+// Nodes are displayed as the source code they were built from.
+impl Display for GreenNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for child in self.children.iter() {
+            match child {
+                GreenElement::Node(node) => write!(f, "{node}")?,
+                GreenElement::Token(token) => write!(f, "{}", token.text())?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,471 @@
+// Lossless concrete syntax tree for tools like formatters and refactorings. In contrast to the AST
+// it contains every character of the input, including whitespace and comments, so the text of the
+// tree is always exactly the text it was built from.
+pub mod green;
+pub mod red;
+pub mod typed;
+
+use crate::error_handling::LineInformation;
+use crate::frontend::ast::visit::{self, Visitor};
+use crate::frontend::ast::{
+    Ast, ExprId, ExprKind, FunctionDecl, Parameter, StmtId, StmtKind, TypeExpr, VarDecl,
+};
+use crate::frontend::cst::green::{GreenElement, GreenNode, GreenToken};
+use crate::frontend::cst::red::SyntaxNode;
+use crate::frontend::scanner::{Token, TokenType};
+use std::rc::Rc;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    // Trivia.
+    Whitespace,
+    Comment,
+
+    // Tokens, one per token type of the scanner.
+    LeftParenthesis,
+    RightParenthesis,
+    LeftBrace,
+    RightBrace,
+    Colon,
+    Comma,
+    Dot,
+    Minus,
+    Plus,
+    Question,
+    Semicolon,
+    Slash,
+    Star,
+    Bang,
+    BangEqual,
+    Equal,
+    EqualEqual,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    MinusEqual,
+    PlusEqual,
+    SlashEqual,
+    StarEqual,
+    Identifier,
+    StringValue,
+    FloatValue,
+    IntegerValue,
+    And,
+    Bool,
+    Class,
+    Else,
+    False,
+    Float,
+    Fun,
+    For,
+    If,
+    Int,
+    Nil,
+    Or,
+    Print,
+    Return,
+    String,
+    Super,
+    This,
+    True,
+    Val,
+    Var,
+    While,
+
+    // Nodes.
+    SourceFile,
+    VarDecl,
+    FunctionDecl,
+    Parameter,
+    ClassDecl,
+    Type,
+    ExprStmt,
+    PrintStmt,
+    Block,
+    IfStmt,
+    WhileStmt,
+    ForStmt,
+    ReturnStmt,
+    ErrorStmt,
+    LiteralExpr,
+    GroupingExpr,
+    UnaryExpr,
+    BinaryExpr,
+    LogicalExpr,
+    VariableExpr,
+    AssignExpr,
+    CallExpr,
+    GetExpr,
+    SetExpr,
+    ThisExpr,
+    SuperExpr,
+    ConversionExpr,
+    ErrorExpr,
+}
+
+impl SyntaxKind {
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, SyntaxKind::Whitespace | SyntaxKind::Comment)
+    }
+
+    // None for EOF, which has no text and is not part of the tree.
+    fn from_token_type(token_type: &TokenType) -> Option<SyntaxKind> {
+        let kind = match token_type {
+            TokenType::LeftParenthesis => SyntaxKind::LeftParenthesis,
+            TokenType::RightParenthesis => SyntaxKind::RightParenthesis,
+            TokenType::LeftBrace => SyntaxKind::LeftBrace,
+            TokenType::RightBrace => SyntaxKind::RightBrace,
+            TokenType::Colon => SyntaxKind::Colon,
+            TokenType::Comma => SyntaxKind::Comma,
+            TokenType::Dot => SyntaxKind::Dot,
+            TokenType::Minus => SyntaxKind::Minus,
+            TokenType::Plus => SyntaxKind::Plus,
+            TokenType::Question => SyntaxKind::Question,
+            TokenType::Semicolon => SyntaxKind::Semicolon,
+            TokenType::Slash => SyntaxKind::Slash,
+            TokenType::Star => SyntaxKind::Star,
+            TokenType::Bang => SyntaxKind::Bang,
+            TokenType::BangEqual => SyntaxKind::BangEqual,
+            TokenType::Equal => SyntaxKind::Equal,
+            TokenType::EqualEqual => SyntaxKind::EqualEqual,
+            TokenType::Greater => SyntaxKind::Greater,
+            TokenType::GreaterEqual => SyntaxKind::GreaterEqual,
+            TokenType::Less => SyntaxKind::Less,
+            TokenType::LessEqual => SyntaxKind::LessEqual,
+            TokenType::MinusEqual => SyntaxKind::MinusEqual,
+            TokenType::PlusEqual => SyntaxKind::PlusEqual,
+            TokenType::SlashEqual => SyntaxKind::SlashEqual,
+            TokenType::StarEqual => SyntaxKind::StarEqual,
+            TokenType::Identifier(_) => SyntaxKind::Identifier,
+            TokenType::StringValue(_) => SyntaxKind::StringValue,
+            TokenType::FloatValue(_) => SyntaxKind::FloatValue,
+            TokenType::IntegerValue(_) => SyntaxKind::IntegerValue,
+            TokenType::And => SyntaxKind::And,
+            TokenType::Bool => SyntaxKind::Bool,
+            TokenType::Class => SyntaxKind::Class,
+            TokenType::Else => SyntaxKind::Else,
+            TokenType::False => SyntaxKind::False,
+            TokenType::Float => SyntaxKind::Float,
+            TokenType::Fun => SyntaxKind::Fun,
+            TokenType::For => SyntaxKind::For,
+            TokenType::If => SyntaxKind::If,
+            TokenType::Int => SyntaxKind::Int,
+            TokenType::Nil => SyntaxKind::Nil,
+            TokenType::Or => SyntaxKind::Or,
+            TokenType::Print => SyntaxKind::Print,
+            TokenType::Return => SyntaxKind::Return,
+            TokenType::String => SyntaxKind::String,
+            TokenType::Super => SyntaxKind::Super,
+            TokenType::This => SyntaxKind::This,
+            TokenType::True => SyntaxKind::True,
+            TokenType::Val => SyntaxKind::Val,
+            TokenType::Var => SyntaxKind::Var,
+            TokenType::While => SyntaxKind::While,
+            TokenType::EOF => return None,
+        };
+        Some(kind)
+    }
+}
+
+// Build the CST of code from its tokens and the AST parsed from them. The nesting of nodes follows
+// the spans of the AST. The AST may be empty, e.g. if parsing failed, which results in a flat but
+// still lossless tree.
+pub fn build(code: &str, tokens: &[Token], ast: &Ast) -> SyntaxNode {
+    let mut shapes = ShapeBuilder {
+        ast,
+        stack: vec![Shape::new(
+            SyntaxKind::SourceFile,
+            LineInformation::new(0, code.len()),
+        )],
+    };
+    for declaration in ast.declarations() {
+        shapes.visit_stmt(*declaration);
+    }
+    let root = shapes.stack.pop().unwrap();
+
+    let mut builder = GreenBuilder {
+        pieces: split_into_pieces(code, tokens),
+        next: 0,
+        code,
+    };
+    SyntaxNode::new_root(builder.build(&root))
+}
+
+// The tokens and trivia of code in order, covering all of it.
+fn split_into_pieces(code: &str, tokens: &[Token]) -> Vec<(SyntaxKind, LineInformation)> {
+    let mut pieces = vec![];
+    let mut position = 0;
+    for token in tokens {
+        let Some(kind) = SyntaxKind::from_token_type(token.token_type()) else {
+            continue;
+        };
+        let li = token.line_information();
+        split_trivia(code, position, li.offset(), &mut pieces);
+        pieces.push((kind, *li));
+        position = li.end();
+    }
+    split_trivia(code, position, code.len(), &mut pieces);
+    pieces
+}
+
+// The scanner skips whitespace and comments, so the gaps between tokens consist of them only.
+fn split_trivia(
+    code: &str,
+    start: usize,
+    end: usize,
+    pieces: &mut Vec<(SyntaxKind, LineInformation)>,
+) {
+    let mut position = start;
+    while position < end {
+        let rest = &code[position..end];
+        let (kind, length) = if rest.starts_with("//") {
+            (SyntaxKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+        } else if rest.starts_with("/*") {
+            let length = rest.find("*/").map_or(rest.len(), |it| it + 2);
+            (SyntaxKind::Comment, length)
+        } else {
+            let length = rest.find('/').unwrap_or(rest.len()).max(1);
+            (SyntaxKind::Whitespace, length)
+        };
+        pieces.push((kind, LineInformation::new(position, length)));
+        position += length;
+    }
+}
+
+// The nesting of CST nodes, before tokens are filled in.
+struct Shape {
+    kind: SyntaxKind,
+    line_information: LineInformation,
+    children: Vec<Shape>,
+}
+
+impl Shape {
+    fn new(kind: SyntaxKind, line_information: LineInformation) -> Shape {
+        Shape {
+            kind,
+            line_information,
+            children: vec![],
+        }
+    }
+}
+
+struct ShapeBuilder<'ast> {
+    ast: &'ast Ast,
+    stack: Vec<Shape>,
+}
+
+impl ShapeBuilder<'_> {
+    fn nested(&mut self, kind: SyntaxKind, li: LineInformation, walk: impl FnOnce(&mut Self)) {
+        self.stack.push(Shape::new(kind, li));
+        walk(self);
+        let shape = self.stack.pop().unwrap();
+        self.stack.last_mut().unwrap().children.push(shape);
+    }
+}
+
+impl<'ast> Visitor<'ast> for ShapeBuilder<'ast> {
+    fn ast(&self) -> &'ast Ast {
+        self.ast
+    }
+
+    fn visit_stmt(&mut self, stmt: StmtId) {
+        let kind = match self.ast.stmt(stmt) {
+            // Variables and functions have the same span as their statement and get their node
+            // from visit_var_decl and visit_function.
+            StmtKind::Var(_) | StmtKind::Function(_) => return visit::walk_stmt(self, stmt),
+            StmtKind::Class(_) => SyntaxKind::ClassDecl,
+            StmtKind::Expression(_) => SyntaxKind::ExprStmt,
+            StmtKind::Print(_) => SyntaxKind::PrintStmt,
+            StmtKind::Block(_) => SyntaxKind::Block,
+            StmtKind::If { .. } => SyntaxKind::IfStmt,
+            StmtKind::While { .. } => SyntaxKind::WhileStmt,
+            StmtKind::For { .. } => SyntaxKind::ForStmt,
+            StmtKind::Return(_) => SyntaxKind::ReturnStmt,
+            StmtKind::Error => SyntaxKind::ErrorStmt,
+        };
+        self.nested(kind, self.ast.span(stmt), |it| visit::walk_stmt(it, stmt));
+    }
+
+    fn visit_expr(&mut self, expr: ExprId) {
+        let kind = match self.ast.expr(expr) {
+            ExprKind::Literal(_) => SyntaxKind::LiteralExpr,
+            ExprKind::Grouping(_) => SyntaxKind::GroupingExpr,
+            ExprKind::Unary { .. } => SyntaxKind::UnaryExpr,
+            ExprKind::Binary { .. } => SyntaxKind::BinaryExpr,
+            ExprKind::Logical { .. } => SyntaxKind::LogicalExpr,
+            ExprKind::Variable(_) => SyntaxKind::VariableExpr,
+            ExprKind::Assign { .. } => SyntaxKind::AssignExpr,
+            ExprKind::Call { .. } => SyntaxKind::CallExpr,
+            ExprKind::Get { .. } => SyntaxKind::GetExpr,
+            ExprKind::Set { .. } => SyntaxKind::SetExpr,
+            ExprKind::This => SyntaxKind::ThisExpr,
+            ExprKind::Super { .. } => SyntaxKind::SuperExpr,
+            ExprKind::Conversion { .. } => SyntaxKind::ConversionExpr,
+            ExprKind::Error => SyntaxKind::ErrorExpr,
+        };
+        self.nested(kind, self.ast.span(expr), |it| visit::walk_expr(it, expr));
+    }
+
+    fn visit_var_decl(&mut self, decl: &'ast VarDecl) {
+        self.nested(SyntaxKind::VarDecl, decl.line_information, |it| {
+            visit::walk_var_decl(it, decl)
+        });
+    }
+
+    fn visit_function(&mut self, function: &'ast FunctionDecl) {
+        self.nested(SyntaxKind::FunctionDecl, function.line_information, |it| {
+            visit::walk_function(it, function)
+        });
+    }
+
+    fn visit_parameter(&mut self, parameter: &'ast Parameter) {
+        self.nested(SyntaxKind::Parameter, parameter.line_information, |it| {
+            visit::walk_parameter(it, parameter)
+        });
+    }
+
+    fn visit_type_expr(&mut self, type_expr: &'ast TypeExpr) {
+        self.nested(SyntaxKind::Type, type_expr.line_information, |it| {
+            visit::walk_type_expr(it, type_expr)
+        });
+    }
+}
+
+struct GreenBuilder<'a> {
+    pieces: Vec<(SyntaxKind, LineInformation)>,
+    next: usize,
+    code: &'a str,
+}
+
+impl GreenBuilder<'_> {
+    // Every piece ends up in exactly one node, which makes the tree lossless even if the shapes
+    // do not match the tokens.
+    fn build(&mut self, shape: &Shape) -> Rc<GreenNode> {
+        let mut children = vec![];
+        for child in &shape.children {
+            self.push_pieces(child.line_information.offset(), &mut children);
+            children.push(GreenElement::Node(self.build(child)));
+        }
+        self.push_pieces(shape.line_information.end(), &mut children);
+        GreenNode::new(shape.kind, children)
+    }
+
+    // Push the pieces starting before end.
+    fn push_pieces(&mut self, end: usize, children: &mut Vec<GreenElement>) {
+        while let Some((kind, li)) = self.pieces.get(self.next) {
+            if li.offset() >= end {
+                break;
+            }
+            let text = &self.code[li.offset()..li.end()];
+            children.push(GreenElement::Token(GreenToken::new(*kind, text)));
+            self.next += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_handling::ErrorHandler;
+    use crate::frontend::cst::typed::{AstNode, BinaryExpr, ClassDecl, SourceFile, VarDecl};
+    use crate::frontend::parser::parse;
+    use crate::frontend::scanner::scan;
+
+    fn build_from(input: &str) -> SyntaxNode {
+        let error_handler = ErrorHandler::new(input);
+        let tokens = scan(input, &error_handler).unwrap();
+        let ast = parse(tokens.clone(), &error_handler).unwrap();
+        build(input, &tokens, &ast)
+    }
+
+    #[test]
+    fn test_lossless() {
+        let input =
+            "// leading\nfun f(a: int): int {\n\treturn a /* inline */ * 2;\n}\n\n// trailing";
+        let root = build_from(input);
+        assert_eq!(root.text(), input);
+        assert_eq!(
+            root.line_information(),
+            LineInformation::new(0, input.len())
+        );
+    }
+
+    #[test]
+    fn test_structure() {
+        let root = build_from("print -a;");
+        assert_eq!(
+            root.debug_dump(),
+            "SourceFile@0..9\n\
+             \x20 PrintStmt@0..9\n\
+             \x20   Print@0..5 \"print\"\n\
+             \x20   Whitespace@5..6 \" \"\n\
+             \x20   UnaryExpr@6..8\n\
+             \x20     Minus@6..7 \"-\"\n\
+             \x20     VariableExpr@7..8\n\
+             \x20       Identifier@7..8 \"a\"\n\
+             \x20   Semicolon@8..9 \";\"\n"
+        );
+    }
+
+    #[test]
+    fn test_without_declarations() {
+        let input = "var a = ; // broken";
+        let error_handler = ErrorHandler::new(input);
+        let tokens = scan(input, &error_handler).unwrap();
+        let root = build(input, &tokens, &Ast::new());
+        assert_eq!(root.text(), input);
+        assert_eq!(root.children().count(), 0);
+    }
+
+    #[test]
+    fn test_typed_accessors() {
+        let root = build_from("class B < A { val x: int; m(a: A) {} }\nvar y = 1 + 2;");
+        let file = SourceFile::cast(root).unwrap();
+        let declarations = file.declarations();
+        assert_eq!(declarations.len(), 2);
+
+        let class = ClassDecl::cast(declarations[0].clone()).unwrap();
+        assert_eq!(class.name().unwrap().text(), "B");
+        assert_eq!(class.superclass().unwrap().text(), "A");
+        let fields = class.fields();
+        assert!(!fields[0].is_mutable());
+        assert_eq!(fields[0].name().unwrap().text(), "x");
+        let field_type = fields[0].type_annotation().unwrap();
+        assert_eq!(field_type.name().unwrap().kind(), SyntaxKind::Int);
+        let method = &class.methods()[0];
+        assert_eq!(method.name().unwrap().text(), "m");
+        let parameter = &method.parameters()[0];
+        assert_eq!(parameter.name().unwrap().text(), "a");
+        assert_eq!(parameter.type_annotation().unwrap().syntax().text(), "A");
+        assert!(method.return_type().is_none());
+
+        let var = VarDecl::cast(declarations[1].clone()).unwrap();
+        assert!(var.is_mutable());
+        assert!(var.type_annotation().is_none());
+        let binary = BinaryExpr::cast(var.initializer().unwrap()).unwrap();
+        assert_eq!(binary.lhs().unwrap().text(), "1");
+        assert_eq!(binary.operator().unwrap().kind(), SyntaxKind::Plus);
+        assert_eq!(binary.rhs().unwrap().text(), "2");
+        assert!(VarDecl::cast(binary.syntax().clone()).is_none());
+    }
+
+    #[test]
+    fn test_replace_shares_unchanged_subtrees() {
+        let root = build_from("var a = 1;\nvar b = 2 * 3;");
+        let first = root.children().next().unwrap();
+        let old_second = root.children().nth(1).unwrap();
+        let literal = first
+            .descendant_tokens()
+            .into_iter()
+            .find(|token| token.kind() == SyntaxKind::IntegerValue)
+            .unwrap();
+
+        let new_root = literal.replace_with(GreenToken::new(SyntaxKind::IntegerValue, "42"));
+        assert_eq!(new_root.text(), "var a = 42;\nvar b = 2 * 3;");
+        assert_eq!(root.text(), "var a = 1;\nvar b = 2 * 3;");
+        let new_second = new_root.children().nth(1).unwrap();
+        assert!(Rc::ptr_eq(old_second.green(), new_second.green()));
+        assert_eq!(new_second.line_information(), LineInformation::new(12, 14));
+    }
+}
@@ -0,0 +1,191 @@
+// Red trees are cursors into a green tree that know their parent and their position in the file.
+// They are created on demand while navigating and are cheap to clone.
+use crate::error_handling::LineInformation;
+use crate::frontend::cst::green::{GreenElement, GreenNode, GreenToken};
+use crate::frontend::cst::SyntaxKind;
+use std::rc::Rc;
+
+#[derive(Clone, Debug)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+#[derive(Debug)]
+struct NodeData {
+    green: Rc<GreenNode>,
+    parent: Option<SyntaxNode>,
+    // Position among the children of the parent.
+    index: usize,
+    offset: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    parent: SyntaxNode,
+    index: usize,
+    offset: usize,
+}
+
+#[derive(Clone, Debug)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: Rc<GreenNode>) -> SyntaxNode {
+        SyntaxNode(Rc::new(NodeData {
+            green,
+            parent: None,
+            index: 0,
+            offset: 0,
+        }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind()
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.clone()
+    }
+
+    pub fn line_information(&self) -> LineInformation {
+        LineInformation::new(self.0.offset, self.0.green.width())
+    }
+
+    pub fn text(&self) -> String {
+        self.0.green.to_string()
+    }
+
+    pub fn children_with_tokens(&self) -> Vec<SyntaxElement> {
+        let mut offset = self.0.offset;
+        let mut result = vec![];
+        for (index, child) in self.0.green.children().iter().enumerate() {
+            result.push(match child {
+                GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                    green: green.clone(),
+                    parent: Some(self.clone()),
+                    index,
+                    offset,
+                }))),
+                GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                    green: green.clone(),
+                    parent: self.clone(),
+                    index,
+                    offset,
+                }),
+            });
+            offset += child.width();
+        }
+        result
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = SyntaxNode> {
+        self.children_with_tokens()
+            .into_iter()
+            .filter_map(|child| match child {
+                SyntaxElement::Node(node) => Some(node),
+                SyntaxElement::Token(_) => None,
+            })
+    }
+
+    // The direct child tokens, including trivia.
+    pub fn tokens(&self) -> impl Iterator<Item = SyntaxToken> {
+        self.children_with_tokens()
+            .into_iter()
+            .filter_map(|child| match child {
+                SyntaxElement::Node(_) => None,
+                SyntaxElement::Token(token) => Some(token),
+            })
+    }
+
+    // All tokens in this subtree in source order, including trivia.
+    pub fn descendant_tokens(&self) -> Vec<SyntaxToken> {
+        let mut result = vec![];
+        for child in self.children_with_tokens() {
+            match child {
+                SyntaxElement::Node(node) => result.extend(node.descendant_tokens()),
+                SyntaxElement::Token(token) => result.push(token),
+            }
+        }
+        result
+    }
+
+    // Replace this node in its tree and return the root of the new tree. Only the nodes on the
+    // path to the root are copied, everything else is shared with the old tree.
+    pub fn replace_with(&self, green: Rc<GreenNode>) -> SyntaxNode {
+        match &self.0.parent {
+            None => SyntaxNode::new_root(green),
+            Some(parent) => parent.replace_with(
+                parent
+                    .green()
+                    .replace_child(self.0.index, GreenElement::Node(green)),
+            ),
+        }
+    }
+
+    // One line per node and token with its kind and span, indented by depth.
+    pub fn debug_dump(&self) -> String {
+        let mut result = String::new();
+        self.dump_into(&mut result, 0);
+        result
+    }
+
+    fn dump_into(&self, result: &mut String, depth: usize) {
+        let li = self.line_information();
+        *result += &format!(
+            "{}{:?}@{}..{}\n",
+            "  ".repeat(depth),
+            self.kind(),
+            li.offset(),
+            li.end()
+        );
+        for child in self.children_with_tokens() {
+            match child {
+                SyntaxElement::Node(node) => node.dump_into(result, depth + 1),
+                SyntaxElement::Token(token) => {
+                    let li = token.line_information();
+                    *result += &format!(
+                        "{}{:?}@{}..{} {:?}\n",
+                        "  ".repeat(depth + 1),
+                        token.kind(),
+                        li.offset(),
+                        li.end(),
+                        token.text()
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> SyntaxKind {
+        self.green.kind()
+    }
+
+    pub fn text(&self) -> &str {
+        self.green.text()
+    }
+
+    pub fn parent(&self) -> &SyntaxNode {
+        &self.parent
+    }
+
+    pub fn line_information(&self) -> LineInformation {
+        LineInformation::new(self.offset, self.green.width())
+    }
+
+    // Replace this token in its tree and return the root of the new tree.
+    pub fn replace_with(&self, green: Rc<GreenToken>) -> SyntaxNode {
+        self.parent.replace_with(
+            self.parent
+                .green()
+                .replace_child(self.index, GreenElement::Token(green)),
+        )
+    }
+}
@@ -0,0 +1,147 @@
+// Typed views of CST nodes. They only wrap a SyntaxNode of the right kind and look up their parts
+// on demand, so they stay valid for incomplete code, where any part may be missing.
+use crate::frontend::cst::red::{SyntaxNode, SyntaxToken};
+use crate::frontend::cst::SyntaxKind;
+
+pub trait AstNode: Sized {
+    fn cast(node: SyntaxNode) -> Option<Self>;
+
+    fn syntax(&self) -> &SyntaxNode;
+}
+
+macro_rules! ast_node {
+    ($name:ident, $kind:ident) => {
+        #[derive(Clone, Debug)]
+        pub struct $name(SyntaxNode);
+
+        impl AstNode for $name {
+            fn cast(node: SyntaxNode) -> Option<Self> {
+                (node.kind() == SyntaxKind::$kind).then(|| $name(node))
+            }
+
+            fn syntax(&self) -> &SyntaxNode {
+                &self.0
+            }
+        }
+    };
+}
+
+ast_node!(SourceFile, SourceFile);
+ast_node!(VarDecl, VarDecl);
+ast_node!(FunctionDecl, FunctionDecl);
+ast_node!(Parameter, Parameter);
+ast_node!(ClassDecl, ClassDecl);
+ast_node!(TypeRef, Type);
+ast_node!(BinaryExpr, BinaryExpr);
+
+fn child<N: AstNode>(node: &SyntaxNode) -> Option<N> {
+    node.children().find_map(N::cast)
+}
+
+fn children<N: AstNode>(node: &SyntaxNode) -> Vec<N> {
+    node.children().filter_map(N::cast).collect()
+}
+
+fn token(node: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxToken> {
+    node.tokens().find(|token| token.kind() == kind)
+}
+
+impl SourceFile {
+    pub fn declarations(&self) -> Vec<SyntaxNode> {
+        self.0.children().collect()
+    }
+}
+
+impl VarDecl {
+    pub fn is_mutable(&self) -> bool {
+        token(&self.0, SyntaxKind::Var).is_some()
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    pub fn type_annotation(&self) -> Option<TypeRef> {
+        child(&self.0)
+    }
+
+    pub fn initializer(&self) -> Option<SyntaxNode> {
+        self.0.children().find(|it| it.kind() != SyntaxKind::Type)
+    }
+}
+
+impl FunctionDecl {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    pub fn parameters(&self) -> Vec<Parameter> {
+        children(&self.0)
+    }
+
+    pub fn return_type(&self) -> Option<TypeRef> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Vec<SyntaxNode> {
+        self.0
+            .children()
+            .filter(|it| !matches!(it.kind(), SyntaxKind::Parameter | SyntaxKind::Type))
+            .collect()
+    }
+}
+
+impl Parameter {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    pub fn type_annotation(&self) -> Option<TypeRef> {
+        child(&self.0)
+    }
+}
+
+impl ClassDecl {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    // The identifier following `<`.
+    pub fn superclass(&self) -> Option<SyntaxToken> {
+        self.0
+            .tokens()
+            .filter(|token| !token.kind().is_trivia())
+            .skip_while(|token| token.kind() != SyntaxKind::Less)
+            .nth(1)
+            .filter(|token| token.kind() == SyntaxKind::Identifier)
+    }
+
+    pub fn fields(&self) -> Vec<VarDecl> {
+        children(&self.0)
+    }
+
+    pub fn methods(&self) -> Vec<FunctionDecl> {
+        children(&self.0)
+    }
+}
+
+impl TypeRef {
+    // The keyword of a primitive type or the name of a class.
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.0.tokens().find(|token| !token.kind().is_trivia())
+    }
+}
+
+impl BinaryExpr {
+    pub fn lhs(&self) -> Option<SyntaxNode> {
+        self.0.children().next()
+    }
+
+    pub fn operator(&self) -> Option<SyntaxToken> {
+        self.0.tokens().find(|token| !token.kind().is_trivia())
+    }
+
+    pub fn rhs(&self) -> Option<SyntaxNode> {
+        self.0.children().nth(1)
+    }
+}
@@ -1 +1,7 @@
+pub mod ast;
+pub mod cst;
+pub mod parser;
 pub mod scanner;
+pub mod suggest;
+pub mod typecheck;
+pub mod types;
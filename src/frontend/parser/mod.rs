@@ -0,0 +1,609 @@
+mod statement;
+mod token_stream;
+
+use crate::error_handling::messages::{Message, MessageId};
+use crate::error_handling::{Diagnostic, ErrorHandler, LineInformation, Severity};
+use crate::frontend::ast::{
+    Ast, BinaryOperator, ExprId, ExprKind, Identifier, Literal, LogicalOperator, TypeExpr,
+    TypeExprKind, UnaryOperator,
+};
+use crate::frontend::scanner::{Token, TokenType};
+use thiserror::Error;
+
+pub use crate::frontend::parser::token_stream::{Checkpoint, TokenStream};
+
+// Parse tokens as produced by `scan` into the declarations of a program.
+pub fn parse(tokens: Vec<Token>, error_handler: &ErrorHandler) -> Result<Ast, ParseError> {
+    let mut parser = Parser::new(tokens, error_handler);
+    while !parser.tokens.check(&TokenType::EOF) {
+        let declaration = parser.declaration();
+        parser.ast.push_declaration(declaration);
+    }
+    parser.finish()
+}
+
+// Parse tokens as produced by `scan` into a single expression that spans all of them.
+pub fn parse_expression(
+    tokens: Vec<Token>,
+    error_handler: &ErrorHandler,
+) -> Result<(Ast, ExprId), ParseError> {
+    let mut parser = Parser::new(tokens, error_handler);
+    let expression = parser.expression()?;
+    parser.skip_implicit_semicolon();
+    parser.consume(&TokenType::EOF)?;
+    Ok((parser.finish()?, expression))
+}
+
+#[derive(Error, Debug)]
+#[error("{message:}")]
+pub struct ParseError {
+    message: String,
+}
+
+impl ParseError {
+    fn new(message: &str) -> ParseError {
+        ParseError {
+            message: message.to_string(),
+        }
+    }
+}
+
+// Binding power of operators, from weakest to strongest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Assignment,
+    Or,
+    And,
+    Equality,
+    Comparison,
+    Term,
+    Factor,
+    Unary,
+    Call,
+}
+
+impl Precedence {
+    // The precedence of a token used as an infix operator, None if it is not an infix operator.
+    fn of_infix(token_type: &TokenType) -> Option<Precedence> {
+        match token_type {
+            TokenType::Equal
+            | TokenType::PlusEqual
+            | TokenType::MinusEqual
+            | TokenType::StarEqual
+            | TokenType::SlashEqual => Some(Precedence::Assignment),
+            TokenType::Or => Some(Precedence::Or),
+            TokenType::And => Some(Precedence::And),
+            TokenType::EqualEqual | TokenType::BangEqual => Some(Precedence::Equality),
+            TokenType::Less
+            | TokenType::LessEqual
+            | TokenType::Greater
+            | TokenType::GreaterEqual => Some(Precedence::Comparison),
+            TokenType::Plus | TokenType::Minus => Some(Precedence::Term),
+            TokenType::Star | TokenType::Slash => Some(Precedence::Factor),
+            TokenType::LeftParenthesis | TokenType::Dot => Some(Precedence::Call),
+            _ => None,
+        }
+    }
+
+    // The precedence the right operand of a left-associative operator is parsed with.
+    fn next(&self) -> Precedence {
+        match self {
+            Precedence::Assignment => Precedence::Or,
+            Precedence::Or => Precedence::And,
+            Precedence::And => Precedence::Equality,
+            Precedence::Equality => Precedence::Comparison,
+            Precedence::Comparison => Precedence::Term,
+            Precedence::Term => Precedence::Factor,
+            Precedence::Factor => Precedence::Unary,
+            Precedence::Unary | Precedence::Call => Precedence::Call,
+        }
+    }
+}
+
+struct Parser<'a> {
+    error_handler: &'a ErrorHandler,
+    tokens: TokenStream,
+    ast: Ast,
+    had_error: bool,
+    // Set after a syntax error until the parser has synchronized. Errors in the meantime are most
+    // likely caused by the first one and are not reported.
+    panic_mode: bool,
+    // Number of enclosing blocks and class bodies, whose `}` recovery must not skip.
+    block_depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(tokens: Vec<Token>, error_handler: &'a ErrorHandler) -> Parser<'a> {
+        Parser {
+            error_handler,
+            tokens: TokenStream::new(tokens),
+            ast: Ast::new(),
+            had_error: false,
+            panic_mode: false,
+            block_depth: 0,
+        }
+    }
+
+    // Errors the parser could continue after still fail the parse.
+    fn finish(self) -> Result<Ast, ParseError> {
+        match self.had_error {
+            true => Err(ParseError::new("Error parsing file.")),
+            false => Ok(self.ast),
+        }
+    }
+
+    fn expression(&mut self) -> Result<ExprId, ParseError> {
+        self.parse_precedence(Precedence::Assignment)
+    }
+
+    // Parse an expression whose operators bind at least as strongly as min_precedence.
+    fn parse_precedence(&mut self, min_precedence: Precedence) -> Result<ExprId, ParseError> {
+        let mut expression = self.prefix()?;
+
+        while let Some(precedence) = Precedence::of_infix(self.tokens.peek(0).token_type()) {
+            if precedence < min_precedence {
+                break;
+            }
+            expression = self.infix(expression, precedence)?;
+        }
+        Ok(expression)
+    }
+
+    fn prefix(&mut self) -> Result<ExprId, ParseError> {
+        let checkpoint = self.tokens.checkpoint();
+        let token = self.tokens.advance();
+        let li = *token.line_information();
+        let literal = |parser: &mut Self, literal: Literal| {
+            Ok(parser.ast.alloc_expr(ExprKind::Literal(literal), li))
+        };
+
+        match token.token_type().clone() {
+            TokenType::IntegerValue(value) => literal(self, Literal::Integer(value)),
+            TokenType::FloatValue(value) => literal(self, Literal::Float(value)),
+            TokenType::StringValue(value) => literal(self, Literal::String(value)),
+            TokenType::True => literal(self, Literal::Bool(true)),
+            TokenType::False => literal(self, Literal::Bool(false)),
+            TokenType::Nil => literal(self, Literal::Nil),
+            TokenType::Identifier(name) => Ok(self.ast.alloc_expr(
+                ExprKind::Variable(Identifier {
+                    name,
+                    line_information: li,
+                }),
+                li,
+            )),
+            TokenType::This => Ok(self.ast.alloc_expr(ExprKind::This, li)),
+            TokenType::Super => {
+                self.consume(&TokenType::Dot)?;
+                let method = self.identifier(MessageId::ExpectedPropertyName)?;
+                let span = li.join(&method.line_information);
+                Ok(self.ast.alloc_expr(ExprKind::Super { method }, span))
+            }
+            // The numeric type keywords are used like functions for explicit conversions.
+            TokenType::Int | TokenType::Float => {
+                let kind = match token.token_type() {
+                    TokenType::Int => TypeExprKind::Int,
+                    _ => TypeExprKind::Float,
+                };
+                let target = TypeExpr {
+                    kind,
+                    line_information: li,
+                };
+                self.consume(&TokenType::LeftParenthesis)?;
+                let value = self.expression()?;
+                let closing = self.consume(&TokenType::RightParenthesis)?;
+                Ok(self
+                    .ast
+                    .alloc_expr(ExprKind::Conversion { target, value }, li.join(&closing)))
+            }
+            TokenType::LeftParenthesis => {
+                let inner = self.expression()?;
+                let closing = self.consume(&TokenType::RightParenthesis)?;
+                Ok(self
+                    .ast
+                    .alloc_expr(ExprKind::Grouping(inner), li.join(&closing)))
+            }
+            TokenType::Minus | TokenType::Bang => {
+                let operator = match token.token_type() {
+                    TokenType::Minus => UnaryOperator::Negate,
+                    _ => UnaryOperator::Not,
+                };
+                let operand = self.parse_precedence(Precedence::Unary)?;
+                let span = li.join(&self.ast.span(operand));
+                Ok(self
+                    .ast
+                    .alloc_expr(ExprKind::Unary { operator, operand }, span))
+            }
+            token_type => {
+                self.tokens.rewind(checkpoint);
+                let error = self.error(
+                    &Message::new(MessageId::ExpectedExpression).arg("found", &token_type),
+                    &li,
+                );
+                // A missing operand before a closing token, as in `f(1, )`, does not confuse the
+                // parser. An error node stands in for it and parsing continues.
+                match token_type {
+                    TokenType::Semicolon
+                    | TokenType::RightParenthesis
+                    | TokenType::RightBrace
+                    | TokenType::Comma
+                    | TokenType::EOF => Ok(self
+                        .ast
+                        .alloc_expr(ExprKind::Error, LineInformation::new(li.offset(), 0))),
+                    _ => Err(error),
+                }
+            }
+        }
+    }
+
+    fn infix(&mut self, left: ExprId, precedence: Precedence) -> Result<ExprId, ParseError> {
+        let token = self.tokens.advance();
+        let operator_li = *token.line_information();
+
+        match token.token_type() {
+            TokenType::Equal => self.assignment(left, None, &operator_li),
+            TokenType::PlusEqual => self.assignment(left, Some(BinaryOperator::Add), &operator_li),
+            TokenType::MinusEqual => {
+                self.assignment(left, Some(BinaryOperator::Subtract), &operator_li)
+            }
+            TokenType::StarEqual => {
+                self.assignment(left, Some(BinaryOperator::Multiply), &operator_li)
+            }
+            TokenType::SlashEqual => {
+                self.assignment(left, Some(BinaryOperator::Divide), &operator_li)
+            }
+            TokenType::LeftParenthesis => self.call(left),
+            TokenType::Dot => {
+                let name = self.identifier(MessageId::ExpectedPropertyName)?;
+                let span = self.ast.span(left).join(&name.line_information);
+                Ok(self
+                    .ast
+                    .alloc_expr(ExprKind::Get { object: left, name }, span))
+            }
+            TokenType::And | TokenType::Or => {
+                let operator = match token.token_type() {
+                    TokenType::And => LogicalOperator::And,
+                    _ => LogicalOperator::Or,
+                };
+                let right = self.parse_precedence(precedence.next())?;
+                let span = self.ast.span(left).join(&self.ast.span(right));
+                Ok(self.ast.alloc_expr(
+                    ExprKind::Logical {
+                        left,
+                        operator,
+                        right,
+                    },
+                    span,
+                ))
+            }
+            token_type => {
+                let operator = match token_type {
+                    TokenType::Plus => BinaryOperator::Add,
+                    TokenType::Minus => BinaryOperator::Subtract,
+                    TokenType::Star => BinaryOperator::Multiply,
+                    TokenType::Slash => BinaryOperator::Divide,
+                    TokenType::EqualEqual => BinaryOperator::Equal,
+                    TokenType::BangEqual => BinaryOperator::NotEqual,
+                    TokenType::Less => BinaryOperator::Less,
+                    TokenType::LessEqual => BinaryOperator::LessEqual,
+                    TokenType::Greater => BinaryOperator::Greater,
+                    _ => BinaryOperator::GreaterEqual,
+                };
+                // A `>` at the end of a line only ends the statement if it closes type arguments.
+                if operator == BinaryOperator::Greater {
+                    self.skip_implicit_semicolon();
+                }
+                let right = self.parse_precedence(precedence.next())?;
+                let span = self.ast.span(left).join(&self.ast.span(right));
+                Ok(self.ast.alloc_expr(
+                    ExprKind::Binary {
+                        left,
+                        operator,
+                        right,
+                    },
+                    span,
+                ))
+            }
+        }
+    }
+
+    // Assignment is right-associative and only valid if the left side denotes a variable or
+    // property. The operator is set for compound assignments like `+=`.
+    fn assignment(
+        &mut self,
+        target: ExprId,
+        operator: Option<BinaryOperator>,
+        operator_li: &LineInformation,
+    ) -> Result<ExprId, ParseError> {
+        let value = self.parse_precedence(Precedence::Assignment)?;
+        let target_li = self.ast.span(target);
+        let span = target_li.join(&self.ast.span(value));
+        let kind = match self.ast.expr(target).clone() {
+            ExprKind::Variable(target) => ExprKind::Assign {
+                target,
+                operator,
+                value,
+            },
+            ExprKind::Get { object, name } => ExprKind::Set {
+                object,
+                name,
+                operator,
+                value,
+            },
+            _ => {
+                // The parser is not confused by an invalid target, so parsing continues.
+                self.report(Diagnostic::from_message(
+                    Severity::Error,
+                    &Message::new(MessageId::InvalidAssignmentTarget),
+                    &target_li.join(operator_li),
+                ));
+                ExprKind::Error
+            }
+        };
+        // The target node becomes the assignment, s.t. it does not remain in the arena unused.
+        *self.ast.expr_mut(target) = kind;
+        self.ast.set_span(target, span);
+        Ok(target)
+    }
+
+    fn call(&mut self, callee: ExprId) -> Result<ExprId, ParseError> {
+        let mut arguments = vec![];
+        if !self.tokens.check(&TokenType::RightParenthesis) {
+            loop {
+                arguments.push(self.expression()?);
+                if !self.tokens.matches(&TokenType::Comma) {
+                    break;
+                }
+            }
+        }
+        let closing = self.consume(&TokenType::RightParenthesis)?;
+        let span = self.ast.span(callee).join(&closing);
+        Ok(self
+            .ast
+            .alloc_expr(ExprKind::Call { callee, arguments }, span))
+    }
+
+    fn identifier(&mut self, expected: MessageId) -> Result<Identifier, ParseError> {
+        let token = self.tokens.peek(0).clone();
+        match token.token_type() {
+            TokenType::Identifier(name) => {
+                self.tokens.advance();
+                Ok(Identifier {
+                    name: name.clone(),
+                    line_information: *token.line_information(),
+                })
+            }
+            found => Err(self.error(
+                &Message::new(expected).arg("found", found),
+                token.line_information(),
+            )),
+        }
+    }
+
+    // Skip a semicolon inserted at a line break where the construct continues on the next line,
+    // as between `if (a)` and its body.
+    fn skip_implicit_semicolon(&mut self) {
+        if self.tokens.check(&TokenType::Semicolon)
+            && self.tokens.peek(0).line_information().length() == 0
+        {
+            self.tokens.advance();
+        }
+    }
+
+    // Consume the `;` ending a statement. Inside a block, a `}` ends it as well, but is left to
+    // close the block, s.t. one-line blocks like `{ x = 3 }` need no semicolon.
+    fn end_statement(&mut self) -> Result<(), ParseError> {
+        if self.block_depth == 0 || !self.tokens.check(&TokenType::RightBrace) {
+            self.consume(&TokenType::Semicolon)?;
+        }
+        Ok(())
+    }
+
+    // Consume a token of the expected type and return its span, or report what was found instead.
+    fn consume(&mut self, expected: &TokenType) -> Result<LineInformation, ParseError> {
+        self.tokens
+            .expect(expected)
+            .map_err(|diagnostic| self.fail(*diagnostic))
+    }
+
+    // Report a syntax error the parser cannot continue after, unless it is a follow-up error.
+    fn error(&mut self, message: &Message, line_information: &LineInformation) -> ParseError {
+        self.fail(Diagnostic::from_message(
+            Severity::Error,
+            message,
+            line_information,
+        ))
+    }
+
+    fn fail(&mut self, diagnostic: Diagnostic) -> ParseError {
+        let error = ParseError::new(&diagnostic.message);
+        if !self.panic_mode {
+            self.error_handler.report(diagnostic);
+        }
+        self.had_error = true;
+        self.panic_mode = true;
+        error
+    }
+
+    // Skip tokens until the end of the statement an error occurred in, i.e. after a `;`, before a
+    // keyword starting the next one, or before the `}` closing the enclosing block. Blocks opened
+    // by skipped tokens are skipped as a whole. The token at start is skipped in any case, s.t. the
+    // parser cannot get stuck on it.
+    fn synchronize(&mut self, start: Checkpoint) {
+        // The number of skipped `{` that are not closed yet.
+        let mut depth = 0;
+        loop {
+            match self.tokens.peek(0).token_type() {
+                TokenType::EOF => return,
+                TokenType::RightBrace if depth > 0 => {
+                    self.tokens.advance();
+                    depth -= 1;
+                    continue;
+                }
+                TokenType::RightBrace if self.block_depth > 0 => return,
+                _ if depth > 0 => {}
+                TokenType::Class
+                | TokenType::Fun
+                | TokenType::Var
+                | TokenType::Val
+                | TokenType::For
+                | TokenType::If
+                | TokenType::While
+                | TokenType::Print
+                | TokenType::Return
+                    if self.tokens.checkpoint() > start =>
+                {
+                    return
+                }
+                _ => {}
+            }
+            match self.tokens.advance().token_type() {
+                TokenType::Semicolon if depth == 0 => return,
+                TokenType::LeftBrace => depth += 1,
+                _ => {}
+            }
+        }
+    }
+
+    fn report(&mut self, diagnostic: Diagnostic) {
+        self.error_handler.report(diagnostic);
+        self.had_error = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_handling::sink::MemorySink;
+    use crate::frontend::ast::sexpr;
+    use crate::frontend::scanner::scan;
+
+    fn parse(input: &str) -> Result<(Ast, ExprId), ParseError> {
+        let error_handler = ErrorHandler::new(input);
+        let tokens = scan(input, &error_handler).unwrap();
+        parse_expression(tokens, &error_handler)
+    }
+
+    fn assert_parses_to(input: &str, expected: &str) {
+        let (ast, expr) = parse(input).unwrap();
+        assert_eq!(sexpr::expression(&ast, expr), expected);
+    }
+
+    #[test]
+    fn test_literals() {
+        assert_parses_to("1", "1");
+        assert_parses_to("2.5", "2.5");
+        assert_parses_to("\"abc\"", "\"abc\"");
+        assert_parses_to("true", "true");
+        assert_parses_to("nil", "nil");
+    }
+
+    #[test]
+    fn test_arithmetic_precedence() {
+        assert_parses_to("1 + 2 * 3", "(+ 1 (* 2 3))");
+        assert_parses_to("(1 + 2) * 3", "(* (group (+ 1 2)) 3)");
+        assert_parses_to("1 - 2 - 3", "(- (- 1 2) 3)");
+        assert_parses_to("8 / 4 / 2", "(/ (/ 8 4) 2)");
+    }
+
+    #[test]
+    fn test_unary() {
+        assert_parses_to("-1 * 2", "(* (- 1) 2)");
+        assert_parses_to("!!true", "(! (! true))");
+    }
+
+    #[test]
+    fn test_comparison_and_equality() {
+        assert_parses_to("1 < 2 == 3 >= 4", "(== (< 1 2) (>= 3 4))");
+        assert_parses_to("a != b", "(!= a b)");
+    }
+
+    #[test]
+    fn test_logical() {
+        assert_parses_to("a or b and c", "(or a (and b c))");
+        assert_parses_to("a and b or c", "(or (and a b) c)");
+    }
+
+    #[test]
+    fn test_assignment_is_right_associative() {
+        assert_parses_to("a = b = 1 + 2", "(= a (= b (+ 1 2)))");
+        assert_parses_to("a.b = c", "(.= a b c)");
+    }
+
+    #[test]
+    fn test_compound_assignment() {
+        assert_parses_to("a += b -= 1", "(+= a (-= b 1))");
+        assert_parses_to("a.b *= 2 + 3", "(.*= a b (+ 2 3))");
+        assert_parses_to("a /= 2", "(/= a 2)");
+    }
+
+    #[test]
+    fn test_calls_and_properties() {
+        assert_parses_to("f()", "(call f)");
+        assert_parses_to("f(1, g(2))(3)", "(call (call f 1 (call g 2)) 3)");
+        assert_parses_to("a.b.c(1).d", "(. (call (. (. a b) c) 1) d)");
+        assert_parses_to("-a.b", "(- (. a b))");
+        assert_parses_to("this.x", "(. this x)");
+        assert_parses_to("super.f(1)", "(call (super f) 1)");
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_parses_to("int(a / 2.0)", "(int (/ a 2.0))");
+        assert_parses_to("-float(1) * 2", "(* (- (float 1)) 2)");
+        let (ast, expr) = parse("int(x)").unwrap();
+        assert_eq!(ast.span(expr), LineInformation::new(0, 6));
+        assert!(parse("int + 1").is_err());
+    }
+
+    #[test]
+    fn test_spans() {
+        let (ast, expr) = parse("foo(1, 2) + -x").unwrap();
+        assert_eq!(ast.span(expr), LineInformation::new(0, 14));
+        match ast.expr(expr) {
+            ExprKind::Binary { left, right, .. } => {
+                assert_eq!(ast.span(*left), LineInformation::new(0, 9));
+                assert_eq!(ast.span(*right), LineInformation::new(12, 2));
+            }
+            _ => panic!("Expected a binary expression."),
+        }
+    }
+
+    #[test]
+    fn test_invalid_assignment_target() {
+        let input = "1 + 2 = 3";
+        let error_handler = ErrorHandler::new(input);
+        let tokens = scan(input, &error_handler).unwrap();
+        assert!(parse_expression(tokens, &error_handler).is_err());
+        error_handler.flush();
+        assert_eq!(error_handler.error_count(), 1);
+    }
+
+    #[test]
+    fn test_missing_closing_parenthesis() {
+        assert!(parse("(1 + 2").is_err());
+        assert!(parse("f(1, 2").is_err());
+    }
+
+    #[test]
+    fn test_expected_expression() {
+        // The trailing `)` is not reported again after the missing operand.
+        let input = "1 + )";
+        let sink = MemorySink::new();
+        let error_handler = ErrorHandler::new(input).with_sink(Box::new(sink.clone()));
+        let tokens = scan(input, &error_handler).unwrap();
+        assert!(parse_expression(tokens, &error_handler).is_err());
+        error_handler.flush();
+        let diagnostics = sink.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "Expected an expression, found `)`.");
+    }
+
+    #[test]
+    fn test_trailing_tokens() {
+        let input = "1 2";
+        let error_handler = ErrorHandler::new(input);
+        let tokens = scan(input, &error_handler).unwrap();
+        let error = parse_expression(tokens, &error_handler).unwrap_err();
+        assert_eq!(error.to_string(), "Expected end of file, found `2`.");
+    }
+}
@@ -0,0 +1,836 @@
+use crate::error_handling::messages::{Message, MessageId};
+use crate::error_handling::{Applicability, Diagnostic, LineInformation, Severity, Suggestion};
+use crate::frontend::ast::{
+    BinaryOperator, ClassDecl, ExprId, ExprKind, FunctionDecl, Identifier, Parameter, StmtId,
+    StmtKind, TypeExpr, TypeExprKind, TypeParameter, VarDecl,
+};
+use crate::frontend::parser::{ParseError, Parser, Precedence};
+use crate::frontend::scanner::{self, Token, TokenType};
+use crate::frontend::suggest::{suggest_keyword, unknown_identifier};
+
+impl Parser<'_> {
+    // Parse a declaration. After a syntax error, the parser skips to the start of the next
+    // declaration and an error node takes the place of the broken one.
+    pub(super) fn declaration(&mut self) -> StmtId {
+        let checkpoint = self.tokens.checkpoint();
+        let start = *self.tokens.peek(0).line_information();
+        let stmt = self.try_declaration().unwrap_or_else(|_| {
+            self.synchronize(checkpoint);
+            let span = match self.tokens.checkpoint() > checkpoint {
+                true => start.join(&self.tokens.previous()),
+                false => LineInformation::new(start.offset(), 0),
+            };
+            self.ast.alloc_stmt(StmtKind::Error, span)
+        });
+        self.panic_mode = false;
+        stmt
+    }
+
+    fn try_declaration(&mut self) -> Result<StmtId, ParseError> {
+        self.check_misspelled_keyword();
+        let start = *self.tokens.peek(0).line_information();
+        let kind = match self.tokens.peek(0).token_type() {
+            TokenType::Var | TokenType::Val => StmtKind::Var(self.var_declaration()?),
+            TokenType::Fun => {
+                self.tokens.advance();
+                StmtKind::Function(self.function(start)?)
+            }
+            TokenType::Class => StmtKind::Class(self.class_declaration()?),
+            _ => return self.statement(),
+        };
+        let span = start.join(&self.tokens.previous());
+        Ok(self.ast.alloc_stmt(kind, span))
+    }
+
+    // Starts at the `var` or `val` keyword.
+    fn var_declaration(&mut self) -> Result<VarDecl, ParseError> {
+        let start = *self.tokens.peek(0).line_information();
+        let mutable = self.tokens.advance().token_type() == &TokenType::Var;
+        let name = self.identifier(MessageId::ExpectedName)?;
+        let type_annotation = match self.tokens.matches(&TokenType::Colon) {
+            true => Some(self.type_expr()?),
+            false => None,
+        };
+        let initializer = match self.tokens.matches(&TokenType::Equal) {
+            true => Some(self.expression()?),
+            false => None,
+        };
+        self.end_statement()?;
+        Ok(VarDecl {
+            mutable,
+            name,
+            type_annotation,
+            initializer,
+            line_information: start.join(&self.tokens.previous()),
+        })
+    }
+
+    // Starts at the name, s.t. methods can be parsed the same way as functions after `fun`. The
+    // declaration is spanned from start, i.e. `fun` or the name of a method.
+    fn function(&mut self, start: LineInformation) -> Result<FunctionDecl, ParseError> {
+        let name = self.identifier(MessageId::ExpectedName)?;
+        let type_parameters = match self.tokens.check(&TokenType::Less) {
+            true => self.type_parameters()?,
+            false => vec![],
+        };
+        self.skip_implicit_semicolon();
+        self.consume(&TokenType::LeftParenthesis)?;
+        let mut parameters = vec![];
+        if !self.tokens.check(&TokenType::RightParenthesis) {
+            loop {
+                let name = self.identifier(MessageId::ExpectedName)?;
+                self.consume(&TokenType::Colon)?;
+                let type_annotation = self.type_expr()?;
+                let line_information = name
+                    .line_information
+                    .join(&type_annotation.line_information);
+                parameters.push(Parameter {
+                    name,
+                    type_annotation,
+                    line_information,
+                });
+                if !self.tokens.matches(&TokenType::Comma) {
+                    break;
+                }
+            }
+        }
+        self.consume(&TokenType::RightParenthesis)?;
+        let return_type = match self.tokens.matches(&TokenType::Colon) {
+            true => Some(self.type_expr()?),
+            false => None,
+        };
+        self.skip_implicit_semicolon();
+        self.consume(&TokenType::LeftBrace)?;
+        let body = self.block()?;
+        Ok(FunctionDecl {
+            name,
+            type_parameters,
+            parameters,
+            return_type,
+            body,
+            line_information: start.join(&self.tokens.previous()),
+        })
+    }
+
+    // Classes contain field declarations and methods, which are written without `fun`. Both type
+    // parameters and the superclass follow a `<`, but only type parameters are followed by `>`,
+    // `,` or `:`.
+    fn class_declaration(&mut self) -> Result<ClassDecl, ParseError> {
+        self.tokens.advance();
+        let name = self.identifier(MessageId::ExpectedName)?;
+        let has_type_parameters = self.tokens.check(&TokenType::Less)
+            && matches!(self.tokens.peek(1).token_type(), TokenType::Identifier(_))
+            && matches!(
+                self.tokens.peek(2).token_type(),
+                TokenType::Greater | TokenType::Comma | TokenType::Colon
+            );
+        let type_parameters = match has_type_parameters {
+            true => self.type_parameters()?,
+            false => vec![],
+        };
+        let superclass = match self.tokens.matches(&TokenType::Less) {
+            true => Some(self.identifier(MessageId::ExpectedName)?),
+            false => None,
+        };
+        self.skip_implicit_semicolon();
+        self.consume(&TokenType::LeftBrace)?;
+
+        // Broken members are skipped, s.t. the rest of the class is still parsed.
+        let mut fields = vec![];
+        let mut methods = vec![];
+        self.block_depth += 1;
+        while !self.tokens.check(&TokenType::RightBrace) && !self.tokens.check(&TokenType::EOF) {
+            let checkpoint = self.tokens.checkpoint();
+            let result = match self.tokens.peek(0).token_type() {
+                TokenType::Var | TokenType::Val => self.var_declaration().map(|it| fields.push(it)),
+                _ => {
+                    let start = *self.tokens.peek(0).line_information();
+                    self.function(start).map(|it| methods.push(it))
+                }
+            };
+            if result.is_err() {
+                self.synchronize(checkpoint);
+            }
+            self.panic_mode = false;
+        }
+        self.block_depth -= 1;
+        self.consume(&TokenType::RightBrace)?;
+        Ok(ClassDecl {
+            name,
+            type_parameters,
+            superclass,
+            fields,
+            methods,
+        })
+    }
+
+    // Starts at the `<`, e.g. `<T, U: Animal>`.
+    fn type_parameters(&mut self) -> Result<Vec<TypeParameter>, ParseError> {
+        self.consume(&TokenType::Less)?;
+        let mut type_parameters = vec![];
+        loop {
+            let name = self.identifier(MessageId::ExpectedName)?;
+            let bound = match self.tokens.matches(&TokenType::Colon) {
+                true => Some(self.type_expr()?),
+                false => None,
+            };
+            type_parameters.push(TypeParameter { name, bound });
+            if !self.tokens.matches(&TokenType::Comma) {
+                break;
+            }
+        }
+        self.consume(&TokenType::Greater)?;
+        Ok(type_parameters)
+    }
+
+    // A `?` after a type makes it nullable. In `fun(): int?`, it belongs to the return type.
+    fn type_expr(&mut self) -> Result<TypeExpr, ParseError> {
+        let token = self.tokens.peek(0).clone();
+        let li = *token.line_information();
+        let kind = match token.token_type() {
+            TokenType::Fun => None,
+            TokenType::Int => Some(TypeExprKind::Int),
+            TokenType::Float => Some(TypeExprKind::Float),
+            TokenType::Bool => Some(TypeExprKind::Bool),
+            TokenType::String => Some(TypeExprKind::String),
+            TokenType::Identifier(name) => Some(TypeExprKind::Named(Identifier {
+                name: name.clone(),
+                line_information: li,
+            })),
+            found => {
+                return Err(self.error(
+                    &Message::new(MessageId::ExpectedType).arg("found", found),
+                    &li,
+                ))
+            }
+        };
+        let type_expr = match kind {
+            Some(TypeExprKind::Named(name))
+                if self.tokens.peek(1).token_type() == &TokenType::Less =>
+            {
+                self.tokens.advance();
+                self.generic_type(name)?
+            }
+            Some(kind) => {
+                self.tokens.advance();
+                TypeExpr::new(kind, li)
+            }
+            None => self.function_type()?,
+        };
+        match self.tokens.matches(&TokenType::Question) {
+            true => Ok(TypeExpr::new(
+                TypeExprKind::Nullable(Box::new(type_expr)),
+                li.join(&self.tokens.previous()),
+            )),
+            false => Ok(type_expr),
+        }
+    }
+
+    // Starts after the name of the class, e.g. `<int, string>` in `Map<int, string>`.
+    fn generic_type(&mut self, name: Identifier) -> Result<TypeExpr, ParseError> {
+        self.consume(&TokenType::Less)?;
+        let mut arguments = vec![self.type_expr()?];
+        while self.tokens.matches(&TokenType::Comma) {
+            arguments.push(self.type_expr()?);
+        }
+        self.consume(&TokenType::Greater)?;
+        let line_information = name.line_information.join(&self.tokens.previous());
+        Ok(TypeExpr::new(
+            TypeExprKind::Generic { name, arguments },
+            line_information,
+        ))
+    }
+
+    // Starts at `fun`, e.g. `fun(int, string): bool`.
+    fn function_type(&mut self) -> Result<TypeExpr, ParseError> {
+        let start = *self.tokens.advance().line_information();
+        self.consume(&TokenType::LeftParenthesis)?;
+        let mut parameters = vec![];
+        if !self.tokens.check(&TokenType::RightParenthesis) {
+            loop {
+                parameters.push(self.type_expr()?);
+                if !self.tokens.matches(&TokenType::Comma) {
+                    break;
+                }
+            }
+        }
+        self.consume(&TokenType::RightParenthesis)?;
+        let return_type = match self.tokens.matches(&TokenType::Colon) {
+            true => Some(Box::new(self.type_expr()?)),
+            false => None,
+        };
+        let kind = TypeExprKind::Function {
+            parameters,
+            return_type,
+        };
+        Ok(TypeExpr::new(kind, start.join(&self.tokens.previous())))
+    }
+
+    fn statement(&mut self) -> Result<StmtId, ParseError> {
+        let start = *self.tokens.peek(0).line_information();
+        let kind = match self.tokens.peek(0).token_type() {
+            TokenType::Print => {
+                self.tokens.advance();
+                let value = self.expression()?;
+                self.end_statement()?;
+                StmtKind::Print(value)
+            }
+            TokenType::LeftBrace => {
+                self.tokens.advance();
+                StmtKind::Block(self.block()?)
+            }
+            TokenType::If => {
+                self.tokens.advance();
+                self.consume(&TokenType::LeftParenthesis)?;
+                let condition = self.condition()?;
+                self.consume(&TokenType::RightParenthesis)?;
+                self.skip_implicit_semicolon();
+                let then_branch = self.statement()?;
+                let else_branch = match self.tokens.matches(&TokenType::Else) {
+                    true => Some(self.statement()?),
+                    false => None,
+                };
+                StmtKind::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                }
+            }
+            TokenType::While => {
+                self.tokens.advance();
+                self.consume(&TokenType::LeftParenthesis)?;
+                let condition = self.condition()?;
+                self.consume(&TokenType::RightParenthesis)?;
+                self.skip_implicit_semicolon();
+                let body = self.statement()?;
+                StmtKind::While { condition, body }
+            }
+            TokenType::For => self.for_statement()?,
+            TokenType::Return => {
+                self.tokens.advance();
+                let value = match self.tokens.peek(0).token_type() {
+                    TokenType::Semicolon | TokenType::RightBrace => None,
+                    _ => Some(self.expression()?),
+                };
+                self.end_statement()?;
+                StmtKind::Return(value)
+            }
+            _ => {
+                let expression = self.expression()?;
+                self.end_statement()?;
+                StmtKind::Expression(expression)
+            }
+        };
+        let span = start.join(&self.tokens.previous());
+        Ok(self.ast.alloc_stmt(kind, span))
+    }
+
+    fn for_statement(&mut self) -> Result<StmtKind, ParseError> {
+        self.tokens.advance();
+        self.consume(&TokenType::LeftParenthesis)?;
+        let initializer = match self.tokens.peek(0).token_type() {
+            TokenType::Semicolon => {
+                self.tokens.advance();
+                None
+            }
+            TokenType::Var | TokenType::Val => Some(self.try_declaration()?),
+            _ => Some(self.statement()?),
+        };
+        let condition = match self.tokens.check(&TokenType::Semicolon) {
+            true => None,
+            false => Some(self.condition()?),
+        };
+        self.consume(&TokenType::Semicolon)?;
+        let increment = match self.tokens.check(&TokenType::RightParenthesis) {
+            true => None,
+            false => Some(self.expression()?),
+        };
+        self.consume(&TokenType::RightParenthesis)?;
+        self.skip_implicit_semicolon();
+        let body = self.statement()?;
+        Ok(StmtKind::For {
+            initializer,
+            condition,
+            increment,
+            body,
+        })
+    }
+
+    // Parse the statements of a block after its `{`, including the closing `}`.
+    fn block(&mut self) -> Result<Vec<StmtId>, ParseError> {
+        let mut statements = vec![];
+        self.block_depth += 1;
+        while !self.tokens.check(&TokenType::RightBrace) && !self.tokens.check(&TokenType::EOF) {
+            statements.push(self.declaration());
+        }
+        self.block_depth -= 1;
+        self.consume(&TokenType::RightBrace)?;
+        Ok(statements)
+    }
+
+    // A `=` on the top level of a condition is almost always meant to be `==`. It is reported with
+    // a fix-it and parsed as a comparison.
+    fn condition(&mut self) -> Result<ExprId, ParseError> {
+        let left = self.parse_precedence(Precedence::Or)?;
+        if !self.tokens.check(&TokenType::Equal) {
+            return Ok(left);
+        }
+
+        let operator = *self.tokens.advance().line_information();
+        let suggestion = Suggestion::new(
+            &Message::new(MessageId::UseComparison).to_string(),
+            &operator,
+            "==",
+            Applicability::MachineApplicable,
+        );
+        self.report(
+            Diagnostic::from_message(
+                Severity::Error,
+                &Message::new(MessageId::AssignmentInCondition),
+                &operator,
+            )
+            .with_suggestion(suggestion),
+        );
+
+        let right = self.parse_precedence(Precedence::Or)?;
+        let span = self.ast.span(left).join(&self.ast.span(right));
+        Ok(self.ast.alloc_expr(
+            ExprKind::Binary {
+                left,
+                operator: BinaryOperator::Equal,
+                right,
+            },
+            span,
+        ))
+    }
+
+    // An identifier directly followed by another identifier at the start of a statement is most
+    // likely a misspelled keyword, as in `retrun x;` or `fucn f()`. It is reported and parsed as
+    // that keyword. So is one followed by a literal or `!`, as in `retrun 1;`, which can only be
+    // an expression statement keyword.
+    fn check_misspelled_keyword(&mut self) {
+        let TokenType::Identifier(name) = self.tokens.peek(0).token_type() else {
+            return;
+        };
+        let candidates: &[TokenType] = match self.tokens.peek(1).token_type() {
+            TokenType::Identifier(_) => &[
+                TokenType::Var,
+                TokenType::Val,
+                TokenType::Fun,
+                TokenType::Class,
+                TokenType::Print,
+                TokenType::Return,
+            ],
+            TokenType::IntegerValue(_)
+            | TokenType::FloatValue(_)
+            | TokenType::StringValue(_)
+            | TokenType::True
+            | TokenType::False
+            | TokenType::Nil
+            | TokenType::Bang => &[TokenType::Print, TokenType::Return],
+            _ => return,
+        };
+        let Some(keyword) = suggest_keyword(name).and_then(scanner::keyword) else {
+            return;
+        };
+        if !candidates.contains(&keyword) {
+            return;
+        }
+
+        let li = *self.tokens.peek(0).line_information();
+        let (message, suggestion) = unknown_identifier(name, &li, []);
+        let mut diagnostic = Diagnostic::from_message(Severity::Error, &message, &li);
+        if let Some(suggestion) = suggestion {
+            diagnostic = diagnostic.with_suggestion(suggestion);
+        }
+        self.report(diagnostic);
+        self.tokens.replace_current(Token::new(keyword, li));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error_handling::sink::MemorySink;
+    use crate::error_handling::{ErrorHandler, LineInformation};
+    use crate::frontend::ast::{sexpr, Ast, ExprKind, Literal, StmtId, StmtKind, TypeExprKind};
+    use crate::frontend::parser::{parse, ParseError, Parser};
+    use crate::frontend::scanner::scan;
+    use crate::frontend::scanner::TokenType;
+
+    fn parse_program(input: &str) -> Result<Ast, ParseError> {
+        let error_handler = ErrorHandler::new(input);
+        let tokens = scan(input, &error_handler).unwrap();
+        parse(tokens, &error_handler)
+    }
+
+    #[test]
+    fn test_var_declarations() {
+        let ast = parse_program("var a: int = 1;\nval b = \"x\";\nvar c: Point;").unwrap();
+        let program = ast.declarations();
+        assert_eq!(program.len(), 3);
+
+        let StmtKind::Var(a) = ast.stmt(program[0]) else {
+            panic!("Expected a declaration.");
+        };
+        assert!(a.mutable);
+        assert_eq!(a.name.name, "a");
+        assert_eq!(a.type_annotation.as_ref().unwrap().kind, TypeExprKind::Int);
+        assert_eq!(ast.span(program[0]), LineInformation::new(0, 15));
+
+        let StmtKind::Var(b) = ast.stmt(program[1]) else {
+            panic!("Expected a declaration.");
+        };
+        assert!(!b.mutable);
+        assert_eq!(b.type_annotation, None);
+
+        let StmtKind::Var(c) = ast.stmt(program[2]) else {
+            panic!("Expected a declaration.");
+        };
+        assert!(matches!(
+            &c.type_annotation.as_ref().unwrap().kind,
+            TypeExprKind::Named(name) if name.name == "Point"
+        ));
+        assert_eq!(c.initializer, None);
+    }
+
+    #[test]
+    fn test_function_declaration() {
+        let input = "fun myFunction(a: int, b: string): string {\nreturn \"result\";\n}";
+        let ast = parse_program(input).unwrap();
+        let program = ast.declarations();
+        let StmtKind::Function(function) = ast.stmt(program[0]) else {
+            panic!("Expected a function.");
+        };
+        assert_eq!(function.name.name, "myFunction");
+        assert_eq!(function.parameters.len(), 2);
+        assert_eq!(function.parameters[1].name.name, "b");
+        assert_eq!(
+            function.parameters[1].type_annotation.kind,
+            TypeExprKind::String
+        );
+        assert_eq!(
+            function.return_type.as_ref().unwrap().kind,
+            TypeExprKind::String
+        );
+        assert!(matches!(
+            ast.stmt(function.body[0]),
+            StmtKind::Return(Some(_))
+        ));
+        assert_eq!(ast.span(program[0]), LineInformation::new(0, input.len()));
+    }
+
+    #[test]
+    fn test_untyped_parameter() {
+        assert!(parse_program("fun f(a) {}").is_err());
+    }
+
+    #[test]
+    fn test_function_type() {
+        let ast = parse_program("var f: fun(int, fun(string)): bool;").unwrap();
+        let StmtKind::Var(f) = ast.stmt(ast.declarations()[0]) else {
+            panic!("Expected a declaration.");
+        };
+        let type_annotation = f.type_annotation.as_ref().unwrap();
+        assert_eq!(type_annotation.to_string(), "fun(int, fun(string)): bool");
+        assert_eq!(
+            type_annotation.line_information,
+            LineInformation::new(7, 27)
+        );
+        let TypeExprKind::Function { parameters, .. } = &type_annotation.kind else {
+            panic!("Expected a function type.");
+        };
+        assert_eq!(parameters[1].line_information, LineInformation::new(16, 11));
+        assert!(parse_program("var f: fun(int;").is_err());
+    }
+
+    #[test]
+    fn test_type_parameters() {
+        let input = "fun f<T, U: Animal>(x: Box<T>): Map<T, Box<U>?> {}\n\
+                     class A<T> < B {}\n\
+                     class C < D {}";
+        let ast = parse_program(input).unwrap();
+        let program = ast.declarations();
+        let StmtKind::Function(function) = ast.stmt(program[0]) else {
+            panic!("Expected a function.");
+        };
+        let type_parameters: Vec<String> = function
+            .type_parameters
+            .iter()
+            .map(|it| it.to_string())
+            .collect();
+        assert_eq!(type_parameters, vec!["T", "U: Animal"]);
+        let return_type = function.return_type.as_ref().unwrap();
+        assert_eq!(return_type.to_string(), "Map<T, Box<U>?>");
+        assert_eq!(return_type.line_information, LineInformation::new(32, 15));
+        let classes: Vec<(usize, Option<&str>)> = program[1..]
+            .iter()
+            .map(|it| match ast.stmt(*it) {
+                StmtKind::Class(class) => (
+                    class.type_parameters.len(),
+                    class.superclass.as_ref().map(|it| it.name.as_str()),
+                ),
+                _ => panic!("Expected a class."),
+            })
+            .collect();
+        assert_eq!(classes, vec![(1, Some("B")), (0, Some("D"))]);
+    }
+
+    #[test]
+    fn test_nullable_type() {
+        let ast = parse_program("var f: fun(Node?): int?;\nvar g: fun()?;").unwrap();
+        let annotations: Vec<(String, LineInformation)> = ast
+            .declarations()
+            .iter()
+            .map(|it| match ast.stmt(*it) {
+                StmtKind::Var(decl) => {
+                    let annotation = decl.type_annotation.as_ref().unwrap();
+                    (annotation.to_string(), annotation.line_information)
+                }
+                _ => panic!("Expected a declaration."),
+            })
+            .collect();
+        assert_eq!(
+            annotations,
+            vec![
+                ("fun(Node?): int?".to_string(), LineInformation::new(7, 16)),
+                ("fun()?".to_string(), LineInformation::new(32, 6)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_class_declaration() {
+        let input = "class B < A {\n  var x: int;\n  init(x: int) { this.x = x; }\n  \
+                     get(): int { return super.get() + this.x; }\n}";
+        let ast = parse_program(input).unwrap();
+        let program = ast.declarations();
+        let StmtKind::Class(class) = ast.stmt(program[0]) else {
+            panic!("Expected a class.");
+        };
+        assert_eq!(class.name.name, "B");
+        assert_eq!(class.superclass.as_ref().unwrap().name, "A");
+        assert_eq!(class.fields.len(), 1);
+        assert_eq!(class.methods.len(), 2);
+        assert_eq!(class.methods[1].name.name, "get");
+    }
+
+    #[test]
+    fn test_control_flow() {
+        let input = "if (a < 1) print a; else { print b; }\n\
+                     while (true) a = a + 1;\n\
+                     for (var i: int = 0; i < 10; i = i + 1) print i;\n\
+                     for (;;) {}";
+        let ast = parse_program(input).unwrap();
+        let program = ast.declarations();
+        assert!(matches!(
+            ast.stmt(program[0]),
+            StmtKind::If {
+                else_branch: Some(_),
+                ..
+            }
+        ));
+        assert!(matches!(ast.stmt(program[1]), StmtKind::While { .. }));
+        let StmtKind::For {
+            initializer,
+            condition,
+            increment,
+            ..
+        } = ast.stmt(program[2])
+        else {
+            panic!("Expected a for loop.");
+        };
+        assert!(matches!(ast.stmt(initializer.unwrap()), StmtKind::Var(_)));
+        assert!(condition.is_some() && increment.is_some());
+        assert!(matches!(
+            ast.stmt(program[3]),
+            StmtKind::For {
+                initializer: None,
+                condition: None,
+                increment: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_return_without_value() {
+        let ast = parse_program("fun f() { return; }").unwrap();
+        let program = ast.declarations();
+        let StmtKind::Function(function) = ast.stmt(program[0]) else {
+            panic!("Expected a function.");
+        };
+        assert_eq!(ast.stmt(function.body[0]), &StmtKind::Return(None));
+    }
+
+    #[test]
+    fn test_missing_semicolon() {
+        assert!(parse_program("print 1 print 2").is_err());
+        assert!(parse_program("var a = 1 var b = 2;").is_err());
+    }
+
+    #[test]
+    fn test_semicolons_are_optional_at_line_ends() {
+        let input = "var a: int = 1\nfun f(x: int): int\n{\n  if (x < a)\n    return x\n                       return f(x - 1)\n}\nclass A\n{\n}\nprint a\n  + 1\n\
+                     var b: Box<int>\nval c = a >\n  1\nfun g<T>\n(x: T) {}";
+        let ast = parse_program(input).unwrap();
+        assert_eq!(
+            sexpr::print(&ast),
+            "(var a:int 1)\n\
+             (fun f (x:int):int (if (< x a) (return x)) (return (call f (- x 1))))\n\
+             (class A)\n\
+             (print (+ a 1))\n\
+             (var b:Box<int>)\n\
+             (val c (> a 1))\n\
+             (fun g<T> (x:T))\n"
+        );
+    }
+
+    #[test]
+    fn test_closing_brace_ends_statement() {
+        let input =
+            "fun f() { x = 3 }\nfun g(): int { print 1; return 2 }\nclass A { var x: int = 0 }\n\
+                     fun h() { return }";
+        let ast = parse_program(input).unwrap();
+        assert_eq!(
+            sexpr::print(&ast),
+            "(fun f () (expr (= x 3)))\n\
+             (fun g ():int (print 1) (return 2))\n\
+             (class A (var x:int 0))\n\
+             (fun h () (return))\n"
+        );
+    }
+
+    #[test]
+    fn test_assignment_in_condition() {
+        let input = "if (a = 1) print a;";
+        let sink = MemorySink::new();
+        let error_handler = ErrorHandler::new(input).with_sink(Box::new(sink.clone()));
+        let tokens = scan(input, &error_handler).unwrap();
+        assert!(parse(tokens, &error_handler).is_err());
+        error_handler.flush();
+
+        let suggestions = error_handler.suggestions();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].replacement, "==");
+        assert_eq!(suggestions[0].line_information, LineInformation::new(6, 1));
+    }
+
+    #[test]
+    fn test_misspelled_keyword() {
+        let input = "fucn f() { retrun x; }";
+        let sink = MemorySink::new();
+        let error_handler = ErrorHandler::new(input).with_sink(Box::new(sink.clone()));
+        let tokens = scan(input, &error_handler).unwrap();
+        assert!(parse(tokens, &error_handler).is_err());
+        error_handler.flush();
+
+        let diagnostics = sink.diagnostics();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics[0].message,
+            "Unknown identifier `fucn`; did you mean the keyword `fun`?"
+        );
+        assert_eq!(diagnostics[1].suggestions[0].replacement, "return");
+    }
+
+    #[test]
+    fn test_misspelled_keyword_before_literal() {
+        let input = "fun f(): int { retrun 1; }\nprnit !true;\nvall 1;";
+        let sink = MemorySink::new();
+        let error_handler = ErrorHandler::new(input).with_sink(Box::new(sink.clone()));
+        let tokens = scan(input, &error_handler).unwrap();
+        assert!(parse(tokens, &error_handler).is_err());
+        error_handler.flush();
+
+        let diagnostics = sink.diagnostics();
+        assert_eq!(diagnostics[0].suggestions[0].replacement, "return");
+        assert_eq!(diagnostics[1].suggestions[0].replacement, "print");
+        assert_eq!(diagnostics[2].message, "Expected `;`, found `1`.");
+    }
+
+    #[test]
+    fn test_expression_statement() {
+        let ast = parse_program("f(1);").unwrap();
+        let program = ast.declarations();
+        let StmtKind::Expression(expression) = ast.stmt(program[0]) else {
+            panic!("Expected an expression statement.");
+        };
+        assert!(matches!(
+            ast.expr(*expression),
+            ExprKind::Call { arguments, .. }
+                if ast.expr(arguments[0]) == &ExprKind::Literal(Literal::Integer(1))
+        ));
+    }
+
+    // Parse the whole input, and return the recovered declarations and the number of errors.
+    fn parse_recovering(input: &str) -> (Ast, Vec<StmtId>, usize) {
+        let error_handler = ErrorHandler::new(input);
+        let tokens = scan(input, &error_handler).unwrap();
+        let mut parser = Parser::new(tokens, &error_handler);
+        let mut declarations = vec![];
+        while !parser.tokens.check(&TokenType::EOF) {
+            declarations.push(parser.declaration());
+        }
+        error_handler.flush();
+        (parser.ast, declarations, error_handler.error_count())
+    }
+
+    #[test]
+    fn test_reports_every_independent_error() {
+        let input = "var a = ;\nprint (1 + ;\nfun f( { }\nvar ok = 1;\nclass { }";
+        let (ast, declarations, errors) = parse_recovering(input);
+        assert_eq!(errors, 4);
+        assert_eq!(declarations.len(), 5);
+        assert!(matches!(ast.stmt(declarations[0]), StmtKind::Var(decl)
+            if ast.expr(decl.initializer.unwrap()) == &ExprKind::Error));
+        assert_eq!(ast.stmt(declarations[1]), &StmtKind::Error);
+        assert_eq!(ast.stmt(declarations[2]), &StmtKind::Error);
+        assert!(matches!(ast.stmt(declarations[3]), StmtKind::Var(_)));
+        assert_eq!(ast.stmt(declarations[4]), &StmtKind::Error);
+    }
+
+    #[test]
+    fn test_recovery_stays_inside_blocks() {
+        let input = "fun f() {\n  var = 1;\n  print 2;\n}\nprint 3;";
+        let (ast, declarations, errors) = parse_recovering(input);
+        assert_eq!(errors, 1);
+        assert_eq!(declarations.len(), 2);
+        let StmtKind::Function(function) = ast.stmt(declarations[0]) else {
+            panic!("Expected a function.");
+        };
+        assert_eq!(function.body.len(), 2);
+        assert_eq!(ast.stmt(function.body[0]), &StmtKind::Error);
+        assert!(matches!(ast.stmt(function.body[1]), StmtKind::Print(_)));
+    }
+
+    #[test]
+    fn test_recovery_inside_class_body() {
+        let input = "class A {\n  var x: ;\n  f(): int { return 1; }\n}";
+        let (ast, declarations, errors) = parse_recovering(input);
+        assert_eq!(errors, 1);
+        let StmtKind::Class(class) = ast.stmt(declarations[0]) else {
+            panic!("Expected a class.");
+        };
+        assert_eq!(class.fields.len(), 0);
+        assert_eq!(class.methods.len(), 1);
+    }
+
+    #[test]
+    fn test_recovery_skips_nested_blocks() {
+        let inputs = [
+            "if (a b) { print 1; }",
+            "fun f() {\n  while (x y) {\n    print 1;\n  }\n  print 2;\n}\nprint 3;",
+            "class A { var x: int = 0; fun() {} }",
+        ];
+        for input in inputs {
+            let (_, _, errors) = parse_recovering(input);
+            assert_eq!(errors, 1, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_follow_up_errors_are_not_reported() {
+        let (_, _, errors) = parse_recovering("print (1 + + 2;\n}\nprint 1;");
+        assert_eq!(errors, 2);
+    }
+}
@@ -0,0 +1,152 @@
+// Cursor over the tokens produced by `scan`, with arbitrary lookahead and checkpoints to return to
+// after parsing speculatively.
+use crate::error_handling::ice;
+use crate::error_handling::messages::{Message, MessageId};
+use crate::error_handling::{Diagnostic, LineInformation, Severity};
+use crate::frontend::scanner::{Token, TokenType};
+
+pub struct TokenStream {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+// A position in a token stream, s.t. the stream can be rewound to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Checkpoint(usize);
+
+impl TokenStream {
+    // The tokens have to end with EOF, as `scan` produces them.
+    pub fn new(tokens: Vec<Token>) -> TokenStream {
+        assert!(
+            tokens.last().map(|token| token.token_type()) == Some(&TokenType::EOF),
+            "token streams have to end with EOF"
+        );
+        TokenStream {
+            tokens,
+            position: 0,
+        }
+    }
+
+    // The token k tokens after the current one, or EOF if there is none.
+    pub fn peek(&self, k: usize) -> &Token {
+        let index = (self.position + k).min(self.tokens.len() - 1);
+        &self.tokens[index]
+    }
+
+    // The span of the last consumed token.
+    pub fn previous(&self) -> LineInformation {
+        *self.tokens[self.position.saturating_sub(1)].line_information()
+    }
+
+    // Consume the current token. The EOF token is never consumed.
+    pub fn advance(&mut self) -> Token {
+        let token = self.tokens[self.position].clone();
+        ice::set_location(token.line_information().offset());
+        if token.token_type() != &TokenType::EOF {
+            self.position += 1;
+        }
+        token
+    }
+
+    pub fn check(&self, token_type: &TokenType) -> bool {
+        self.peek(0).token_type() == token_type
+    }
+
+    pub fn matches(&mut self, token_type: &TokenType) -> bool {
+        if self.check(token_type) {
+            self.advance();
+            return true;
+        }
+        false
+    }
+
+    // Consume a token of the expected type and return its span, or describe what was found
+    // instead. The stream does not move in that case.
+    pub fn expect(&mut self, expected: &TokenType) -> Result<LineInformation, Box<Diagnostic>> {
+        if self.check(expected) {
+            return Ok(*self.advance().line_information());
+        }
+        let token = self.peek(0);
+        Err(Box::new(Diagnostic::from_message(
+            Severity::Error,
+            &Message::new(MessageId::ExpectedToken)
+                .arg("expected", expected)
+                .arg("found", token.token_type()),
+            token.line_information(),
+        )))
+    }
+
+    // Replace the current token, e.g. by the keyword a misspelled identifier was meant to be.
+    pub fn replace_current(&mut self, token: Token) {
+        self.tokens[self.position] = token;
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.position)
+    }
+
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.position = checkpoint.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_handling::ErrorHandler;
+    use crate::frontend::scanner::scan;
+
+    fn stream(input: &str) -> TokenStream {
+        let error_handler = ErrorHandler::new(input);
+        TokenStream::new(scan(input, &error_handler).unwrap())
+    }
+
+    #[test]
+    fn test_lookahead() {
+        let mut tokens = stream("a < b;");
+        assert_eq!(tokens.peek(1).token_type(), &TokenType::Less);
+        assert_eq!(tokens.peek(3).token_type(), &TokenType::Semicolon);
+        assert_eq!(tokens.peek(10).token_type(), &TokenType::EOF);
+        tokens.advance();
+        assert_eq!(tokens.previous(), LineInformation::new(0, 1));
+        assert!(tokens.matches(&TokenType::Less));
+        assert!(!tokens.matches(&TokenType::Less));
+    }
+
+    #[test]
+    fn test_expect() {
+        let mut tokens = stream("( 1");
+        assert_eq!(
+            tokens.expect(&TokenType::LeftParenthesis),
+            Ok(LineInformation::new(0, 1))
+        );
+        let diagnostic = tokens.expect(&TokenType::RightParenthesis).unwrap_err();
+        assert_eq!(diagnostic.message, "Expected `)`, found `1`.");
+        assert_eq!(diagnostic.line_information, LineInformation::new(2, 1));
+        assert_eq!(tokens.peek(0).token_type(), &TokenType::IntegerValue(1));
+    }
+
+    #[test]
+    fn test_rewind() {
+        let mut tokens = stream("f < int > (x);");
+        let checkpoint = tokens.checkpoint();
+        tokens.advance();
+        tokens.advance();
+        assert!(tokens
+            .expect(&TokenType::Identifier("T".to_string()))
+            .is_err());
+        tokens.rewind(checkpoint);
+        assert_eq!(tokens.checkpoint(), checkpoint);
+        assert_eq!(
+            tokens.advance().token_type(),
+            &TokenType::Identifier("f".to_string())
+        );
+    }
+
+    #[test]
+    fn test_eof_is_not_consumed() {
+        let mut tokens = stream("");
+        assert_eq!(tokens.advance().token_type(), &TokenType::EOF);
+        assert!(tokens.check(&TokenType::EOF));
+    }
+}
@@ -4,7 +4,7 @@ pub struct CharStream<'a> {
 }
 
 impl<'a> CharStream<'a> {
-    pub fn new(text: &'a str) -> CharStream {
+    pub fn new(text: &'a str) -> CharStream<'a> {
         CharStream { text, position: 0 }
     }
 
@@ -29,7 +29,7 @@ impl<'a> CharStream<'a> {
     }
 
     pub fn peek_n(&self, n: usize) -> Option<char> {
-        self.text[self.position + n..].chars().next()
+        self.text.get(self.position + n..)?.chars().next()
     }
 
     // Check if the next character in the stream matches an expected char. If so, consume the
@@ -1,7 +1,12 @@
 mod char_stream;
 mod token;
 
-use crate::error_handling::{ErrorHandler, LineInformation};
+use crate::error_handling::ice;
+use crate::error_handling::lint::{self, Lint};
+use crate::error_handling::messages::{Message, MessageId};
+use crate::error_handling::{
+    Applicability, Diagnostic, ErrorHandler, LineInformation, Severity, Suggestion,
+};
 use crate::frontend::scanner::char_stream::CharStream;
 pub use crate::frontend::scanner::token::TokenType;
 use once_cell::sync::Lazy;
@@ -56,11 +61,26 @@ static KEYWORDS: Lazy<HashMap<&str, TokenType>> = Lazy::new(|| {
     ])
 });
 
+// The spellings of all keywords.
+pub fn keywords() -> impl Iterator<Item = &'static str> {
+    KEYWORDS.keys().copied()
+}
+
+// The token type of the keyword with the given spelling.
+pub fn keyword(name: &str) -> Option<TokenType> {
+    KEYWORDS.get(name).cloned()
+}
+
 struct Scanner<'a> {
     error_handler: &'a ErrorHandler,
+    input: &'a str,
     char_stream: CharStream<'a>,
     token_start: usize,
     had_error: bool,
+    // Whether the last token can end a statement, s.t. a line break after it inserts a semicolon.
+    can_end_statement: bool,
+    // Whether the last token is `>`, which only ends a statement if it closes type arguments.
+    after_greater: bool,
 }
 
 impl<'a> Scanner<'a> {
@@ -68,9 +88,12 @@ impl<'a> Scanner<'a> {
         let char_stream = CharStream::new(input);
         Scanner {
             error_handler,
+            input,
             char_stream,
             token_start: 0,
             had_error: false,
+            can_end_statement: false,
+            after_greater: false,
         }
     }
 
@@ -84,13 +107,21 @@ impl<'a> Scanner<'a> {
             let token = self.next_token()?;
             match token {
                 None => continue,
-                Some(t) => result.push(t),
+                Some(t) => {
+                    self.can_end_statement = can_end_statement(t.token_type());
+                    self.after_greater = t.token_type() == &TokenType::Greater;
+                    result.push(t);
+                }
             }
         }
 
+        let end_of_file = self.char_stream.get_position();
+        if self.can_end_statement {
+            result.push(implicit_semicolon(end_of_file));
+        }
         result.push(Token::new(
             TokenType::EOF,
-            LineInformation::new(self.char_stream.get_position(), 0),
+            LineInformation::new(end_of_file, 0),
         ));
 
         match self.had_error {
@@ -101,6 +132,7 @@ impl<'a> Scanner<'a> {
 
     fn next_token(&mut self) -> Result<Option<Token>, ScannerError> {
         self.token_start = self.char_stream.get_position();
+        ice::set_location(self.token_start);
 
         match self.char_stream.next() {
             None => Err(ScannerError::new(&format!(
@@ -116,11 +148,21 @@ impl<'a> Scanner<'a> {
                 ':' => self.create_token(TokenType::Colon),
                 ',' => self.create_token(TokenType::Comma),
                 '.' => self.create_token(TokenType::Dot),
-                '-' => self.create_token(TokenType::Minus),
-                '+' => self.create_token(TokenType::Plus),
+                '?' => self.create_token(TokenType::Question),
                 ';' => self.create_token(TokenType::Semicolon),
-                '*' => self.create_token(TokenType::Star),
                 // Single or two character tokens.
+                '-' => match self.char_stream.matches('=') {
+                    true => self.create_token(TokenType::MinusEqual),
+                    false => self.create_token(TokenType::Minus),
+                },
+                '+' => match self.char_stream.matches('=') {
+                    true => self.create_token(TokenType::PlusEqual),
+                    false => self.create_token(TokenType::Plus),
+                },
+                '*' => match self.char_stream.matches('=') {
+                    true => self.create_token(TokenType::StarEqual),
+                    false => self.create_token(TokenType::Star),
+                },
                 '/' => match self.char_stream.current_char() {
                     None => self.create_token(TokenType::Slash),
                     Some(x) => match x {
@@ -131,7 +173,17 @@ impl<'a> Scanner<'a> {
                         '*' => {
                             self.char_stream.next();
                             self.process_multiline_comment();
-                            Ok(None)
+                            // A comment spanning lines counts as a line break.
+                            let comment =
+                                &self.input[self.token_start..self.char_stream.get_position()];
+                            match comment.contains('\n') {
+                                true => Ok(self.line_break()),
+                                false => Ok(None),
+                            }
+                        }
+                        '=' => {
+                            self.char_stream.next();
+                            self.create_token(TokenType::SlashEqual)
                         }
                         _ => self.create_token(TokenType::Slash),
                     },
@@ -152,18 +204,21 @@ impl<'a> Scanner<'a> {
                     true => self.create_token(TokenType::LessEqual),
                     false => self.create_token(TokenType::Less),
                 },
-                // Whitespace is ignored.
-                ' ' | '\r' | '\t' | '\n' => Ok(None),
+                // Whitespace is ignored, except for line breaks ending a statement.
+                ' ' | '\r' | '\t' => Ok(None),
+                '\n' => Ok(self.line_break()),
                 // Strings
                 '"' => self.process_string(),
                 // Character is invalid.
                 _ => {
-                    if matches!(c, '0'..='9') {
+                    if c.is_ascii_digit() {
                         self.process_number(c)
                     } else if self.is_valid_id_start(&c) {
                         self.process_identifier(c)
                     } else {
-                        self.process_error(&format!("Unexpected character '{c}'."));
+                        self.process_error(
+                            &Message::new(MessageId::UnexpectedCharacter).arg("character", c),
+                        );
                         Ok(None)
                     }
                 }
@@ -172,32 +227,92 @@ impl<'a> Scanner<'a> {
     }
 
     // Consume characters until end of line (or end of file, whichever is sooner).
+    // Comments of the form `// #[allow(lint_name)]` set lint levels for the rest of the file.
     fn process_comment(&mut self) {
-        while let Some(c) = self.char_stream.next() {
+        // Skip the second slash.
+        self.char_stream.next();
+
+        // The line break is left to be scanned, as it may end a statement.
+        let mut text = "".to_string();
+        while let Some(c) = self.char_stream.current_char() {
             if c == '\n' {
                 break;
             }
+            text.push(c);
+            self.char_stream.next();
+        }
+
+        if let Some((level, names)) = lint::parse_attribute(&text) {
+            let li = LineInformation::new(self.token_start, text.len() + 2);
+            for name in names {
+                if let Some(Severity::Error) =
+                    self.error_handler.set_source_lint_level(name, level, &li)
+                {
+                    self.had_error = true;
+                }
+            }
         }
     }
 
     fn process_multiline_comment(&mut self) {
         while let Some(c1) = self.char_stream.next() {
+            if c1 == '/' && self.char_stream.current_char() == Some('*') {
+                let li = LineInformation::new(self.char_stream.get_position() - 1, 2);
+                self.process_lint(
+                    &lint::NESTED_BLOCK_COMMENT,
+                    &Message::new(MessageId::NestedBlockComment),
+                    &li,
+                );
+                continue;
+            }
             if c1 != '*' {
                 continue;
             }
-            match self.char_stream.current_char() {
-                None => {}
-                Some(c2) => {
-                    if c2 == '/' {
-                        self.char_stream.next();
-                        return;
-                    }
-                }
+            if let Some('/') = self.char_stream.current_char() {
+                self.char_stream.next();
+                return;
             }
         }
 
         self.char_stream.revert();
-        self.process_error("Unterminated multiline comment.");
+        let end_of_file = LineInformation::new(self.input.len(), 0);
+        self.process_error_with_suggestion(
+            &Message::new(MessageId::UnterminatedComment),
+            Suggestion::new(
+                &Message::new(MessageId::CloseComment).to_string(),
+                &end_of_file,
+                "*/",
+                Applicability::MachineApplicable,
+            ),
+        );
+    }
+
+    // Semicolons are optional at the end of a line, as in Go: a line break inserts one if the
+    // line ends with a token that can end a statement. Unlike in Go, no semicolon is inserted if
+    // the next line starts with a token that can only continue the statement, like `.` or `+`.
+    fn line_break(&mut self) -> Option<Token> {
+        if !self.can_end_statement {
+            return None;
+        }
+        let rest = &self.input[self.char_stream.get_position()..];
+        let next_line = rest.trim_start();
+        if continues_statement(next_line) {
+            return None;
+        }
+
+        // `(` and `-` can both start a new statement and continue the previous one as a call or
+        // subtraction. The new statement is taken, but that is likely not what was meant. After
+        // `>`, the parser decides whether the statement ends, see `can_end_statement`.
+        if !self.after_greater && (next_line.starts_with('(') || next_line.starts_with('-')) {
+            let offset = self.input.len() - next_line.len();
+            self.process_lint(
+                &lint::AMBIGUOUS_CONTINUATION,
+                &Message::new(MessageId::AmbiguousContinuation),
+                &LineInformation::new(offset, 1),
+            );
+        }
+        self.can_end_statement = false;
+        Some(implicit_semicolon(self.token_start))
     }
 
     fn process_string(&mut self) -> Result<Option<Token>, ScannerError> {
@@ -209,7 +324,20 @@ impl<'a> Scanner<'a> {
             match r {
                 None => {
                     self.char_stream.revert();
-                    self.process_error("Unterminated string.");
+                    // Most likely, the string was meant to end with the line it started on. Since
+                    // strings may span lines, this is only a guess and not applied by `--fix`.
+                    let end_of_line = self.input[self.token_start..]
+                        .find('\n')
+                        .map_or(self.input.len(), |idx| self.token_start + idx);
+                    self.process_error_with_suggestion(
+                        &Message::new(MessageId::UnterminatedString),
+                        Suggestion::new(
+                            &Message::new(MessageId::CloseString).to_string(),
+                            &LineInformation::new(end_of_line, 0),
+                            "\"",
+                            Applicability::MaybeIncorrect,
+                        ),
+                    );
                     result = Ok(None);
                     break;
                 }
@@ -231,7 +359,7 @@ impl<'a> Scanner<'a> {
 
         // Every number has to start with a flow of digits.
         while let Some(c) = self.char_stream.current_char() {
-            if !(matches!(c, '0'..='9')) {
+            if !c.is_ascii_digit() {
                 break;
             };
             number.push(c);
@@ -242,7 +370,7 @@ impl<'a> Scanner<'a> {
         if is_float {
             number.push(self.char_stream.next().unwrap());
             while let Some(n) = self.char_stream.current_char() {
-                if !(matches!(n, '0'..='9')) {
+                if !n.is_ascii_digit() {
                     break;
                 }
                 number.push(n);
@@ -260,7 +388,7 @@ impl<'a> Scanner<'a> {
         let r1 = self.char_stream.current_char();
         let r2 = self.char_stream.peek();
         match (r1, r2) {
-            (Some(c1), Some(c2)) => c1 == '.' && matches!(c2, '0'..='9'),
+            (Some(c1), Some(c2)) => c1 == '.' && c2.is_ascii_digit(),
             (_, _) => false,
         }
     }
@@ -269,7 +397,7 @@ impl<'a> Scanner<'a> {
         match number.parse::<i64>() {
             Ok(n) => self.create_token(TokenType::IntegerValue(n)),
             Err(_) => {
-                self.process_error(&format!("Cannot parse integer {}", number));
+                self.process_error(&Message::new(MessageId::InvalidInteger).arg("number", number));
                 Ok(None)
             }
         }
@@ -279,7 +407,7 @@ impl<'a> Scanner<'a> {
         match number.parse::<f64>() {
             Ok(n) => self.create_token(TokenType::FloatValue(n)),
             Err(_) => {
-                self.process_error(&format!("Cannot parse float {}", number));
+                self.process_error(&Message::new(MessageId::InvalidFloat).arg("number", number));
                 Ok(None)
             }
         }
@@ -307,7 +435,7 @@ impl<'a> Scanner<'a> {
     }
 
     fn is_valid_id_char(&self, c: &char) -> bool {
-        self.is_valid_id_start(c) || matches!(c, '0'..='9')
+        self.is_valid_id_start(c) || c.is_ascii_digit()
     }
 
     fn create_token(&mut self, token_type: TokenType) -> Result<Option<Token>, ScannerError> {
@@ -322,20 +450,83 @@ impl<'a> Scanner<'a> {
         )
     }
 
-    fn process_error(&mut self, error_msg: &str) {
+    fn process_error(&mut self, message: &Message) {
         self.error_handler
-            .report_error(error_msg, &self.get_line_information());
+            .report_error(message, &self.get_line_information());
+        self.had_error = true;
+    }
+
+    fn process_error_with_suggestion(&mut self, message: &Message, suggestion: Suggestion) {
+        let diagnostic =
+            Diagnostic::from_message(Severity::Error, message, &self.get_line_information())
+                .with_suggestion(suggestion);
+        self.error_handler.report(diagnostic);
         self.had_error = true;
     }
+
+    // Lints only fail the scan if they are denied.
+    fn process_lint(&mut self, lint: &Lint, message: &Message, line_information: &LineInformation) {
+        if let Some(Severity::Error) =
+            self.error_handler
+                .report_lint(lint, message, line_information)
+        {
+            self.had_error = true;
+        }
+    }
+}
+
+// Tokens after which a line break ends the statement. A `>` ends it if it closes type arguments,
+// as in `var b: Box<int>`. The parser skips the semicolon after a `>` that compares.
+fn can_end_statement(token_type: &TokenType) -> bool {
+    matches!(
+        token_type,
+        TokenType::Identifier(_)
+            | TokenType::StringValue(_)
+            | TokenType::FloatValue(_)
+            | TokenType::IntegerValue(_)
+            | TokenType::True
+            | TokenType::False
+            | TokenType::Nil
+            | TokenType::This
+            | TokenType::Int
+            | TokenType::Float
+            | TokenType::Bool
+            | TokenType::String
+            | TokenType::Return
+            | TokenType::RightParenthesis
+            | TokenType::Question
+            | TokenType::Greater
+    )
+}
+
+// Whether a line starting with text can only continue the statement of the previous line.
+fn continues_statement(text: &str) -> bool {
+    let starts_with_word = |word: &str| {
+        text.strip_prefix(word)
+            .is_some_and(|rest| !rest.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_'))
+    };
+    match text.chars().next() {
+        Some('.' | ')' | ',' | ':' | '+' | '*' | '=' | '<' | '>') => true,
+        Some('/') => !text.starts_with("//") && !text.starts_with("/*"),
+        Some('!') => text.starts_with("!="),
+        _ => starts_with_word("and") || starts_with_word("or"),
+    }
+}
+
+// Inserted semicolons are empty, which distinguishes them from written ones.
+fn implicit_semicolon(offset: usize) -> Token {
+    Token::new(TokenType::Semicolon, LineInformation::new(offset, 0))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error_handling::fix::apply_suggestions;
+    use crate::error_handling::sink::MemorySink;
 
     fn simulate_scan_input(input: &str) -> Result<Vec<TokenType>, ScannerError> {
         let error_handler = ErrorHandler::new(input);
-        let tokens = scan(&input, &error_handler)?;
+        let tokens = scan(input, &error_handler)?;
         Ok(tokens
             .iter()
             .map(|token| token.token_type().clone())
@@ -363,6 +554,7 @@ mod tests {
                 TokenType::LeftBrace,
                 TokenType::Return,
                 TokenType::StringValue("result".to_string()),
+                TokenType::Semicolon,
                 TokenType::RightBrace,
                 TokenType::EOF,
             ]
@@ -371,7 +563,7 @@ mod tests {
 
     #[test]
     fn test_single_token_types() {
-        let input = "+ - * / ( ) { } , ; : = ! == < <= > >=".to_string();
+        let input = "+ - * / ( ) { } , ; : ? = ! == < <= > >=".to_string();
         let expected_tokens = vec![
             TokenType::Plus,
             TokenType::Minus,
@@ -384,6 +576,7 @@ mod tests {
             TokenType::Comma,
             TokenType::Semicolon,
             TokenType::Colon,
+            TokenType::Question,
             TokenType::Equal,
             TokenType::Bang,
             TokenType::EqualEqual,
@@ -398,12 +591,16 @@ mod tests {
 
     #[test]
     fn test_two_character_tokens() {
-        let input = "!= == <= >=".to_string();
+        let input = "!= == <= >= += -= *= /=".to_string();
         let expected_tokens = vec![
             TokenType::BangEqual,
             TokenType::EqualEqual,
             TokenType::LessEqual,
             TokenType::GreaterEqual,
+            TokenType::PlusEqual,
+            TokenType::MinusEqual,
+            TokenType::StarEqual,
+            TokenType::SlashEqual,
             TokenType::EOF,
         ];
         assert_eq!(simulate_scan_input(&input).unwrap(), expected_tokens);
@@ -423,6 +620,7 @@ mod tests {
             TokenType::False,
             TokenType::Var,
             TokenType::Identifier("x".to_string()),
+            TokenType::Semicolon,
             TokenType::EOF,
         ];
         assert_eq!(simulate_scan_input(&input).unwrap(), expected_tokens);
@@ -437,6 +635,7 @@ mod tests {
             TokenType::IntegerValue(0),
             TokenType::Minus,
             TokenType::FloatValue(987.65),
+            TokenType::Semicolon,
             TokenType::EOF,
         ];
         assert_eq!(simulate_scan_input(&input).unwrap(), expected_tokens);
@@ -449,11 +648,89 @@ mod tests {
             TokenType::StringValue("hello".to_string()),
             TokenType::StringValue("world".to_string()),
             TokenType::StringValue("123".to_string()),
+            TokenType::Semicolon,
             TokenType::EOF,
         ];
         assert_eq!(simulate_scan_input(&input).unwrap(), expected_tokens);
     }
 
+    #[test]
+    fn test_semicolon_insertion() {
+        let input = "var a = b // comment\nprint (a)\n\nreturn\n}\nx = 1;\ny";
+        let error_handler = ErrorHandler::new(input);
+        let tokens = scan(input, &error_handler).unwrap();
+        let semicolons: Vec<LineInformation> = tokens
+            .iter()
+            .filter(|token| token.token_type() == &TokenType::Semicolon)
+            .map(|token| *token.line_information())
+            .collect();
+        assert_eq!(
+            semicolons,
+            [
+                LineInformation::new(20, 0),
+                LineInformation::new(30, 0),
+                LineInformation::new(38, 0),
+                LineInformation::new(46, 1),
+                LineInformation::new(49, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_continuation_lines() {
+        let input = "a\n  .b()\n  + 1 / 2\n  and c\n  == d /* a\n */ print 1";
+        let tokens = simulate_scan_input(input).unwrap();
+        let semicolons = tokens
+            .iter()
+            .filter(|token_type| **token_type == TokenType::Semicolon)
+            .count();
+        // Only at the block comment spanning a line break and at the end of file.
+        assert_eq!(semicolons, 2);
+    }
+
+    #[test]
+    fn test_semicolon_after_greater() {
+        let input = "var b: Box<int>
+(f)()
+a >
+-1";
+        let sink = MemorySink::new();
+        let error_handler = ErrorHandler::new(input).with_sink(Box::new(sink.clone()));
+        let tokens = scan(input, &error_handler).unwrap();
+        error_handler.flush();
+        let semicolons: Vec<LineInformation> = tokens
+            .iter()
+            .filter(|token| token.token_type() == &TokenType::Semicolon)
+            .map(|token| *token.line_information())
+            .collect();
+        assert_eq!(
+            semicolons,
+            [
+                LineInformation::new(15, 0),
+                LineInformation::new(21, 0),
+                LineInformation::new(25, 0),
+                LineInformation::new(28, 0),
+            ]
+        );
+        // Whether the statement ends is up to the parser, so no continuation is ambiguous.
+        assert!(sink.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn test_ambiguous_continuation_warns() {
+        let input = "var a = b\n(c).d()\nprint a\n-1;";
+        let sink = MemorySink::new();
+        let error_handler = ErrorHandler::new(input).with_sink(Box::new(sink.clone()));
+        scan(input, &error_handler).unwrap();
+        error_handler.flush();
+
+        let diagnostics = sink.diagnostics();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].lint, Some("ambiguous_continuation"));
+        assert_eq!(diagnostics[0].line_information, LineInformation::new(10, 1));
+        assert_eq!(diagnostics[1].line_information, LineInformation::new(26, 1));
+    }
+
     #[test]
     fn test_comments() {
         let input = "// This is a comment".to_string();
@@ -468,6 +745,40 @@ mod tests {
         assert_eq!(simulate_scan_input(&input).unwrap(), expected_tokens);
     }
 
+    #[test]
+    fn test_nested_multiline_comment_warns() {
+        let input = "/* outer /* inner */ var".to_string();
+        let error_handler = ErrorHandler::new(&input);
+        let tokens = scan(&input, &error_handler).unwrap();
+        assert_eq!(tokens.len(), 2);
+        error_handler.flush();
+        assert_eq!(error_handler.warning_count(), 1);
+    }
+
+    #[test]
+    fn test_lint_attribute_comment() {
+        let input = "// #[deny(nested_block_comment)]\n/* /* */".to_string();
+        let error_handler = ErrorHandler::new(&input);
+        assert!(scan(&input, &error_handler).is_err());
+        error_handler.flush();
+        assert_eq!(error_handler.error_count(), 1);
+
+        let input = "// #[allow(nested_block_comment)]\n/* /* */".to_string();
+        let error_handler = ErrorHandler::new(&input);
+        assert!(scan(&input, &error_handler).is_ok());
+        error_handler.flush();
+        assert_eq!(error_handler.warning_count(), 0);
+    }
+
+    #[test]
+    fn test_lint_attribute_unknown_lint() {
+        let input = "// #[allow(no_such_lint)]".to_string();
+        let error_handler = ErrorHandler::new(&input);
+        assert!(scan(&input, &error_handler).is_ok());
+        error_handler.flush();
+        assert_eq!(error_handler.warning_count(), 1);
+    }
+
     #[test]
     fn test_error_handling_invalid_characters() {
         let input = "$ %".to_string();
@@ -476,6 +787,68 @@ mod tests {
         assert!(res.is_err());
     }
 
+    #[test]
+    fn test_error_handling_reports_to_sink() {
+        let input = "var $".to_string();
+        let sink = MemorySink::new();
+        let error_handler = ErrorHandler::new(&input).with_sink(Box::new(sink.clone()));
+        assert!(scan(&input, &error_handler).is_err());
+        error_handler.flush();
+        assert_eq!(
+            sink.diagnostics(),
+            vec![Diagnostic::from_message(
+                Severity::Error,
+                &Message::new(MessageId::UnexpectedCharacter).arg("character", '$'),
+                &LineInformation::new(4, 1)
+            )]
+        );
+    }
+
+    #[test]
+    fn test_fix_unterminated_string() {
+        let input = "print \"unterminated;\nprint 1;".to_string();
+        let error_handler = ErrorHandler::new(&input);
+        assert!(scan(&input, &error_handler).is_err());
+
+        let suggestions = error_handler.suggestions();
+        assert_eq!(suggestions[0].line_information, LineInformation::new(20, 0));
+        assert_eq!(suggestions[0].applicability, Applicability::MaybeIncorrect);
+
+        let (fixed, applied) = apply_suggestions(&input, &suggestions);
+
+        assert_eq!(applied, 0);
+        assert_eq!(fixed, input);
+    }
+
+    #[test]
+    fn test_fix_unterminated_multiline_comment() {
+        let input = "/* unterminated".to_string();
+        let error_handler = ErrorHandler::new(&input);
+        assert!(scan(&input, &error_handler).is_err());
+
+        let (fixed, _) = apply_suggestions(&input, &error_handler.suggestions());
+
+        assert_eq!(fixed, "/* unterminated*/");
+        assert!(simulate_scan_input(&fixed).is_ok());
+    }
+
+    #[test]
+    fn test_error_handling_merges_repeated_characters() {
+        let input = "var $$$$;".to_string();
+        let sink = MemorySink::new();
+        let error_handler = ErrorHandler::new(&input).with_sink(Box::new(sink.clone()));
+        assert!(scan(&input, &error_handler).is_err());
+        error_handler.flush();
+        assert_eq!(
+            sink.diagnostics(),
+            vec![Diagnostic::from_message(
+                Severity::Error,
+                &Message::new(MessageId::UnexpectedCharacter).arg("character", '$'),
+                &LineInformation::new(4, 4)
+            )]
+        );
+    }
+
     #[test]
     fn test_error_handling_unterminated_strings() {
         let input = "\"unterminated string".to_string();
@@ -1,5 +1,8 @@
+use crate::error_handling::messages::{Message, MessageId};
 use crate::error_handling::LineInformation;
+use std::fmt::{Display, Formatter};
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Debug, PartialEq)]
 pub enum TokenType {
     // Single-character tokens
@@ -12,6 +15,7 @@ pub enum TokenType {
     Dot,
     Minus,
     Plus,
+    Question,
     Semicolon,
     Slash,
     Star,
@@ -25,6 +29,10 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    MinusEqual,
+    PlusEqual,
+    SlashEqual,
+    StarEqual,
 
     // Literals
     Identifier(String),
@@ -59,6 +67,67 @@ pub enum TokenType {
     EOF,
 }
 
+// Tokens are displayed as they are written in code, s.t. diagnostics can quote them.
+impl Display for TokenType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            TokenType::LeftParenthesis => "(",
+            TokenType::RightParenthesis => ")",
+            TokenType::LeftBrace => "{",
+            TokenType::RightBrace => "}",
+            TokenType::Colon => ":",
+            TokenType::Comma => ",",
+            TokenType::Dot => ".",
+            TokenType::Minus => "-",
+            TokenType::Plus => "+",
+            TokenType::Question => "?",
+            TokenType::Semicolon => ";",
+            TokenType::Slash => "/",
+            TokenType::Star => "*",
+            TokenType::Bang => "!",
+            TokenType::BangEqual => "!=",
+            TokenType::Equal => "=",
+            TokenType::EqualEqual => "==",
+            TokenType::Greater => ">",
+            TokenType::GreaterEqual => ">=",
+            TokenType::Less => "<",
+            TokenType::LessEqual => "<=",
+            TokenType::MinusEqual => "-=",
+            TokenType::PlusEqual => "+=",
+            TokenType::SlashEqual => "/=",
+            TokenType::StarEqual => "*=",
+            TokenType::Identifier(name) => return write!(f, "`{name}`"),
+            TokenType::StringValue(value) => return write!(f, "`\"{value}\"`"),
+            TokenType::FloatValue(value) => return write!(f, "`{value:?}`"),
+            TokenType::IntegerValue(value) => return write!(f, "`{value}`"),
+            TokenType::And => "and",
+            TokenType::Bool => "bool",
+            TokenType::Class => "class",
+            TokenType::Else => "else",
+            TokenType::False => "false",
+            TokenType::Float => "float",
+            TokenType::Fun => "fun",
+            TokenType::For => "for",
+            TokenType::If => "if",
+            TokenType::Int => "int",
+            TokenType::Nil => "nil",
+            TokenType::Or => "or",
+            TokenType::Print => "print",
+            TokenType::Return => "return",
+            TokenType::String => "string",
+            TokenType::Super => "super",
+            TokenType::This => "this",
+            TokenType::True => "true",
+            TokenType::Val => "val",
+            TokenType::Var => "var",
+            TokenType::While => "while",
+            TokenType::EOF => return write!(f, "{}", Message::new(MessageId::EndOfFile)),
+        };
+        write!(f, "`{text}`")
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Token {
     token_type: TokenType,
     line_information: LineInformation,
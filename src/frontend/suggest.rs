@@ -0,0 +1,175 @@
+use crate::error_handling::messages::{Message, MessageId};
+use crate::error_handling::{Applicability, LineInformation, Suggestion};
+use crate::frontend::scanner::keywords;
+use std::cmp::{max, min};
+
+// Edit distance between a and b, counting insertions, deletions, substitutions and transpositions
+// of adjacent characters as one edit each (optimal string alignment distance).
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // distances[i][j] is the distance between the first i chars of a and the first j chars of b.
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut distance = min(
+                min(distances[i - 1][j] + 1, distances[i][j - 1] + 1),
+                distances[i - 1][j - 1] + cost,
+            );
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = min(distance, distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+// Find the candidate closest to name, if any is close enough to be a plausible misspelling.
+// Ties are broken in favor of the candidate that comes first.
+pub fn find_best_match<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let max_distance = max(name.chars().count() / 3, 1);
+    let mut best: Option<(usize, &str)> = None;
+
+    for candidate in candidates {
+        if candidate == name {
+            continue;
+        }
+        let distance = edit_distance(name, candidate);
+        if distance > max_distance {
+            continue;
+        }
+        if best.is_none_or(|(best_distance, _)| distance < best_distance) {
+            best = Some((distance, candidate));
+        }
+    }
+    best.map(|(_, candidate)| candidate)
+}
+
+// Suggest the keyword an identifier is probably a misspelling of.
+pub fn suggest_keyword(name: &str) -> Option<&'static str> {
+    let mut keywords: Vec<&'static str> = keywords().collect();
+    // Sorted for deterministic results when several keywords are equally close.
+    keywords.sort();
+    find_best_match(name, keywords)
+}
+
+// Build the message and suggestion for an identifier that does not name anything in scope. The
+// closer of the best matching keyword and name in scope is suggested, the name on a tie.
+pub fn unknown_identifier<'a>(
+    name: &str,
+    line_information: &LineInformation,
+    names_in_scope: impl IntoIterator<Item = &'a str>,
+) -> (Message, Option<Suggestion>) {
+    let keyword = suggest_keyword(name);
+    let similar = find_best_match(name, names_in_scope);
+    let (message, replacement) = match (keyword, similar) {
+        (Some(keyword), similar)
+            if similar.is_none_or(|it| edit_distance(name, keyword) < edit_distance(name, it)) =>
+        {
+            (
+                Message::new(MessageId::UnknownIdentifierKeyword)
+                    .arg("name", name)
+                    .arg("keyword", keyword),
+                Some(keyword),
+            )
+        }
+        (_, Some(similar)) => (
+            Message::new(MessageId::UnknownIdentifierSimilar)
+                .arg("name", name)
+                .arg("similar", similar),
+            Some(similar),
+        ),
+        (_, None) => (
+            Message::new(MessageId::UnknownIdentifier).arg("name", name),
+            None,
+        ),
+    };
+
+    let suggestion = replacement.map(|replacement| {
+        Suggestion::new(
+            &Message::new(MessageId::UseReplacement)
+                .arg("replacement", replacement)
+                .to_string(),
+            line_information,
+            replacement,
+            Applicability::MaybeIncorrect,
+        )
+    });
+    (message, suggestion)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_handling::messages::Locale;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("retrun", "return"), 1);
+        assert_eq!(edit_distance("fucn", "fun"), 1);
+        assert_eq!(edit_distance("whiel", "while"), 1);
+    }
+
+    #[test]
+    fn test_suggest_keyword() {
+        assert_eq!(suggest_keyword("retrun"), Some("return"));
+        assert_eq!(suggest_keyword("fucn"), Some("fun"));
+        assert_eq!(suggest_keyword("clas"), Some("class"));
+        assert_eq!(suggest_keyword("counter"), None);
+        assert_eq!(suggest_keyword("return"), None);
+    }
+
+    #[test]
+    fn test_find_best_match_prefers_closest() {
+        let candidates = ["counter", "count", "amount"];
+        assert_eq!(find_best_match("coutn", candidates), Some("count"));
+        assert_eq!(find_best_match("xyz", candidates), None);
+    }
+
+    #[test]
+    fn test_unknown_identifier() {
+        let li = LineInformation::new(0, 6);
+
+        let (message, suggestion) = unknown_identifier("retrun", &li, []);
+        assert_eq!(
+            message.render_in(Locale::English),
+            "Unknown identifier `retrun`; did you mean the keyword `return`?"
+        );
+        assert_eq!(suggestion.unwrap().replacement, "return");
+
+        let (message, _) = unknown_identifier("countr", &li, ["counter"]);
+        assert_eq!(
+            message.render_in(Locale::English),
+            "Unknown identifier `countr`; did you mean `counter`?"
+        );
+
+        // Names in scope win ties with keywords, but not against closer ones.
+        let (_, suggestion) = unknown_identifier("forn", &li, ["form"]);
+        assert_eq!(suggestion.unwrap().replacement, "form");
+        let (_, suggestion) = unknown_identifier("retrun", &li, ["returns"]);
+        assert_eq!(suggestion.unwrap().replacement, "return");
+
+        let (message, suggestion) = unknown_identifier("zzz", &li, ["counter"]);
+        assert_eq!(
+            message.render_in(Locale::English),
+            "Unknown identifier `zzz`."
+        );
+        assert_eq!(suggestion, None);
+    }
+}
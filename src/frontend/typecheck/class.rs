@@ -0,0 +1,696 @@
+// Classes are nominal types. Members are looked up in the class of an instance and then along the
+// chain of its superclasses. Members overriding inherited ones have to be compatible with them.
+use crate::error_handling::messages::{Message, MessageId};
+use crate::error_handling::{Applicability, Diagnostic, Label, Severity, Suggestion};
+use crate::frontend::ast::{
+    BinaryOperator, ClassDecl, ExprId, ExprKind, FunctionDecl, Identifier, StmtId, StmtKind,
+};
+use crate::frontend::suggest::find_best_match;
+use crate::frontend::typecheck::generics::split_function_type;
+use crate::frontend::typecheck::{Binding, BindingKind, TypeChecker};
+use crate::frontend::types::{ClassType, ParameterType, Type};
+use std::collections::HashMap;
+use std::ptr;
+use std::rc::Rc;
+
+// A class together with the members it declares itself, i.e. without the inherited ones.
+pub(super) struct Class<'ast> {
+    pub(super) class_type: Rc<ClassType>,
+    members: HashMap<String, Binding<'ast>>,
+}
+
+impl<'ast> TypeChecker<'_, 'ast> {
+    // Declare the classes among statements sharing a scope, s.t. they can be used as types and
+    // superclasses before their declaration. Their members are declared by `declare_members`.
+    pub(super) fn declare_classes(&mut self, classes: &[(StmtId, &'ast ClassDecl)]) {
+        for (stmt, class) in classes {
+            let binding = Binding {
+                binding_type: Type::Error,
+                origin: None,
+                immutable: None,
+                kind: BindingKind::Class(*stmt),
+                narrowable: false,
+                narrowed_from: None,
+            };
+            self.declare(&class.name, binding);
+        }
+        for (stmt, _) in classes {
+            self.resolve_class(*stmt, &mut vec![]);
+        }
+    }
+
+    // Create the type of a class after the type of its superclass. The classes whose superclasses
+    // are being resolved are kept in resolving, to detect classes inheriting from themselves.
+    fn resolve_class(
+        &mut self,
+        stmt: StmtId,
+        resolving: &mut Vec<StmtId>,
+    ) -> Option<Rc<ClassType>> {
+        if let Some(class) = self.classes.get(stmt) {
+            return Some(class.class_type.clone());
+        }
+        let class = self.class_declaration(stmt);
+        resolving.push(stmt);
+        let superclass = class
+            .superclass
+            .as_ref()
+            .and_then(|superclass| self.resolve_superclass(class, superclass, resolving));
+        resolving.pop();
+        let class_type = Rc::new(ClassType {
+            name: class.name.name.clone(),
+            declaration: stmt,
+            superclass,
+            type_parameters: self.new_type_parameters(&class.name, &class.type_parameters),
+        });
+        self.classes.insert(
+            stmt,
+            Class {
+                class_type: class_type.clone(),
+                members: HashMap::new(),
+            },
+        );
+        Some(class_type)
+    }
+
+    fn resolve_superclass(
+        &mut self,
+        class: &ClassDecl,
+        superclass: &Identifier,
+        resolving: &mut Vec<StmtId>,
+    ) -> Option<Rc<ClassType>> {
+        let binding = self.lookup(superclass)?;
+        let message = match binding.kind {
+            BindingKind::Class(declaration) if !resolving.contains(&declaration) => {
+                let superclass_type = self.resolve_class(declaration, resolving)?;
+                return self
+                    .check_superclass_not_generic(superclass, &superclass_type)
+                    .then_some(superclass_type);
+            }
+            BindingKind::Class(_) => {
+                Message::new(MessageId::InheritanceCycle).arg("name", &class.name.name)
+            }
+            _ => Message::new(MessageId::NotAClass).arg("name", &superclass.name),
+        };
+        self.report(Diagnostic::from_message(
+            Severity::Error,
+            &message,
+            &superclass.line_information,
+        ));
+        None
+    }
+
+    // Declare the fields and methods of classes. Classes are called to construct instances, so
+    // their bindings get the type of a function taking the parameters of `init`, which is generic
+    // for generic classes. Bounds of type parameters are resolved first, since the types of members
+    // may instantiate any of the classes.
+    pub(super) fn declare_members(&mut self, classes: &[(StmtId, &'ast ClassDecl)]) {
+        for (stmt, class) in classes {
+            if let Some(class_type) = self.classes.get(*stmt).map(|it| it.class_type.clone()) {
+                self.resolve_bounds(&class.type_parameters, &class_type.type_parameters);
+            }
+        }
+        for (stmt, class) in classes {
+            let Some(class_type) = self.classes.get(*stmt).map(|it| it.class_type.clone()) else {
+                continue;
+            };
+            let members = self.with_type_parameters(class_type.type_parameters.clone(), |it| {
+                it.class_members(class)
+            });
+            self.classes.insert(
+                *stmt,
+                Class {
+                    class_type,
+                    members,
+                },
+            );
+        }
+
+        for (stmt, class) in classes {
+            let Some(class_type) = self.classes.get(*stmt).map(|it| it.class_type.clone()) else {
+                continue;
+            };
+            let parameters = self
+                .find_member(&class_type, "init")
+                .and_then(|init| split_function_type(&init.binding_type))
+                .map_or(vec![], |(_, parameters, _)| parameters);
+            let constructor = Type::Function {
+                parameters,
+                return_type: Box::new(class_type.this_type()),
+            };
+            let constructor = match class_type.type_parameters.is_empty() {
+                true => constructor,
+                false => Type::Generic {
+                    type_parameters: class_type.type_parameters.clone(),
+                    function: Box::new(constructor),
+                },
+            };
+            let scope = self
+                .scopes
+                .last_mut()
+                .expect("The global scope is never left.");
+            if let Some(binding) = scope.get_mut(&class.name.name) {
+                if matches!(binding.kind, BindingKind::Class(declaration) if declaration == *stmt) {
+                    binding.binding_type = constructor;
+                }
+            }
+        }
+    }
+
+    fn class_members(&mut self, class: &'ast ClassDecl) -> HashMap<String, Binding<'ast>> {
+        let mut members = HashMap::new();
+        for field in &class.fields {
+            let binding = self.check_var_decl(field);
+            self.declare_member(&mut members, class, &field.name, binding);
+        }
+        for method in &class.methods {
+            let binding = Binding {
+                binding_type: self.function_type(method),
+                origin: None,
+                immutable: None,
+                kind: BindingKind::Function(method),
+                narrowable: false,
+                narrowed_from: None,
+            };
+            self.declare_member(&mut members, class, &method.name, binding);
+        }
+        members
+    }
+
+    // Members of a class have to have distinct names. Only the first one of a name is kept.
+    fn declare_member(
+        &mut self,
+        members: &mut HashMap<String, Binding<'ast>>,
+        class: &ClassDecl,
+        name: &Identifier,
+        binding: Binding<'ast>,
+    ) {
+        if members.contains_key(&name.name) {
+            let message = Message::new(MessageId::MemberRedeclared)
+                .arg("name", &name.name)
+                .arg("class", &class.name.name);
+            self.report(Diagnostic::from_message(
+                Severity::Error,
+                &message,
+                &name.line_information,
+            ));
+            return;
+        }
+        members.insert(name.name.clone(), binding);
+    }
+
+    pub(super) fn check_class(&mut self, stmt: StmtId, class: &'ast ClassDecl) {
+        let Some(class_type) = self.classes.get(stmt).map(|it| it.class_type.clone()) else {
+            return;
+        };
+        if let Some(superclass) = &class_type.superclass {
+            self.check_overrides(class, &class_type, superclass);
+        }
+        self.check_fields_initialized(class, &class_type);
+        self.enclosing_classes.push(class_type.clone());
+        self.with_type_parameters(class_type.type_parameters.clone(), |it| {
+            for method in &class.methods {
+                let method_type = it.method_type(&class_type, method);
+                it.check_function(method, &method_type);
+            }
+        });
+        self.enclosing_classes.pop();
+    }
+
+    // Fields without initializer have to be assigned in `init`, unless they are nullable. Only
+    // assignments directly in its body count, not those in branches or loops.
+    fn check_fields_initialized(&mut self, class: &ClassDecl, class_type: &Rc<ClassType>) {
+        let assigned: Vec<&str> = class
+            .methods
+            .iter()
+            .filter(|method| method.name.name == "init")
+            .flat_map(|init| &init.body)
+            .filter_map(|stmt| match self.ast.stmt(*stmt) {
+                StmtKind::Expression(expr) => match self.ast.expr(*expr) {
+                    ExprKind::Set {
+                        object,
+                        name,
+                        operator: None,
+                        ..
+                    } if matches!(self.ast.expr(*object), ExprKind::This) => {
+                        Some(name.name.as_str())
+                    }
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        for field in &class.fields {
+            if field.initializer.is_some() || assigned.contains(&field.name.name.as_str()) {
+                continue;
+            }
+            let Some(member) = self.find_member(class_type, &field.name.name) else {
+                continue;
+            };
+            if Type::Nil.is_assignable_to(&member.binding_type) {
+                continue;
+            }
+            let message = Message::new(MessageId::UninitializedField)
+                .arg("name", &field.name.name)
+                .arg("type", &member.binding_type);
+            self.report(Diagnostic::from_message(
+                Severity::Error,
+                &message,
+                &field.name.line_information,
+            ));
+        }
+    }
+
+    // The type a method was declared with. Methods that were not declared because of a duplicate
+    // name are resolved again, s.t. they can still be checked.
+    fn method_type(&mut self, class_type: &Rc<ClassType>, method: &FunctionDecl) -> Type {
+        match self.find_member(class_type, &method.name.name) {
+            Some(Binding {
+                binding_type,
+                kind: BindingKind::Function(declaration),
+                ..
+            }) if std::ptr::eq(*declaration, method) => binding_type.clone(),
+            _ => self.function_type(method),
+        }
+    }
+
+    // Fields cannot be redeclared in subclasses. Methods can be overridden by methods whose type is
+    // assignable to the overridden one, except for `init`, which may take different parameters.
+    fn check_overrides(
+        &mut self,
+        class: &ClassDecl,
+        class_type: &Rc<ClassType>,
+        superclass: &Rc<ClassType>,
+    ) {
+        let members = class.fields.iter().map(|field| (&field.name, None)).chain(
+            class
+                .methods
+                .iter()
+                .map(|method| (&method.name, Some(method))),
+        );
+        for (name, method) in members {
+            let Some(declaring_class) = self.declaring_class(superclass, &name.name) else {
+                continue;
+            };
+            let Some(inherited) = self.find_member(&declaring_class, &name.name).cloned() else {
+                continue;
+            };
+            let diagnostic = match (method, inherited.kind) {
+                (Some(method), BindingKind::Function(overridden)) => {
+                    if method.name.name == "init" {
+                        continue;
+                    }
+                    let method_type = self.method_type(class_type, method);
+                    if method_type.is_assignable_to(&inherited.binding_type) {
+                        continue;
+                    }
+                    let message = Message::new(MessageId::IncompatibleOverride)
+                        .arg("name", &name.name)
+                        .arg("found", &method_type)
+                        .arg("expected", &inherited.binding_type)
+                        .arg("class", &declaring_class.name);
+                    Diagnostic::from_message(Severity::Error, &message, &name.line_information)
+                        .with_label(Label::from_message(
+                            &Message::new(MessageId::DeclaredHere),
+                            &overridden.name.line_information,
+                        ))
+                }
+                _ => {
+                    let message = Message::new(MessageId::MemberRedeclared)
+                        .arg("name", &name.name)
+                        .arg("class", &declaring_class.name);
+                    Diagnostic::from_message(Severity::Error, &message, &name.line_information)
+                }
+            };
+            self.report(diagnostic);
+        }
+    }
+
+    pub(super) fn check_get(&mut self, object: ExprId, name: &Identifier) -> Type {
+        let object_type = self.check_expr(object);
+        self.member(object, &object_type, name)
+            .map_or(Type::Error, |member| member.binding_type)
+    }
+
+    // Assignments to fields are checked like assignments to variables. Fields declared with `val`
+    // can only be assigned to in `init`.
+    pub(super) fn check_set(
+        &mut self,
+        expr: ExprId,
+        object: ExprId,
+        name: &Identifier,
+        operator: Option<BinaryOperator>,
+        value: ExprId,
+    ) -> Type {
+        let object_type = self.check_expr(object);
+        let Some(member) = self.member(object, &object_type, name) else {
+            self.check_expr(value);
+            return Type::Error;
+        };
+        if let BindingKind::Function(method) = member.kind {
+            let message = Message::new(MessageId::AssignToMethod).arg("name", &name.name);
+            let diagnostic =
+                Diagnostic::from_message(Severity::Error, &message, &self.ast.span(expr))
+                    .with_label(Label::from_message(
+                        &Message::new(MessageId::DeclaredHere),
+                        &method.name.line_information,
+                    ));
+            self.report(diagnostic);
+            self.check_expr(value);
+            return Type::Error;
+        }
+        // Only the class's own `init` may assign `val` fields, not functions nested in it that are
+        // named `init` as well.
+        let own_init = self.enclosing_classes.last().and_then(|class| {
+            self.class_declaration(class.declaration)
+                .methods
+                .iter()
+                .find(|method| method.name.name == "init")
+        });
+        let initializing = matches!(self.ast.expr(object), ExprKind::This)
+            && own_init.is_some_and(|init| {
+                self.functions
+                    .last()
+                    .is_some_and(|(function, _)| ptr::eq(*function, init))
+            });
+        if !initializing {
+            self.check_mutable(expr, name, &member);
+        }
+        match operator {
+            Some(operator) => self.check_compound_assignment(expr, name, operator, value, &member),
+            None => _ = self.expect(value, &member.binding_type, member.origin.clone()),
+        }
+        member.binding_type
+    }
+
+    pub(super) fn check_this(&mut self, expr: ExprId) -> Type {
+        match self.enclosing_classes.last() {
+            Some(class) => class.this_type(),
+            None => {
+                self.report(Diagnostic::from_message(
+                    Severity::Error,
+                    &Message::new(MessageId::ThisOutsideClass),
+                    &self.ast.span(expr),
+                ));
+                Type::Error
+            }
+        }
+    }
+
+    pub(super) fn check_super(&mut self, expr: ExprId, method: &Identifier) -> Type {
+        let superclass = self
+            .enclosing_classes
+            .last()
+            .and_then(|class| class.superclass.clone());
+        let Some(superclass) = superclass else {
+            self.report(Diagnostic::from_message(
+                Severity::Error,
+                &Message::new(MessageId::SuperWithoutSuperclass),
+                &self.ast.span(expr),
+            ));
+            return Type::Error;
+        };
+        self.member(expr, &Type::Instance(superclass, vec![]), method)
+            .map_or(Type::Error, |member| member.binding_type)
+    }
+
+    // The member a property of object refers to. Unknown members and properties of values other
+    // than instances are reported.
+    fn member(
+        &mut self,
+        object: ExprId,
+        object_type: &Type,
+        name: &Identifier,
+    ) -> Option<Binding<'ast>> {
+        let (class, arguments) = match object_type {
+            Type::Instance(class, arguments) => (class, arguments),
+            Type::Parameter(parameter) if parameter.bound().is_some() => {
+                let bound = parameter.bound().cloned()?;
+                return self.member(object, &bound, name);
+            }
+            Type::Error => return None,
+            Type::Nullable(_) => {
+                let message = Message::new(MessageId::NullableMember)
+                    .arg("name", &name.name)
+                    .arg("type", object_type);
+                let diagnostic =
+                    Diagnostic::from_message(Severity::Error, &message, &name.line_information);
+                self.report(self.with_nil_check_note(diagnostic, object));
+                return None;
+            }
+            object_type => {
+                let message = Message::new(MessageId::NoMembers).arg("type", object_type);
+                self.report(Diagnostic::from_message(
+                    Severity::Error,
+                    &message,
+                    &name.line_information,
+                ));
+                return None;
+            }
+        };
+        // Members of generic classes are instantiated with the type arguments of the instance.
+        if let Some(member) = self.find_member(class, &name.name) {
+            let arguments: Vec<(Rc<ParameterType>, Type)> = class
+                .type_parameters
+                .iter()
+                .cloned()
+                .zip(arguments.iter().cloned())
+                .collect();
+            return Some(Binding {
+                binding_type: member.binding_type.substitute(&arguments),
+                ..member.clone()
+            });
+        }
+
+        let names = self.member_names(class);
+        let message = Message::new(MessageId::UnknownMember)
+            .arg("class", &class.name)
+            .arg("name", &name.name);
+        let mut diagnostic =
+            Diagnostic::from_message(Severity::Error, &message, &name.line_information);
+        if let Some(similar) = find_best_match(&name.name, names.iter().copied()) {
+            diagnostic = diagnostic.with_suggestion(Suggestion::new(
+                &Message::new(MessageId::UseReplacement)
+                    .arg("replacement", similar)
+                    .to_string(),
+                &name.line_information,
+                similar,
+                Applicability::MaybeIncorrect,
+            ));
+        }
+        if !names.is_empty() {
+            let members: Vec<String> = names.iter().map(|it| format!("`{it}`")).collect();
+            let note = Message::new(MessageId::AvailableMembers).arg("members", members.join(", "));
+            diagnostic = diagnostic.with_note(&note.to_string());
+        }
+        self.report(diagnostic);
+        None
+    }
+
+    pub(super) fn find_member(&self, class: &Rc<ClassType>, name: &str) -> Option<&Binding<'ast>> {
+        let declaring_class = self.declaring_class(class, name)?;
+        self.classes
+            .get(declaring_class.declaration)?
+            .members
+            .get(name)
+    }
+
+    // The class declaring the member of the given name, which is class or one of its superclasses.
+    fn declaring_class(&self, class: &Rc<ClassType>, name: &str) -> Option<Rc<ClassType>> {
+        let mut current = Some(class);
+        while let Some(class) = current {
+            let declares = self
+                .classes
+                .get(class.declaration)
+                .is_some_and(|it| it.members.contains_key(name));
+            if declares {
+                return Some(class.clone());
+            }
+            current = class.superclass.as_ref();
+        }
+        None
+    }
+
+    // The names of all members of a class including the inherited ones, sorted s.t. they can be
+    // listed in diagnostics.
+    fn member_names(&self, class: &Rc<ClassType>) -> Vec<&str> {
+        let mut names = vec![];
+        let mut current = Some(class);
+        while let Some(class) = current {
+            if let Some(class) = self.classes.get(class.declaration) {
+                names.extend(class.members.keys().map(|name| name.as_str()));
+            }
+            current = class.superclass.as_ref();
+        }
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    fn class_declaration(&self, stmt: StmtId) -> &'ast ClassDecl {
+        match self.ast.stmt(stmt) {
+            StmtKind::Class(class) => class,
+            _ => unreachable!("Classes are bound to class declarations."),
+        }
+    }
+
+    // The constructor of a class is its `init` method, if it has one.
+    pub(super) fn constructor(&self, stmt: StmtId) -> Option<&'ast FunctionDecl> {
+        let class = self.classes.get(stmt)?;
+        match self.find_member(&class.class_type, "init")?.kind {
+            BindingKind::Function(init) => Some(init),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error_handling::LineInformation;
+    use crate::frontend::typecheck::tests::{check_diagnostics, check_messages};
+
+    #[test]
+    fn test_classes() {
+        let input = "class Point {\n  \
+                       val x: int;\n  \
+                       var y: int = 0;\n  \
+                       init(x: int) { this.x = x; }\n  \
+                       norm(): int { return this.x * this.x + this.y * this.y; }\n\
+                     }\n\
+                     val p: Point = Point(3);\n\
+                     p.y = 4;\n\
+                     print p.norm() + p.x;";
+        assert!(check_messages(input).is_empty());
+    }
+
+    #[test]
+    fn test_member_errors() {
+        let input = "class Point {\n  \
+                       val x: int = 0;\n  \
+                       norm(): int { return this.x; }\n\
+                     }\n\
+                     val p = Point();\n\
+                     p.x = 1;\n\
+                     p.norm = 2;\n\
+                     p.y;\n\
+                     p.nrom();\n\
+                     p.x = \"a\";\n\
+                     1.x;\n\
+                     Point(1);\n\
+                     this.x;";
+        assert_eq!(
+            check_messages(input),
+            vec![
+                "Cannot assign to `x`, since it is declared with `val`.",
+                "Cannot assign to method `norm`.",
+                "`Point` has no member `y`.",
+                "`Point` has no member `nrom`.",
+                "Cannot assign to `x`, since it is declared with `val`.",
+                "Expected `int`, found `string`.",
+                "Only instances of classes have members, found `int`.",
+                "Expected 0 arguments, found 1.",
+                "`this` can only be used inside of a class.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_val_fields_are_only_assigned_in_init() {
+        let input = "class A {\n  \
+                       val x: int;\n  \
+                       init() { this.x = 1; fun init() { this.x = 2; } init(); }\n  \
+                       reset() { fun init() { this.x = 3; } init(); }\n\
+                     }";
+        assert_eq!(
+            check_messages(input),
+            vec![
+                "Cannot assign to `x`, since it is declared with `val`.",
+                "Cannot assign to `x`, since it is declared with `val`.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_member_lists_members() {
+        let input = "class A { var count: int = 0; }\n\
+                     class B < A { reset() { this.cuont = 0; } }";
+        let diagnostics = check_diagnostics(input);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].notes,
+            vec!["available members: `count`, `reset`"]
+        );
+        assert_eq!(diagnostics[0].suggestions[0].replacement, "count");
+    }
+
+    #[test]
+    fn test_subclasses() {
+        let input = "class Animal {\n  \
+                       val name: string;\n  \
+                       init(name: string) { this.name = name; }\n  \
+                       speak(): string { return this.name; }\n\
+                     }\n\
+                     class Dog < Animal {\n  \
+                       init() { this.name = \"dog\"; }\n  \
+                       speak(): string { return super.speak() + \"!\"; }\n\
+                     }\n\
+                     fun greet(animal: Animal): string { return animal.speak(); }\n\
+                     val dog: Animal = Dog();\n\
+                     print greet(Dog()) + dog.name;\n\
+                     val animal: Dog = Animal(\"cat\");";
+        assert_eq!(
+            check_messages(input),
+            vec!["Expected `Dog`, found `Animal`."]
+        );
+    }
+
+    #[test]
+    fn test_overrides() {
+        let input = "class A {\n  \
+                       var x: int = 0;\n  \
+                       f(a: int): int { return a; }\n\
+                     }\n\
+                     class B < A {\n  \
+                       var x: int = 1;\n  \
+                       f(a: string): int { return 0; }\n\
+                     }";
+        let diagnostics = check_diagnostics(input);
+        let messages: Vec<&str> = diagnostics.iter().map(|it| it.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "`x` is already declared in `A`.",
+                "`f` has type `fun(string): int`, which is not compatible with `fun(int): int` \
+                 in `A`.",
+            ]
+        );
+        assert_eq!(
+            diagnostics[1].labels[0].line_information,
+            LineInformation::new(30, 1)
+        );
+    }
+
+    #[test]
+    fn test_inheritance_errors() {
+        let input = "class A < B {}\nclass B < A {}\nval c = 1;\n{ class D < c {} }\n\
+                     class E { f() { super.f(); } }";
+        assert_eq!(
+            check_messages(input),
+            vec![
+                "`B` cannot inherit from itself.",
+                "`c` is not a class.",
+                "`super` can only be used in classes with a superclass.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classes_can_be_used_before_their_declaration() {
+        let input = "fun make(): Node { return Node(nil); }\n\
+                     class Node {\n  \
+                       var next: Node?;\n  \
+                       init(next: Node?) { this.next = next; }\n\
+                     }\n\
+                     print make().next;";
+        assert!(check_messages(input).is_empty());
+    }
+}
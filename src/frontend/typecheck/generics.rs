@@ -0,0 +1,348 @@
+// Generic functions and classes. Type parameters are in scope inside of the declaring function or
+// class. Calls of generic functions infer the type arguments from the type the result is expected
+// to have and, if that does not determine all of them, from the types of the arguments. Generic
+// classes are instantiated explicitly in types, e.g. `Box<int>`, or by calling their constructor.
+use crate::error_handling::messages::{Message, MessageId};
+use crate::error_handling::{Diagnostic, Label, LineInformation, Severity};
+use crate::frontend::ast::{ExprId, FunctionDecl, Identifier, TypeExpr, TypeParameter};
+use crate::frontend::typecheck::TypeChecker;
+use crate::frontend::types::{infer_type_arguments, ClassType, ParameterType, Type};
+use std::cell::OnceCell;
+use std::rc::Rc;
+
+impl<'ast> TypeChecker<'_, 'ast> {
+    // Type parameters whose bounds are not resolved yet, see `resolve_bounds`.
+    pub(super) fn new_type_parameters(
+        &self,
+        owner: &Identifier,
+        type_parameters: &[TypeParameter],
+    ) -> Vec<Rc<ParameterType>> {
+        type_parameters
+            .iter()
+            .map(|it| {
+                Rc::new(ParameterType {
+                    name: it.name.name.clone(),
+                    owner: owner.name.clone(),
+                    declaration: it.name.line_information,
+                    bound: OnceCell::new(),
+                })
+            })
+            .collect()
+    }
+
+    // Bounds may refer to all type parameters of the same declaration.
+    pub(super) fn resolve_bounds(
+        &mut self,
+        declarations: &[TypeParameter],
+        type_parameters: &[Rc<ParameterType>],
+    ) {
+        self.with_type_parameters(type_parameters.to_vec(), |it| {
+            for (declaration, type_parameter) in declarations.iter().zip(type_parameters) {
+                if let Some(bound) = &declaration.bound {
+                    let bound = it.resolve_type(bound);
+                    _ = type_parameter.bound.set(bound);
+                }
+            }
+        });
+    }
+
+    pub(super) fn with_type_parameters<T>(
+        &mut self,
+        type_parameters: Vec<Rc<ParameterType>>,
+        check: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        let depth = self.type_parameters.len();
+        self.type_parameters.extend(type_parameters);
+        let result = check(self);
+        self.type_parameters.truncate(depth);
+        result
+    }
+
+    pub(super) fn find_type_parameter(&self, name: &str) -> Option<Rc<ParameterType>> {
+        self.type_parameters
+            .iter()
+            .rev()
+            .find(|it| it.name == name)
+            .cloned()
+    }
+
+    // The type of a function, which is generic if the function has type parameters.
+    pub(super) fn function_type(&mut self, function: &FunctionDecl) -> Type {
+        let type_parameters = self.new_type_parameters(&function.name, &function.type_parameters);
+        self.resolve_bounds(&function.type_parameters, &type_parameters);
+        let (parameters, return_type) =
+            self.with_type_parameters(type_parameters.clone(), |it| it.signature(function));
+        let function_type = Type::Function {
+            parameters,
+            return_type: Box::new(return_type),
+        };
+        match type_parameters.is_empty() {
+            true => function_type,
+            false => Type::Generic {
+                type_parameters,
+                function: Box::new(function_type),
+            },
+        }
+    }
+
+    // An instance of a class written as a type. Generic classes need type arguments that satisfy
+    // the bounds of their type parameters.
+    pub(super) fn class_instance(
+        &mut self,
+        type_expr: &TypeExpr,
+        class: Rc<ClassType>,
+        arguments: &[TypeExpr],
+    ) -> Type {
+        let argument_types: Vec<Type> = arguments.iter().map(|it| self.resolve_type(it)).collect();
+        if arguments.len() != class.type_parameters.len() {
+            let message = Message::new(MessageId::TypeArgumentCount)
+                .arg("name", &class.name)
+                .arg("count", class.type_parameters.len())
+                .arg("found", arguments.len());
+            self.report(Diagnostic::from_message(
+                Severity::Error,
+                &message,
+                &type_expr.line_information,
+            ));
+            return Type::Error;
+        }
+        for ((argument, argument_type), parameter) in arguments
+            .iter()
+            .zip(&argument_types)
+            .zip(&class.type_parameters)
+        {
+            self.check_bound(&argument.line_information, argument_type, parameter);
+        }
+        Type::Instance(class, argument_types)
+    }
+
+    fn check_bound(&mut self, span: &LineInformation, argument: &Type, parameter: &ParameterType) {
+        let Some(bound) = parameter.bound() else {
+            return;
+        };
+        if argument.is_assignable_to(bound) {
+            return;
+        }
+        let message = Message::new(MessageId::TypeArgumentBound)
+            .arg("type", argument)
+            .arg("bound", bound)
+            .arg("parameter", &parameter.name);
+        let diagnostic = Diagnostic::from_message(Severity::Error, &message, span).with_label(
+            Label::from_message(
+                &Message::new(MessageId::DeclaredHere),
+                &parameter.declaration,
+            ),
+        );
+        self.report(diagnostic);
+    }
+
+    // The function type a call of a generic function is checked against. Type arguments that can
+    // neither be inferred from the arguments nor from the expected type are reported.
+    pub(super) fn instantiate(
+        &mut self,
+        call: ExprId,
+        type_parameters: &[Rc<ParameterType>],
+        function: &Type,
+        argument_types: &[Type],
+        expected: Option<&Type>,
+    ) -> Type {
+        let Type::Function {
+            parameters,
+            return_type,
+        } = function
+        else {
+            return Type::Error;
+        };
+        // The expected type takes precedence, s.t. e.g. `val a: Box<Animal> = Box(Dog());`
+        // instantiates `Box<Animal>`.
+        let mut inferred = vec![None; type_parameters.len()];
+        if let Some(expected) = expected {
+            infer_type_arguments(return_type, expected, type_parameters, &mut inferred);
+        }
+        let mut from_arguments = vec![None; type_parameters.len()];
+        for (parameter, argument) in parameters.iter().zip(argument_types) {
+            infer_type_arguments(parameter, argument, type_parameters, &mut from_arguments);
+        }
+        for (inferred, from_arguments) in inferred.iter_mut().zip(from_arguments) {
+            if inferred.is_none() {
+                *inferred = from_arguments;
+            }
+        }
+
+        // Calls with the wrong number of arguments are reported by the caller.
+        let span = self.ast.span(call);
+        let complete = argument_types.len() == parameters.len();
+        let mut arguments = vec![];
+        for (parameter, inferred) in type_parameters.iter().zip(inferred) {
+            let argument = match inferred {
+                Some(argument) => {
+                    self.check_bound(&span, &argument, parameter);
+                    argument
+                }
+                None if complete => {
+                    self.report_not_inferred(&span, parameter, return_type);
+                    Type::Error
+                }
+                None => Type::Error,
+            };
+            arguments.push((parameter.clone(), argument));
+        }
+        function.substitute(&arguments)
+    }
+
+    fn report_not_inferred(
+        &mut self,
+        span: &LineInformation,
+        parameter: &ParameterType,
+        return_type: &Type,
+    ) {
+        let message = Message::new(MessageId::CannotInferTypeArgument)
+            .arg("parameter", &parameter.name)
+            .arg("name", &parameter.owner);
+        let mut diagnostic = Diagnostic::from_message(Severity::Error, &message, span).with_label(
+            Label::from_message(
+                &Message::new(MessageId::DeclaredHere),
+                &parameter.declaration,
+            ),
+        );
+        if return_type.mentions(parameter) {
+            let note =
+                Message::new(MessageId::InferFromAnnotation).arg("parameter", &parameter.name);
+            diagnostic = diagnostic.with_note(&note.to_string());
+        }
+        self.report(diagnostic);
+    }
+
+    // Report a generic class used as a superclass. Subclasses could not tell which type arguments
+    // the inherited members are instantiated with.
+    pub(super) fn check_superclass_not_generic(
+        &mut self,
+        superclass: &Identifier,
+        class: &ClassType,
+    ) -> bool {
+        if class.type_parameters.is_empty() {
+            return true;
+        }
+        let message = Message::new(MessageId::GenericSuperclass).arg("name", &superclass.name);
+        self.report(Diagnostic::from_message(
+            Severity::Error,
+            &message,
+            &superclass.line_information,
+        ));
+        false
+    }
+}
+
+// The parameter types, return type and type parameters of a possibly generic function type.
+pub(super) fn split_function_type(
+    function_type: &Type,
+) -> Option<(Vec<Rc<ParameterType>>, Vec<Type>, Type)> {
+    match function_type {
+        Type::Function {
+            parameters,
+            return_type,
+        } => Some((vec![], parameters.clone(), *return_type.clone())),
+        Type::Generic {
+            type_parameters,
+            function,
+        } => {
+            let (_, parameters, return_type) = split_function_type(function)?;
+            Some((type_parameters.clone(), parameters, return_type))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error_handling::LineInformation;
+    use crate::frontend::typecheck::tests::{check_diagnostics, check_messages};
+
+    const DECLARATIONS: &str = "class Animal { val name: string = \"animal\"; }\n\
+                                class Dog < Animal {}\n\
+                                class Box<T> {\n  \
+                                  var value: T;\n  \
+                                  init(value: T) { this.value = value; }\n  \
+                                  map<U>(f: fun(T): U): Box<U> { return Box(f(this.value)); }\n\
+                                }\n\
+                                class Node<T> {\n  \
+                                  val value: T;\n  \
+                                  var next: Node<T>?;\n  \
+                                  init(value: T, next: Node<T>?) { this.value = value; this.next = next; }\n\
+                                }\n\
+                                fun first<T>(node: Node<T>): T { return node.value; }\n\
+                                fun name<T: Animal>(animal: T): string { return animal.name; }\n\
+                                fun empty<T>(): Node<T>? { return nil; }\n\
+                                fun length(s: string): int { return 1; }\n";
+
+    #[test]
+    fn test_generics() {
+        let input = "val b = Box(1);\n\
+                     val n: int = b.value;\n\
+                     val c: Box<int> = Box(\"a\").map(length);\n\
+                     print first(Node(1, Node(2, nil))) + 1;\n\
+                     print name(Dog());\n\
+                     val e: Node<string>? = empty();\n\
+                     val d: Box<Animal> = Box(Dog());\n\
+                     fun id<T>(x: T): T { return x; }\n\
+                     val f: fun(int): int = id;\n\
+                     val names = Box(Dog()).map(name);\n\
+                     val s: string = names.value;\n\
+                     val g: fun(Dog): Animal = id;";
+        assert!(check_messages(&(DECLARATIONS.to_string() + input)).is_empty());
+    }
+
+    #[test]
+    fn test_generic_errors() {
+        let input = "print first(Node(1, nil)) + \"a\";\n\
+                     Box(1).value = \"a\";\n\
+                     name(1);\n\
+                     var x: Box<int, string>?;\n\
+                     var y: Box?;\n\
+                     val z: Box<Animal> = Box(1);\n\
+                     class Sub < Box {}\n\
+                     val w: Box<Dog> = Box(Dog()).map(name);\n\
+                     val v: fun(int): string = name;";
+        assert_eq!(
+            check_messages(&(DECLARATIONS.to_string() + input)),
+            vec![
+                "Cannot apply `+` to `int` and `string`.",
+                "Expected `int`, found `string`.",
+                "`int` does not satisfy the bound `Animal` of `T`.",
+                "`Box` expects 1 type argument, found 2.",
+                "`Box` expects 1 type argument, found 0.",
+                "Expected `Animal`, found `int`.",
+                "`Box` is generic and cannot be inherited from.",
+                "Expected `fun(Dog): Dog`, found `fun<T: Animal>(T): string`.",
+                "Expected `fun(int): string`, found `fun<T: Animal>(T): string`.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inference_failure() {
+        let input = DECLARATIONS.to_string() + "empty();";
+        let diagnostics = check_diagnostics(&input);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "Cannot infer the type argument `T` of `empty`."
+        );
+        assert_eq!(
+            diagnostics[0].line_information,
+            LineInformation::new(input.len() - 8, 7)
+        );
+        let declaration = DECLARATIONS.find("empty<T>").unwrap() + "empty<".len();
+        assert_eq!(
+            diagnostics[0].labels[0].line_information,
+            LineInformation::new(declaration, 1)
+        );
+        assert_eq!(
+            diagnostics[0].notes,
+            vec![
+                "annotate the variable the result is assigned to, s.t. `T` can be inferred from \
+                 its type"
+            ]
+        );
+    }
+}
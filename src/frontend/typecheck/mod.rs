@@ -0,0 +1,812 @@
+// Static type checking of a parsed program. Every expression is assigned a type, which is stored
+// in a side table indexed by node id. Mismatches are reported at the expression and, where there
+// is one, labeled with the annotation or initializer that set the expectation.
+mod class;
+mod generics;
+mod narrowing;
+mod statement;
+
+use crate::error_handling::ice;
+use crate::error_handling::messages::{Message, MessageId};
+use crate::error_handling::{
+    Applicability, Diagnostic, ErrorHandler, Label, LineInformation, Severity, Suggestion,
+};
+use crate::frontend::ast::{
+    Ast, BinaryOperator, ExprId, ExprKind, FunctionDecl, Identifier, Literal, LogicalOperator,
+    NodeMap, StmtId, TypeExpr, TypeExprKind, UnaryOperator,
+};
+use crate::frontend::suggest::unknown_identifier;
+use crate::frontend::typecheck::class::Class;
+use crate::frontend::typecheck::narrowing::assigned_in_functions;
+use crate::frontend::types::{ClassType, ParameterType, Type};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use thiserror::Error;
+
+// Check the types of a program and return the type of every expression and function declaration.
+pub fn check(ast: &Ast, error_handler: &ErrorHandler) -> Result<NodeMap<Type>, TypeError> {
+    let mut checker = TypeChecker::new(ast, error_handler);
+    checker.check_statements(ast.declarations());
+    checker.finish()
+}
+
+#[derive(Error, Debug)]
+#[error("{message:}")]
+pub struct TypeError {
+    message: String,
+}
+
+impl TypeError {
+    fn new(message: &str) -> TypeError {
+        TypeError {
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum BindingKind<'ast> {
+    // Variables and parameters keep their annotation, s.t. calls through values of function type
+    // can point at the declared parameters.
+    Variable(Option<&'ast TypeExpr>),
+    // Kept to point at the declaration of the parameters when a call does not match them.
+    Function(&'ast FunctionDecl),
+    // Classes are bound to their constructor. Kept to find the type and members of the class.
+    Class(StmtId),
+}
+
+// Where the parameters of a called function are declared, to label calls that do not match them.
+struct Signature {
+    declaration: LineInformation,
+    parameters: Vec<LineInformation>,
+}
+
+impl Signature {
+    fn of(function: &FunctionDecl) -> Signature {
+        Signature {
+            declaration: function.name.line_information,
+            parameters: function
+                .parameters
+                .iter()
+                .map(|it| it.type_annotation.line_information)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Binding<'ast> {
+    binding_type: Type,
+    // Points at where the type comes from, i.e. the annotation or the initializer it was
+    // inferred from.
+    origin: Option<Label>,
+    // The declaration from `val` up to the name, for variables that cannot be assigned to.
+    immutable: Option<LineInformation>,
+    kind: BindingKind<'ast>,
+    // Whether checks for `nil` narrow the type, see narrowing.rs.
+    narrowable: bool,
+    // The declared type of a variable whose type was narrowed.
+    narrowed_from: Option<Type>,
+}
+
+struct TypeChecker<'a, 'ast> {
+    ast: &'ast Ast,
+    error_handler: &'a ErrorHandler,
+    types: NodeMap<Type>,
+    // Names declared in the enclosing scopes, innermost last.
+    scopes: Vec<HashMap<String, Binding<'ast>>>,
+    // The functions whose bodies are being checked together with their return types, innermost
+    // last.
+    functions: Vec<(&'ast FunctionDecl, Type)>,
+    // The classes declared so far, by declaration.
+    classes: NodeMap<Class<'ast>>,
+    // The classes whose methods are being checked, innermost last.
+    enclosing_classes: Vec<Rc<ClassType>>,
+    // The names assigned to by functions nested in the function being checked or the program,
+    // innermost last.
+    captured_assignments: Vec<HashSet<&'ast str>>,
+    // The type parameters of the enclosing generic functions and classes, innermost last.
+    type_parameters: Vec<Rc<ParameterType>>,
+    had_error: bool,
+}
+
+impl<'a, 'ast> TypeChecker<'a, 'ast> {
+    fn new(ast: &'ast Ast, error_handler: &'a ErrorHandler) -> TypeChecker<'a, 'ast> {
+        TypeChecker {
+            ast,
+            error_handler,
+            types: NodeMap::new(),
+            scopes: vec![HashMap::new()],
+            functions: vec![],
+            classes: NodeMap::new(),
+            enclosing_classes: vec![],
+            captured_assignments: vec![assigned_in_functions(ast, ast.declarations())],
+            type_parameters: vec![],
+            had_error: false,
+        }
+    }
+
+    fn finish(self) -> Result<NodeMap<Type>, TypeError> {
+        match self.had_error {
+            true => Err(TypeError::new("Error checking types.")),
+            false => Ok(self.types),
+        }
+    }
+
+    fn check_expr(&mut self, expr: ExprId) -> Type {
+        self.check_expr_expecting(expr, None)
+    }
+
+    // The expected type is only used to infer the type arguments of generic calls, mismatches are
+    // reported by `expect`.
+    fn check_expr_expecting(&mut self, expr: ExprId, expected: Option<&Type>) -> Type {
+        let ast = self.ast;
+        ice::set_location(ast.span(expr).offset());
+        let expr_type = match ast.expr(expr) {
+            ExprKind::Literal(literal) => match literal {
+                Literal::Integer(_) => Type::Int,
+                Literal::Float(_) => Type::Float,
+                Literal::String(_) => Type::String,
+                Literal::Bool(_) => Type::Bool,
+                Literal::Nil => Type::Nil,
+            },
+            ExprKind::Grouping(inner) => self.check_expr_expecting(*inner, expected),
+            ExprKind::Unary { operator, operand } => self.check_unary(expr, *operator, *operand),
+            ExprKind::Binary {
+                left,
+                operator,
+                right,
+            } => {
+                let left_type = self.check_expr(*left);
+                let right_type = self.check_expr(*right);
+                self.check_operation(
+                    expr,
+                    *operator,
+                    (ast.span(*left), left_type),
+                    (ast.span(*right), right_type),
+                )
+            }
+            ExprKind::Logical {
+                left,
+                operator,
+                right,
+            } => {
+                self.expect(*left, &Type::Bool, None);
+                // The right operand is only evaluated if the left one is true for `and` and false
+                // for `or`.
+                let (when_true, when_false) = self.narrowings(*left);
+                let narrowings = match operator {
+                    LogicalOperator::And => when_true,
+                    LogicalOperator::Or => when_false,
+                };
+                self.with_narrowings(narrowings, |it| _ = it.expect(*right, &Type::Bool, None));
+                Type::Bool
+            }
+            ExprKind::Variable(identifier) => self
+                .lookup(identifier)
+                .map_or(Type::Error, |binding| binding.binding_type),
+            ExprKind::Assign {
+                target,
+                operator,
+                value,
+            } => match self.lookup(target) {
+                Some(binding) => {
+                    self.check_mutable(expr, target, &binding);
+                    match operator {
+                        Some(operator) => self
+                            .check_compound_assignment(expr, target, *operator, *value, &binding),
+                        None => self.check_plain_assignment(target, *value, &binding),
+                    }
+                    binding.binding_type
+                }
+                None => {
+                    self.check_expr(*value);
+                    Type::Error
+                }
+            },
+            ExprKind::Call { callee, arguments } => {
+                self.check_call(expr, *callee, arguments, expected)
+            }
+            ExprKind::Get { object, name } => self.check_get(*object, name),
+            ExprKind::Set {
+                object,
+                name,
+                operator,
+                value,
+            } => self.check_set(expr, *object, name, *operator, *value),
+            ExprKind::This => self.check_this(expr),
+            ExprKind::Super { method } => self.check_super(expr, method),
+            ExprKind::Conversion { target, value } => self.check_conversion(target, *value),
+            ExprKind::Error => Type::Error,
+        };
+        self.types.insert(expr, expr_type.clone());
+        expr_type
+    }
+
+    // Check expr and report if its type cannot be used where the expected type is required. The
+    // origin of the expectation is labeled, if there is one.
+    fn expect(&mut self, expr: ExprId, expected: &Type, origin: Option<Label>) -> Type {
+        let actual = self.check_expr_expecting(expr, Some(expected));
+        if !actual.is_assignable_to(expected) {
+            self.report_mismatch(&self.ast.span(expr), expected, &actual, origin);
+        }
+        actual
+    }
+
+    fn report_mismatch(
+        &mut self,
+        span: &LineInformation,
+        expected: &Type,
+        actual: &Type,
+        origin: Option<Label>,
+    ) {
+        // Floats are only not assignable to ints because the fractional part would be dropped.
+        let lossy =
+            *actual == Type::Float && (*expected == Type::Int || *expected == Type::Int.nullable());
+        let id = match lossy {
+            true => MessageId::LossyConversion,
+            false => MessageId::TypeMismatch,
+        };
+        let message = Message::new(id)
+            .arg("expected", expected)
+            .arg("found", actual);
+        let mut diagnostic = Diagnostic::from_message(Severity::Error, &message, span);
+        if lossy {
+            let note = Message::new(MessageId::ConvertExplicitly).arg("target", Type::Int);
+            diagnostic = diagnostic.with_note(&note.to_string());
+        }
+        if let Some(origin) = origin {
+            diagnostic = diagnostic.with_label(origin);
+        }
+        self.report(diagnostic);
+    }
+
+    fn check_unary(&mut self, expr: ExprId, operator: UnaryOperator, operand: ExprId) -> Type {
+        let operand_type = self.check_expr(operand);
+        match (operator, &operand_type) {
+            (_, Type::Error) => Type::Error,
+            (UnaryOperator::Negate, operand_type) if operand_type.is_numeric() => {
+                operand_type.clone()
+            }
+            (UnaryOperator::Not, Type::Bool) => Type::Bool,
+            _ => {
+                let message = Message::new(MessageId::InvalidUnaryOperand)
+                    .arg("operator", operator)
+                    .arg("operand", &operand_type);
+                self.report(Diagnostic::from_message(
+                    Severity::Error,
+                    &message,
+                    &self.ast.span(expr),
+                ));
+                Type::Error
+            }
+        }
+    }
+
+    // The type of applying a binary operator in expr to operands of the given spans and types.
+    // Arithmetic on two ints results in an int, where `/` truncates toward zero. If either operand
+    // is a float, the other one is promoted and the result is a float. `+` also concatenates
+    // strings. Numbers are compared by size and values of compatible types by equality.
+    fn check_operation(
+        &mut self,
+        expr: ExprId,
+        operator: BinaryOperator,
+        (left_span, left_type): (LineInformation, Type),
+        (right_span, right_type): (LineInformation, Type),
+    ) -> Type {
+        let result = match operator {
+            BinaryOperator::Add
+            | BinaryOperator::Subtract
+            | BinaryOperator::Multiply
+            | BinaryOperator::Divide => match (&left_type, &right_type) {
+                (Type::Error, _) | (_, Type::Error) => Some(Type::Error),
+                (Type::String, Type::String) if operator == BinaryOperator::Add => {
+                    Some(Type::String)
+                }
+                (Type::Int, Type::Int) => Some(Type::Int),
+                (left, right) if left.is_numeric() && right.is_numeric() => Some(Type::Float),
+                _ => None,
+            },
+            BinaryOperator::Less
+            | BinaryOperator::LessEqual
+            | BinaryOperator::Greater
+            | BinaryOperator::GreaterEqual => match (&left_type, &right_type) {
+                (Type::Error, _) | (_, Type::Error) => Some(Type::Bool),
+                (left, right) if left.is_numeric() && right.is_numeric() => Some(Type::Bool),
+                _ => None,
+            },
+            BinaryOperator::Equal | BinaryOperator::NotEqual => {
+                let comparable = left_type.is_assignable_to(&right_type)
+                    || right_type.is_assignable_to(&left_type);
+                comparable.then_some(Type::Bool)
+            }
+        };
+        result.unwrap_or_else(|| {
+            let message = Message::new(MessageId::InvalidBinaryOperands)
+                .arg("operator", operator)
+                .arg("left", &left_type)
+                .arg("right", &right_type);
+            let diagnostic =
+                Diagnostic::from_message(Severity::Error, &message, &self.ast.span(expr))
+                    .with_label(type_label(&left_span, &left_type))
+                    .with_label(type_label(&right_span, &right_type));
+            self.report(diagnostic);
+            Type::Error
+        })
+    }
+
+    // Arguments of generic calls are checked before the type arguments are inferred from them, the
+    // others against the parameter types.
+    fn check_call(
+        &mut self,
+        expr: ExprId,
+        callee: ExprId,
+        arguments: &[ExprId],
+        expected: Option<&Type>,
+    ) -> Type {
+        let callee_type = self.check_expr(callee);
+        let mut argument_types = None;
+        let (parameters, return_type) = match callee_type {
+            Type::Function {
+                parameters,
+                return_type,
+            } => (parameters, *return_type),
+            Type::Generic {
+                type_parameters,
+                function,
+            } => {
+                let types: Vec<Type> = arguments.iter().map(|it| self.check_expr(*it)).collect();
+                let function =
+                    self.instantiate(expr, &type_parameters, &function, &types, expected);
+                argument_types = Some(types);
+                match function {
+                    Type::Function {
+                        parameters,
+                        return_type,
+                    } => (parameters, *return_type),
+                    _ => return Type::Error,
+                }
+            }
+            Type::Error => {
+                arguments.iter().for_each(|it| _ = self.check_expr(*it));
+                return Type::Error;
+            }
+            Type::Nullable(_) => {
+                let message = Message::new(MessageId::NullableCall).arg("type", callee_type);
+                let diagnostic =
+                    Diagnostic::from_message(Severity::Error, &message, &self.ast.span(callee));
+                self.report(self.with_nil_check_note(diagnostic, callee));
+                arguments.iter().for_each(|it| _ = self.check_expr(*it));
+                return Type::Error;
+            }
+            callee_type => {
+                let message = Message::new(MessageId::NotCallable).arg("type", callee_type);
+                self.report(Diagnostic::from_message(
+                    Severity::Error,
+                    &message,
+                    &self.ast.span(callee),
+                ));
+                arguments.iter().for_each(|it| _ = self.check_expr(*it));
+                return Type::Error;
+            }
+        };
+
+        let signature = self.called_signature(callee);
+        if arguments.len() != parameters.len() {
+            let message = Message::new(MessageId::ArgumentCount)
+                .arg("count", parameters.len())
+                .arg("found", arguments.len());
+            let mut diagnostic =
+                Diagnostic::from_message(Severity::Error, &message, &self.ast.span(expr));
+            if let Some(signature) = &signature {
+                diagnostic = diagnostic.with_label(Label::from_message(
+                    &Message::new(MessageId::DeclaredHere),
+                    &signature.declaration,
+                ));
+            }
+            self.report(diagnostic);
+            if argument_types.is_none() {
+                arguments.iter().for_each(|it| _ = self.check_expr(*it));
+            }
+            return return_type;
+        }
+        for (index, (argument, parameter)) in arguments.iter().zip(parameters.iter()).enumerate() {
+            let origin = signature
+                .as_ref()
+                .map(|signature| annotation_label(&signature.parameters[index]));
+            match &argument_types {
+                Some(types) if !types[index].is_assignable_to(parameter) => {
+                    let span = self.ast.span(*argument);
+                    self.report_mismatch(&span, parameter, &types[index], origin);
+                }
+                Some(_) => {}
+                None => _ = self.expect(*argument, parameter, origin),
+            }
+        }
+        return_type
+    }
+
+    // The declared signature of the function, method, function typed variable or class a callee
+    // refers to by name, if any.
+    fn called_signature(&self, callee: ExprId) -> Option<Signature> {
+        let binding = match self.ast.expr(callee) {
+            ExprKind::Variable(identifier) => self.find(&identifier.name)?,
+            ExprKind::Get { object, name } => match self.types.get(*object)? {
+                Type::Instance(class, _) => self.find_member(class, &name.name)?,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        match binding.kind {
+            BindingKind::Function(declaration) => Some(Signature::of(declaration)),
+            BindingKind::Class(class) => self.constructor(class).map(Signature::of),
+            BindingKind::Variable(Some(TypeExpr {
+                kind: TypeExprKind::Function { parameters, .. },
+                line_information,
+            })) => Some(Signature {
+                declaration: *line_information,
+                parameters: parameters.iter().map(|it| it.line_information).collect(),
+            }),
+            _ => None,
+        }
+    }
+
+    // Numbers can be converted to either numeric type. Floats are converted to ints by truncating
+    // toward zero.
+    fn check_conversion(&mut self, target: &TypeExpr, value: ExprId) -> Type {
+        let target_type = match target.kind {
+            TypeExprKind::Int => Type::Int,
+            _ => Type::Float,
+        };
+        let value_type = self.check_expr(value);
+        if !value_type.is_numeric() && value_type != Type::Error {
+            let message = Message::new(MessageId::InvalidConversion)
+                .arg("type", &value_type)
+                .arg("target", &target_type);
+            let diagnostic =
+                Diagnostic::from_message(Severity::Error, &message, &self.ast.span(value));
+            match value_type {
+                Type::Nullable(inner) if inner.is_numeric() => {
+                    self.report(self.with_nil_check_note(diagnostic, value))
+                }
+                _ => self.report(diagnostic),
+            }
+        }
+        target_type
+    }
+
+    // Values of the declared type can be assigned to narrowed variables, which then lose their
+    // narrowing unless the value has the narrowed type as well.
+    fn check_plain_assignment(&mut self, target: &Identifier, value: ExprId, binding: &Binding) {
+        let declared_type = binding
+            .narrowed_from
+            .as_ref()
+            .unwrap_or(&binding.binding_type);
+        let value_type = self.expect(value, declared_type, binding.origin.clone());
+        if !value_type.is_assignable_to(&binding.binding_type) {
+            self.reset_narrowing(&target.name);
+        }
+    }
+
+    // `a += b` is checked like `a = a + b`.
+    fn check_compound_assignment(
+        &mut self,
+        expr: ExprId,
+        target: &Identifier,
+        operator: BinaryOperator,
+        value: ExprId,
+        binding: &Binding,
+    ) {
+        let value_type = self.check_expr(value);
+        let result = self.check_operation(
+            expr,
+            operator,
+            (target.line_information, binding.binding_type.clone()),
+            (self.ast.span(value), value_type),
+        );
+        if !result.is_assignable_to(&binding.binding_type) {
+            let span = self.ast.span(expr);
+            self.report_mismatch(
+                &span,
+                &binding.binding_type,
+                &result,
+                binding.origin.clone(),
+            );
+        }
+    }
+
+    // Report an assignment to a variable declared with `val`, suggesting to declare it with `var`
+    // instead.
+    fn check_mutable(&mut self, assignment: ExprId, target: &Identifier, binding: &Binding) {
+        let Some(declaration) = binding.immutable else {
+            return;
+        };
+        let keyword = LineInformation::new(declaration.offset(), "val".len());
+        let suggestion = Suggestion::new(
+            &Message::new(MessageId::UseReplacement)
+                .arg("replacement", "var")
+                .to_string(),
+            &keyword,
+            "var",
+            Applicability::MaybeIncorrect,
+        );
+        let message = Message::new(MessageId::AssignToImmutable).arg("name", &target.name);
+        let diagnostic =
+            Diagnostic::from_message(Severity::Error, &message, &self.ast.span(assignment))
+                .with_label(Label::from_message(
+                    &Message::new(MessageId::DeclaredImmutable),
+                    &declaration,
+                ))
+                .with_suggestion(suggestion);
+        self.report(diagnostic);
+    }
+
+    fn declare(&mut self, name: &Identifier, binding: Binding<'ast>) {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("The global scope is never left.");
+        scope.insert(name.name.clone(), binding);
+    }
+
+    fn find(&self, name: &str) -> Option<&Binding<'ast>> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    // Look up the binding an identifier refers to and report if there is none.
+    fn lookup(&mut self, identifier: &Identifier) -> Option<Binding<'ast>> {
+        if let Some(binding) = self.find(&identifier.name) {
+            return Some(binding.clone());
+        }
+        // Sorted for deterministic suggestions when several names are equally close.
+        let mut names: Vec<&str> = self
+            .scopes
+            .iter()
+            .flat_map(|scope| scope.keys().map(|name| name.as_str()))
+            .collect();
+        names.sort();
+        let (message, suggestion) =
+            unknown_identifier(&identifier.name, &identifier.line_information, names);
+        let mut diagnostic =
+            Diagnostic::from_message(Severity::Error, &message, &identifier.line_information);
+        if let Some(suggestion) = suggestion {
+            diagnostic = diagnostic.with_suggestion(suggestion);
+        }
+        self.report(diagnostic);
+        None
+    }
+
+    fn in_scope(&mut self, check: impl FnOnce(&mut Self)) {
+        self.scopes.push(HashMap::new());
+        check(self);
+        self.scopes.pop();
+    }
+
+    fn report(&mut self, diagnostic: Diagnostic) {
+        self.error_handler.report(diagnostic);
+        self.had_error = true;
+    }
+}
+
+fn annotation_label(annotation: &LineInformation) -> Label {
+    Label::from_message(
+        &Message::new(MessageId::ExpectedDueToAnnotation),
+        annotation,
+    )
+}
+
+fn type_label(span: &LineInformation, operand_type: &Type) -> Label {
+    Label::from_message(
+        &Message::new(MessageId::HasType).arg("type", operand_type),
+        span,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_handling::sink::MemorySink;
+    use crate::frontend::ast::StmtKind;
+    use crate::frontend::parser::parse;
+    use crate::frontend::scanner::scan;
+
+    // The messages of the diagnostics reported for a program.
+    pub(super) fn check_messages(input: &str) -> Vec<String> {
+        check_diagnostics(input)
+            .into_iter()
+            .map(|it| it.message)
+            .collect()
+    }
+
+    pub(super) fn check_diagnostics(input: &str) -> Vec<Diagnostic> {
+        let sink = MemorySink::new();
+        let error_handler = ErrorHandler::new(input).with_sink(Box::new(sink.clone()));
+        let tokens = scan(input, &error_handler).unwrap();
+        let ast = parse(tokens, &error_handler).unwrap();
+        let result = check(&ast, &error_handler);
+        error_handler.flush();
+        assert_eq!(result.is_ok(), error_handler.error_count() == 0);
+        sink.diagnostics()
+    }
+
+    // The types of the printed expressions of a well-typed program.
+    fn printed_types(input: &str) -> Vec<Type> {
+        let error_handler = ErrorHandler::new(input);
+        let tokens = scan(input, &error_handler).unwrap();
+        let ast = parse(tokens, &error_handler).unwrap();
+        let types = check(&ast, &error_handler).unwrap();
+        ast.declarations()
+            .iter()
+            .filter_map(|it| match ast.stmt(*it) {
+                StmtKind::Print(expr) => types.get(*expr).cloned(),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_expression_types() {
+        let input = "print -(1 + 2) * 3; print 1.5 / 2.0 < 1.0 == !true; print \"a\" + \"b\";";
+        assert_eq!(
+            printed_types(input),
+            vec![Type::Int, Type::Bool, Type::String]
+        );
+    }
+
+    #[test]
+    fn test_invalid_operands() {
+        let input = "print 1 + \"a\";\nprint -true;\nprint 1.5 < true;\nprint 1 == \"a\";";
+        assert_eq!(
+            check_messages(input),
+            vec![
+                "Cannot apply `+` to `int` and `string`.",
+                "Cannot apply `-` to `bool`.",
+                "Cannot apply `<` to `float` and `bool`.",
+                "Cannot apply `==` to `int` and `string`.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_numeric_promotion() {
+        let input = "print 1 + 2.5; print 7 / 2; print 1 < 2.0; print 1 == 1.0; print -1.5 * 2;";
+        assert_eq!(
+            printed_types(input),
+            vec![Type::Float, Type::Int, Type::Bool, Type::Bool, Type::Float]
+        );
+    }
+
+    #[test]
+    fn test_conversions() {
+        let input = "fun half(n: int): int { return n / 2.0; }\n\
+                     var count: int = int(2.5) + 1;\n\
+                     var ratio: float = count;\n\
+                     count += 0.5;\n\
+                     val rounded: int? = float(count);\n\
+                     val name: string? = nil;\n\
+                     print int(name) + float(true);";
+        assert_eq!(
+            check_messages(input),
+            vec![
+                "Expected `int`, found `float`, which would lose its fractional part.",
+                "Expected `int`, found `float`, which would lose its fractional part.",
+                "Expected `int?`, found `float`, which would lose its fractional part.",
+                "Cannot convert `string?` to `int`.",
+                "Cannot convert `bool` to `float`.",
+            ]
+        );
+        let diagnostics = check_diagnostics("val n: int = 1.5;");
+        assert_eq!(
+            diagnostics[0].notes,
+            vec!["convert explicitly with `int(...)`, which truncates toward zero"]
+        );
+        let diagnostics = check_diagnostics("var s = \"a\";\nprint int(s);");
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].notes.is_empty());
+        let diagnostics = check_diagnostics("var n: int? = 1;\nprint float(n);");
+        assert_eq!(
+            diagnostics[0].notes,
+            vec!["check that `n` is not `nil` first, e.g. with `if (n != nil)`"]
+        );
+    }
+
+    #[test]
+    fn test_operand_labels() {
+        let diagnostics = check_diagnostics("print 1 + \"a\";");
+        assert_eq!(diagnostics[0].line_information, LineInformation::new(6, 7));
+        let labels: Vec<(&str, LineInformation)> = diagnostics[0]
+            .labels
+            .iter()
+            .map(|it| (it.message.as_str(), it.line_information))
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                ("has type `int`", LineInformation::new(6, 1)),
+                ("has type `string`", LineInformation::new(10, 3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors_do_not_cascade() {
+        // Only the unknown name is reported, not the operators applied to it.
+        assert_eq!(
+            check_messages("print -(y + 1) * 2 < 3;"),
+            vec!["Unknown identifier `y`."]
+        );
+    }
+
+    #[test]
+    fn test_logical_operands_must_be_bool() {
+        assert_eq!(
+            check_messages("print true and 1;"),
+            vec!["Expected `bool`, found `int`."]
+        );
+    }
+
+    #[test]
+    fn test_calls() {
+        let input = "fun f(a: int, b: string): bool { return true; }\n\
+                     val x: bool = f(1, \"a\");\n\
+                     f(1);\n\
+                     f(\"a\", \"b\");\n\
+                     x();";
+        assert_eq!(
+            check_messages(input),
+            vec![
+                "Expected 2 arguments, found 1.",
+                "Expected `int`, found `string`.",
+                "`bool` is not a function.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_argument_mismatch_points_at_parameter() {
+        let diagnostics = check_diagnostics("fun f(a: int) {}\nf(true);");
+        assert_eq!(diagnostics[0].line_information, LineInformation::new(19, 4));
+        assert_eq!(
+            diagnostics[0].labels[0].line_information,
+            LineInformation::new(9, 3)
+        );
+    }
+
+    #[test]
+    fn test_function_types() {
+        let input = "fun apply(f: fun(int): int, x: int): int { return f(x); }\n\
+                     fun adder(n: int): fun(int): int {\n  \
+                       fun add(m: int): int { return m + n; }\n  \
+                       return add;\n\
+                     }\n\
+                     fun shout(s: string): string { return s; }\n\
+                     val g: fun(int): int = adder(2);\n\
+                     print apply(g, 1) + apply(adder(3), 2);\n\
+                     apply(shout, 1);\n\
+                     g(1, 2);\n\
+                     g(\"a\");";
+        let diagnostics = check_diagnostics(input);
+        let messages: Vec<&str> = diagnostics.iter().map(|it| it.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Expected `fun(int): int`, found `fun(string): string`.",
+                "Expected 1 argument, found 2.",
+                "Expected `int`, found `string`.",
+            ]
+        );
+        // Calls through variables point at the function type they are declared with.
+        assert_eq!(
+            diagnostics[1].line_information,
+            LineInformation::new(283, 7)
+        );
+        assert_eq!(
+            diagnostics[1].labels[0].line_information,
+            LineInformation::new(200, 13)
+        );
+        assert_eq!(
+            diagnostics[2].labels[0].line_information,
+            LineInformation::new(204, 3)
+        );
+    }
+}
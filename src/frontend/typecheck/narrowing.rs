@@ -0,0 +1,310 @@
+// Flow-sensitive narrowing of nullable variables. In code that only runs if a variable is not
+// `nil`, like the then branch of `if (x != nil)`, the variable has the non-nullable type. Only
+// variables that cannot change behind the back of the narrowed code are narrowed, i.e. `val`s and
+// variables that no nested function assigns to.
+use crate::error_handling::messages::{Message, MessageId};
+use crate::error_handling::Diagnostic;
+use crate::frontend::ast::visit::{self, Visitor};
+use crate::frontend::ast::{
+    Ast, BinaryOperator, ExprId, ExprKind, FunctionDecl, Identifier, Literal, LogicalOperator,
+    StmtId, UnaryOperator,
+};
+use crate::frontend::typecheck::{Binding, TypeChecker};
+use crate::frontend::types::Type;
+use std::collections::HashSet;
+
+// Bindings with narrowed types by name.
+pub(super) type Narrowings<'ast> = Vec<(String, Binding<'ast>)>;
+
+impl<'ast> TypeChecker<'_, 'ast> {
+    // The narrowings that hold if the condition is true and those that hold if it is false.
+    pub(super) fn narrowings(&self, condition: ExprId) -> (Narrowings<'ast>, Narrowings<'ast>) {
+        match self.ast.expr(condition) {
+            ExprKind::Grouping(inner) => self.narrowings(*inner),
+            ExprKind::Unary {
+                operator: UnaryOperator::Not,
+                operand,
+            } => {
+                let (when_true, when_false) = self.narrowings(*operand);
+                (when_false, when_true)
+            }
+            ExprKind::Binary {
+                left,
+                operator: operator @ (BinaryOperator::Equal | BinaryOperator::NotEqual),
+                right,
+            } => {
+                let narrowed: Narrowings = self
+                    .compared_to_nil(*left, *right)
+                    .and_then(|variable| self.narrow(variable))
+                    .into_iter()
+                    .collect();
+                match operator {
+                    BinaryOperator::NotEqual => (narrowed, vec![]),
+                    _ => (vec![], narrowed),
+                }
+            }
+            ExprKind::Logical {
+                left,
+                operator,
+                right,
+            } => {
+                let (left_true, left_false) = self.narrowings(*left);
+                let (right_true, right_false) = self.narrowings(*right);
+                match operator {
+                    LogicalOperator::And => ([left_true, right_true].concat(), vec![]),
+                    LogicalOperator::Or => (vec![], [left_false, right_false].concat()),
+                }
+            }
+            _ => (vec![], vec![]),
+        }
+    }
+
+    fn compared_to_nil(&self, left: ExprId, right: ExprId) -> Option<&'ast Identifier> {
+        match (self.ast.expr(left), self.ast.expr(right)) {
+            (ExprKind::Variable(variable), ExprKind::Literal(Literal::Nil))
+            | (ExprKind::Literal(Literal::Nil), ExprKind::Variable(variable)) => Some(variable),
+            _ => None,
+        }
+    }
+
+    fn narrow(&self, variable: &Identifier) -> Option<(String, Binding<'ast>)> {
+        let binding = self.find(&variable.name)?;
+        let Type::Nullable(inner) = &binding.binding_type else {
+            return None;
+        };
+        if !binding.narrowable {
+            return None;
+        }
+        let narrowed = Binding {
+            binding_type: *inner.clone(),
+            narrowed_from: Some(binding.binding_type.clone()),
+            ..binding.clone()
+        };
+        Some((variable.name.clone(), narrowed))
+    }
+
+    pub(super) fn with_narrowings(
+        &mut self,
+        narrowings: Narrowings<'ast>,
+        check: impl FnOnce(&mut Self),
+    ) {
+        self.in_scope(|it| {
+            it.scopes
+                .last_mut()
+                .expect("A scope was just entered.")
+                .extend(narrowings);
+            check(it);
+        });
+    }
+
+    // Suggest checking a nullable variable for `nil` before it is used as if it were not.
+    pub(super) fn with_nil_check_note(&self, diagnostic: Diagnostic, expr: ExprId) -> Diagnostic {
+        let ExprKind::Variable(variable) = self.ast.expr(expr) else {
+            return diagnostic;
+        };
+        let nullable = |binding: &Binding| matches!(binding.binding_type, Type::Nullable(_));
+        if !self
+            .find(&variable.name)
+            .is_some_and(|it| it.narrowable && nullable(it))
+        {
+            return diagnostic;
+        }
+        let note = Message::new(MessageId::CheckForNil).arg("name", &variable.name);
+        diagnostic.with_note(&note.to_string())
+    }
+
+    // Forget that a variable was narrowed, e.g. because it was assigned a value that may be `nil`.
+    pub(super) fn reset_narrowing(&mut self, name: &str) {
+        for scope in self.scopes.iter_mut().rev() {
+            match scope.get(name) {
+                Some(binding) if binding.narrowed_from.is_some() => _ = scope.remove(name),
+                Some(_) => return,
+                None => {}
+            }
+        }
+    }
+
+    // Loops run their parts repeatedly, so assignments anywhere in them reset narrowings before
+    // the loop is checked.
+    pub(super) fn reset_narrowings_in_loop(
+        &mut self,
+        statements: &[StmtId],
+        expressions: &[ExprId],
+    ) {
+        let mut assignments = Assignments::new(self.ast, false);
+        statements.iter().for_each(|it| assignments.visit_stmt(*it));
+        expressions
+            .iter()
+            .for_each(|it| assignments.visit_expr(*it));
+        for name in assignments.names {
+            self.reset_narrowing(name);
+        }
+    }
+
+    // A nested function may be called after a narrowed variable was assigned, so variables that
+    // can be assigned to have their declared types inside of it.
+    pub(super) fn unnarrowed_variables(&self) -> Narrowings<'ast> {
+        let mut seen = HashSet::new();
+        let mut variables = vec![];
+        for scope in self.scopes.iter().rev() {
+            for (name, binding) in scope {
+                if !seen.insert(name.as_str()) || binding.immutable.is_some() {
+                    continue;
+                }
+                if let Some(declared_type) = &binding.narrowed_from {
+                    let declared = Binding {
+                        binding_type: declared_type.clone(),
+                        narrowed_from: None,
+                        ..binding.clone()
+                    };
+                    variables.push((name.clone(), declared));
+                }
+            }
+        }
+        variables
+    }
+}
+
+// The names assigned to by the functions nested in a function body or the program.
+pub(super) fn assigned_in_functions<'ast>(ast: &'ast Ast, body: &[StmtId]) -> HashSet<&'ast str> {
+    let mut assignments = Assignments::new(ast, true);
+    body.iter().for_each(|it| assignments.visit_stmt(*it));
+    assignments.names
+}
+
+struct Assignments<'ast> {
+    ast: &'ast Ast,
+    // Whether only assignments inside of nested functions are collected.
+    only_in_functions: bool,
+    function_depth: usize,
+    names: HashSet<&'ast str>,
+}
+
+impl<'ast> Assignments<'ast> {
+    fn new(ast: &'ast Ast, only_in_functions: bool) -> Assignments<'ast> {
+        Assignments {
+            ast,
+            only_in_functions,
+            function_depth: 0,
+            names: HashSet::new(),
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for Assignments<'ast> {
+    fn ast(&self) -> &'ast Ast {
+        self.ast
+    }
+
+    fn visit_expr(&mut self, expr: ExprId) {
+        if let ExprKind::Assign { target, .. } = self.ast.expr(expr) {
+            if !self.only_in_functions || self.function_depth > 0 {
+                self.names.insert(&target.name);
+            }
+        }
+        visit::walk_expr(self, expr);
+    }
+
+    fn visit_function(&mut self, function: &'ast FunctionDecl) {
+        self.function_depth += 1;
+        visit::walk_function(self, function);
+        self.function_depth -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frontend::typecheck::tests::{check_diagnostics, check_messages};
+
+    #[test]
+    fn test_nullable_types() {
+        let input = "class Node { var next: Node?; val value: int; init() { this.value = 1; } }\n\
+                     val node: Node? = Node();\n\
+                     var count: int;\n\
+                     val name: string = nil;\n\
+                     print node.value;\n\
+                     val f: fun()? = nil;\n\
+                     f();\n\
+                     class Empty { val size: int; }";
+        assert_eq!(
+            check_messages(input),
+            vec![
+                "`count` has the non-nullable type `int`, but no initializer.",
+                "Expected `string`, found `nil`.",
+                "Cannot access `value`, since `Node?` may be `nil`.",
+                "Cannot call a value of type `fun()?`, since it may be `nil`.",
+                "`size` has the non-nullable type `int`, but is neither initialized nor assigned \
+                 in `init`.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_narrowing() {
+        let input = "class Node { var next: Node?; val value: int = 0; }\n\
+                     fun length(node: Node?): int {\n  \
+                       var length: int = 0;\n  \
+                       var current: Node? = node;\n  \
+                       while (current != nil) {\n    \
+                         length += current.value;\n    \
+                         current = current.next;\n  \
+                       }\n  \
+                       return length;\n\
+                     }\n\
+                     fun second(node: Node?): int {\n  \
+                       if (node == nil or node.next == nil) return 0;\n  \
+                       else print node.value;\n  \
+                       if (!(nil == node)) print node.value;\n  \
+                       if (node != nil and node.next != nil) print node.value;\n  \
+                       if (node != nil) { val next: Node? = node.next; if (next != nil) return next.value; }\n  \
+                       return 0;\n\
+                     }";
+        assert!(check_messages(input).is_empty());
+    }
+
+    #[test]
+    fn test_narrowing_is_reset() {
+        let input = "class Node { var next: Node?; }\n\
+                     fun f(node: Node?) {\n  \
+                       var current: Node? = node;\n  \
+                       if (current != nil) { current = current.next; print current.next; }\n  \
+                       if (current != nil) { current = Node(); print current.next; }\n  \
+                       if (current != nil) { fun g() { print current.next; } }\n  \
+                       val other: Node? = node;\n  \
+                       if (other != nil) { fun h() { print other.next; } }\n\
+                     }\n\
+                     fun g(node: Node?) {\n  \
+                       var current: Node? = node;\n  \
+                       fun reset() { current = nil; }\n  \
+                       if (current != nil) { reset(); print current.next; }\n\
+                     }\n\
+                     fun h(start: Node?) {\n  \
+                       for (var x: Node? = start; x != nil; x = x.next) { x = nil; }\n  \
+                       for (var y: Node? = start; y != nil; y = y.next) { y = Node(); }\n\
+                     }";
+        assert_eq!(
+            check_messages(input),
+            vec![
+                "Cannot access `next`, since `Node?` may be `nil`.",
+                "Cannot access `next`, since `Node?` may be `nil`.",
+                "Cannot access `next`, since `Node?` may be `nil`.",
+                "Cannot access `next`, since `Node?` may be `nil`.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nil_check_note() {
+        let diagnostics = check_diagnostics(
+            "fun f(s: string?): fun()? { return nil; }\n\
+                                             val g: fun()? = f(nil);\n\
+                                             g();\n\
+                                             f(nil)();",
+        );
+        assert_eq!(
+            diagnostics[0].notes,
+            vec!["check that `g` is not `nil` first, e.g. with `if (g != nil)`"]
+        );
+        assert!(diagnostics[1].notes.is_empty());
+    }
+}
@@ -0,0 +1,589 @@
+use crate::error_handling::ice;
+use crate::error_handling::messages::{Message, MessageId};
+use crate::error_handling::{Diagnostic, Label, LineInformation, Severity};
+use crate::frontend::ast::{
+    Ast, ClassDecl, ExprId, FunctionDecl, Identifier, StmtId, StmtKind, TypeExpr, TypeExprKind,
+    VarDecl,
+};
+use crate::frontend::typecheck::generics::split_function_type;
+use crate::frontend::typecheck::narrowing::assigned_in_functions;
+use crate::frontend::typecheck::{annotation_label, Binding, BindingKind, TypeChecker};
+use crate::frontend::types::{ClassType, Type};
+use std::rc::Rc;
+
+impl<'ast> TypeChecker<'_, 'ast> {
+    // Check statements sharing the current scope. Functions and classes declared among them can
+    // be used before their declaration, s.t. they can refer to each other. Classes are declared
+    // first, since signatures refer to them, and their members after functions, since field
+    // initializers may call them.
+    pub(super) fn check_statements(&mut self, statements: &'ast [StmtId]) {
+        let classes: Vec<(StmtId, &ClassDecl)> = statements
+            .iter()
+            .filter_map(|stmt| match self.ast.stmt(*stmt) {
+                StmtKind::Class(class) => Some((*stmt, class)),
+                _ => None,
+            })
+            .collect();
+        self.declare_classes(&classes);
+        for stmt in statements {
+            self.declare_function(*stmt);
+        }
+        self.declare_members(&classes);
+        for stmt in statements {
+            self.check_stmt(*stmt);
+        }
+    }
+
+    fn declare_function(&mut self, stmt: StmtId) {
+        let StmtKind::Function(function) = self.ast.stmt(stmt) else {
+            return;
+        };
+        let function_type = self.function_type(function);
+        self.types.insert(stmt, function_type.clone());
+        let binding = Binding {
+            binding_type: function_type,
+            origin: None,
+            immutable: None,
+            kind: BindingKind::Function(function),
+            narrowable: false,
+            narrowed_from: None,
+        };
+        self.declare(&function.name, binding);
+    }
+
+    fn check_stmt(&mut self, stmt: StmtId) {
+        let ast = self.ast;
+        ice::set_location(ast.span(stmt).offset());
+        match ast.stmt(stmt) {
+            StmtKind::Expression(expr) | StmtKind::Print(expr) => {
+                self.check_expr(*expr);
+            }
+            StmtKind::Var(decl) => {
+                let binding = self.check_var_decl(decl);
+                if decl.initializer.is_none() {
+                    self.check_initialized(&decl.name, &binding.binding_type);
+                }
+                self.declare(&decl.name, binding);
+            }
+            StmtKind::Function(function) => {
+                let function_type = match self.types.get(stmt) {
+                    Some(function_type) => function_type.clone(),
+                    None => self.function_type(function),
+                };
+                self.check_function(function, &function_type);
+            }
+            StmtKind::Class(class) => self.check_class(stmt, class),
+            StmtKind::Block(statements) => self.in_scope(|it| it.check_statements(statements)),
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.check_condition(*condition);
+                let (when_true, when_false) = self.narrowings(*condition);
+                self.with_narrowings(when_true, |it| it.check_stmt(*then_branch));
+                if let Some(else_branch) = else_branch {
+                    self.with_narrowings(when_false, |it| it.check_stmt(*else_branch));
+                }
+            }
+            StmtKind::While { condition, body } => {
+                self.reset_narrowings_in_loop(&[*body], &[*condition]);
+                self.check_condition(*condition);
+                let (when_true, _) = self.narrowings(*condition);
+                self.with_narrowings(when_true, |it| it.check_stmt(*body));
+            }
+            StmtKind::For {
+                initializer,
+                condition,
+                increment,
+                body,
+            } => self.in_scope(|it| {
+                if let Some(initializer) = initializer {
+                    it.check_stmt(*initializer);
+                }
+                let expressions: Vec<ExprId> = condition.iter().chain(increment).copied().collect();
+                it.reset_narrowings_in_loop(&[*body], &expressions);
+                let mut narrowings = vec![];
+                if let Some(condition) = condition {
+                    it.check_condition(*condition);
+                    (narrowings, _) = it.narrowings(*condition);
+                }
+                // The increment runs after the body, so it is checked with the narrowings the body
+                // leaves.
+                it.with_narrowings(narrowings, |it| {
+                    it.check_stmt(*body);
+                    if let Some(increment) = increment {
+                        it.check_expr(*increment);
+                    }
+                });
+            }),
+            StmtKind::Return(value) => self.check_return(stmt, *value),
+            StmtKind::Error => {}
+        }
+    }
+
+    // Returns the binding the declaration introduces. Declarations without annotation have the
+    // type of their initializer.
+    pub(super) fn check_var_decl(&mut self, decl: &'ast VarDecl) -> Binding<'ast> {
+        let (binding_type, origin) = match (&decl.type_annotation, decl.initializer) {
+            (Some(annotation), initializer) => {
+                let binding_type = self.resolve_type(annotation);
+                let origin = annotation_label(&annotation.line_information);
+                if let Some(initializer) = initializer {
+                    self.expect(initializer, &binding_type, Some(origin.clone()));
+                }
+                (binding_type, Some(origin))
+            }
+            (None, Some(initializer)) => self.infer(decl, initializer),
+            (None, None) => {
+                let message =
+                    Message::new(MessageId::MissingTypeAnnotation).arg("name", &decl.name.name);
+                self.report(Diagnostic::from_message(
+                    Severity::Error,
+                    &message,
+                    &decl.name.line_information,
+                ));
+                (Type::Error, None)
+            }
+        };
+        // Declarations start with their keyword.
+        let immutable = (!decl.mutable).then(|| {
+            LineInformation::new(decl.line_information.offset(), 0)
+                .join(&decl.name.line_information)
+        });
+        Binding {
+            binding_type,
+            origin,
+            immutable,
+            kind: BindingKind::Variable(decl.type_annotation.as_ref()),
+            narrowable: !decl.mutable || !self.is_captured_assignment(&decl.name),
+            narrowed_from: None,
+        }
+    }
+
+    // Variables without initializer start out as `nil`, which only nullable types admit.
+    pub(super) fn check_initialized(&mut self, name: &Identifier, binding_type: &Type) {
+        if Type::Nil.is_assignable_to(binding_type) {
+            return;
+        }
+        let message = Message::new(MessageId::Uninitialized)
+            .arg("name", &name.name)
+            .arg("type", binding_type);
+        self.report(Diagnostic::from_message(
+            Severity::Error,
+            &message,
+            &name.line_information,
+        ));
+    }
+
+    fn is_captured_assignment(&self, name: &Identifier) -> bool {
+        self.captured_assignments
+            .last()
+            .is_some_and(|names| names.contains(name.name.as_str()))
+    }
+
+    // The type of an unannotated declaration and the label pointing at the initializer it was
+    // inferred from. `nil` does not tell which type the variable is meant to have.
+    fn infer(&mut self, decl: &VarDecl, initializer: ExprId) -> (Type, Option<Label>) {
+        let initializer_type = self.check_expr(initializer);
+        let span = self.ast.span(initializer);
+        if initializer_type == Type::Nil {
+            let message = Message::new(MessageId::CannotInferFromNil).arg("name", &decl.name.name);
+            self.report(Diagnostic::from_message(Severity::Error, &message, &span));
+            return (Type::Error, None);
+        }
+        let origin = Label::from_message(
+            &Message::new(MessageId::InferredFrom).arg("type", &initializer_type),
+            &span,
+        );
+        (initializer_type, Some(origin))
+    }
+
+    // The parameter and return types of a function, whose type parameters are in scope. Functions
+    // without return type return `nil`.
+    pub(super) fn signature(&mut self, function: &FunctionDecl) -> (Vec<Type>, Type) {
+        let parameters = function
+            .parameters
+            .iter()
+            .map(|parameter| self.resolve_type(&parameter.type_annotation))
+            .collect();
+        let return_type = match &function.return_type {
+            Some(return_type) => self.resolve_type(return_type),
+            None => Type::Nil,
+        };
+        (parameters, return_type)
+    }
+
+    pub(super) fn check_function(&mut self, function: &'ast FunctionDecl, function_type: &Type) {
+        let Some((type_parameters, parameters, return_type)) = split_function_type(function_type)
+        else {
+            return;
+        };
+        self.with_type_parameters(type_parameters, |it| {
+            it.check_function_body(function, parameters, return_type)
+        });
+    }
+
+    fn check_function_body(
+        &mut self,
+        function: &'ast FunctionDecl,
+        parameters: Vec<Type>,
+        return_type: Type,
+    ) {
+        self.in_scope(|it| {
+            let unnarrowed = it.unnarrowed_variables();
+            it.scopes
+                .last_mut()
+                .expect("A scope was just entered.")
+                .extend(unnarrowed);
+            it.captured_assignments
+                .push(assigned_in_functions(it.ast, &function.body));
+            for (parameter, parameter_type) in function.parameters.iter().zip(parameters) {
+                let binding = Binding {
+                    binding_type: parameter_type,
+                    origin: Some(annotation_label(
+                        &parameter.type_annotation.line_information,
+                    )),
+                    immutable: None,
+                    kind: BindingKind::Variable(Some(&parameter.type_annotation)),
+                    narrowable: !it.is_captured_assignment(&parameter.name),
+                    narrowed_from: None,
+                };
+                it.declare(&parameter.name, binding);
+            }
+            it.functions.push((function, return_type.clone()));
+            it.check_statements(&function.body);
+            it.functions.pop();
+            it.captured_assignments.pop();
+        });
+        if let Some(annotation) = &function.return_type {
+            let may_return_nothing =
+                matches!(return_type, Type::Nil | Type::Nullable(_) | Type::Error);
+            if !may_return_nothing && !definitely_returns(self.ast, &function.body) {
+                let message = Message::new(MessageId::MayNotReturn)
+                    .arg("name", &function.name.name)
+                    .arg("type", &return_type);
+                let diagnostic = Diagnostic::from_message(
+                    Severity::Error,
+                    &message,
+                    &function.name.line_information,
+                )
+                .with_label(annotation_label(&annotation.line_information));
+                self.report(diagnostic);
+            }
+        }
+    }
+
+    fn check_condition(&mut self, condition: ExprId) {
+        self.expect(condition, &Type::Bool, None);
+    }
+
+    fn check_return(&mut self, stmt: StmtId, value: Option<ExprId>) {
+        let Some((function, return_type)) = self.functions.last().cloned() else {
+            if let Some(value) = value {
+                self.check_expr(value);
+            }
+            return;
+        };
+        let annotation = function.return_type.as_ref().map(|it| it.line_information);
+        match (value, annotation) {
+            (Some(value), Some(annotation)) => {
+                self.expect(value, &return_type, Some(annotation_label(&annotation)));
+            }
+            (Some(value), None) => {
+                self.check_expr(value);
+                let message = Message::new(MessageId::ReturnValueWithoutReturnType)
+                    .arg("name", &function.name.name);
+                let diagnostic =
+                    Diagnostic::from_message(Severity::Error, &message, &self.ast.span(value))
+                        .with_label(Label::from_message(
+                            &Message::new(MessageId::DeclaredHere),
+                            &function.name.line_information,
+                        ));
+                self.report(diagnostic);
+            }
+            (None, Some(annotation)) if return_type != Type::Error => {
+                let message = Message::new(MessageId::MissingReturnValue)
+                    .arg("name", &function.name.name)
+                    .arg("type", &return_type);
+                let diagnostic =
+                    Diagnostic::from_message(Severity::Error, &message, &self.ast.span(stmt))
+                        .with_label(annotation_label(&annotation));
+                self.report(diagnostic);
+            }
+            (None, _) => {}
+        }
+    }
+
+    // The class a type refers to by name. Other names are reported.
+    fn find_class(&mut self, name: &Identifier) -> Option<Rc<ClassType>> {
+        let class = match self.find(&name.name).map(|it| &it.kind) {
+            Some(BindingKind::Class(class)) => self.classes.get(*class),
+            _ => None,
+        };
+        if let Some(class) = class {
+            return Some(class.class_type.clone());
+        }
+        let message = Message::new(MessageId::UnknownType).arg("name", &name.name);
+        self.report(Diagnostic::from_message(
+            Severity::Error,
+            &message,
+            &name.line_information,
+        ));
+        None
+    }
+
+    pub(super) fn resolve_type(&mut self, type_expr: &TypeExpr) -> Type {
+        match &type_expr.kind {
+            TypeExprKind::Int => Type::Int,
+            TypeExprKind::Float => Type::Float,
+            TypeExprKind::Bool => Type::Bool,
+            TypeExprKind::String => Type::String,
+            TypeExprKind::Named(name) => {
+                if let Some(type_parameter) = self.find_type_parameter(&name.name) {
+                    return Type::Parameter(type_parameter);
+                }
+                match self.find_class(name) {
+                    Some(class) => self.class_instance(type_expr, class, &[]),
+                    None => Type::Error,
+                }
+            }
+            TypeExprKind::Generic { name, arguments } => match self.find_class(name) {
+                Some(class) => self.class_instance(type_expr, class, arguments),
+                None => {
+                    arguments.iter().for_each(|it| _ = self.resolve_type(it));
+                    Type::Error
+                }
+            },
+            TypeExprKind::Nullable(inner) => self.resolve_type(inner).nullable(),
+            TypeExprKind::Function {
+                parameters,
+                return_type,
+            } => Type::Function {
+                parameters: parameters.iter().map(|it| self.resolve_type(it)).collect(),
+                return_type: Box::new(match return_type {
+                    Some(return_type) => self.resolve_type(return_type),
+                    None => Type::Nil,
+                }),
+            },
+        }
+    }
+}
+
+// Whether running the statements always ends in a `return`. Loops are not assumed to run.
+fn definitely_returns(ast: &Ast, statements: &[StmtId]) -> bool {
+    statements.iter().any(|stmt| match ast.stmt(*stmt) {
+        StmtKind::Return(_) => true,
+        StmtKind::Block(statements) => definitely_returns(ast, statements),
+        StmtKind::If {
+            then_branch,
+            else_branch: Some(else_branch),
+            ..
+        } => definitely_returns(ast, &[*then_branch]) && definitely_returns(ast, &[*else_branch]),
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error_handling::LineInformation;
+    use crate::frontend::typecheck::tests::{check_diagnostics, check_messages};
+
+    #[test]
+    fn test_declaration_mismatch_labels_annotation() {
+        let diagnostics = check_diagnostics("var x: int = \"a\";");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "Expected `int`, found `string`.");
+        assert_eq!(diagnostics[0].line_information, LineInformation::new(13, 3));
+        assert_eq!(
+            diagnostics[0].labels[0].message,
+            "expected because of this annotation"
+        );
+        assert_eq!(
+            diagnostics[0].labels[0].line_information,
+            LineInformation::new(7, 3)
+        );
+    }
+
+    #[test]
+    fn test_assignment_mismatch() {
+        let input = "var x: float = 1.0;\n{ x = true; }";
+        let diagnostics = check_diagnostics(input);
+        assert_eq!(diagnostics[0].message, "Expected `float`, found `bool`.");
+        assert_eq!(
+            diagnostics[0].labels[0].line_information,
+            LineInformation::new(7, 5)
+        );
+    }
+
+    #[test]
+    fn test_well_typed_program() {
+        let input = "fun fib(n: int): int {\n\
+                       if (n < 2) return n;\n\
+                       return fib(n - 1) + fib(n - 2);\n\
+                     }\n\
+                     var total: int = 0;\n\
+                     for (var i: int = 0; i < 10; i = i + 1) total = total + fib(i);\n\
+                     val name: string? = nil;\n\
+                     print name == nil and total > 0;";
+        assert!(check_messages(input).is_empty());
+    }
+
+    #[test]
+    fn test_conditions_must_be_bool() {
+        let input = "if (1) print 1;\nwhile (\"a\") {}\nfor (; 2.0;) {}";
+        assert_eq!(
+            check_messages(input),
+            vec![
+                "Expected `bool`, found `int`.",
+                "Expected `bool`, found `string`.",
+                "Expected `bool`, found `float`.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_returns() {
+        let input = "fun f(): int { return \"a\"; }\n\
+                     fun g(): bool { return; }\n\
+                     fun h() { return 1; }";
+        assert_eq!(
+            check_messages(input),
+            vec![
+                "Expected `int`, found `string`.",
+                "`g` has to return a value of type `bool`.",
+                "`h` has no return type, but returns a value.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_definite_returns() {
+        let input = "fun f(): int {}\n\
+                     fun g(b: bool): int { if (b) return 1; }\n\
+                     fun h(b: bool): int { if (b) { return 1; } else return 2; }\n\
+                     fun i(): int? {}";
+        assert_eq!(
+            check_messages(input),
+            vec![
+                "`f` may finish without returning a value of type `int`.",
+                "`g` may finish without returning a value of type `int`.",
+            ]
+        );
+        let diagnostics = check_diagnostics("fun f(): int {}");
+        assert_eq!(diagnostics[0].line_information, LineInformation::new(4, 1));
+        assert_eq!(
+            diagnostics[0].labels[0].line_information,
+            LineInformation::new(9, 3)
+        );
+    }
+
+    #[test]
+    fn test_functions_are_visible_before_their_declaration() {
+        let input = "fun even(n: int): bool { if (n == 0) return true; return odd(n - 1); }\n\
+                     fun odd(n: int): bool { if (n == 0) return false; return even(n - 1); }";
+        assert!(check_messages(input).is_empty());
+    }
+
+    #[test]
+    fn test_scopes() {
+        let input = "{ var a: int = 1; }\nprint a;";
+        assert_eq!(check_messages(input), vec!["Unknown identifier `a`."]);
+    }
+
+    #[test]
+    fn test_missing_annotation_and_unknown_type() {
+        let input = "var x;\nvar y: Foo;\nclass Bar {}\nvar z: Bar?;";
+        assert_eq!(
+            check_messages(input),
+            vec![
+                "`x` needs a type annotation or an initializer.",
+                "Unknown type `Foo`."
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inference() {
+        let input = "fun half(n: float): float { return n / 2.0; }\n\
+                     var a = 1;\n\
+                     val b = half(3.0) * 2.0;\n\
+                     val c = a < 2 and b > 1.0;\n\
+                     var d: int = b;\n\
+                     a = \"s\";\n\
+                     val e: bool = c;";
+        let diagnostics = check_diagnostics(input);
+        let messages: Vec<&str> = diagnostics.iter().map(|it| it.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Expected `int`, found `float`, which would lose its fractional part.",
+                "Expected `int`, found `string`."
+            ]
+        );
+        // The type of `a` is explained by its initializer.
+        assert_eq!(
+            diagnostics[1].labels[0].message,
+            "type `int` inferred from this"
+        );
+        assert_eq!(
+            diagnostics[1].labels[0].line_information,
+            LineInformation::new(54, 1)
+        );
+    }
+
+    #[test]
+    fn test_cannot_infer_from_nil() {
+        assert_eq!(
+            check_messages("var x = nil;\nx = 1;"),
+            vec!["Cannot infer the type of `x` from `nil`; add a type annotation."]
+        );
+    }
+
+    #[test]
+    fn test_assignment_to_val() {
+        let input = "val x: int = 1;\nx = 2;";
+        let diagnostics = check_diagnostics(input);
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = &diagnostics[0];
+        assert_eq!(
+            diagnostic.message,
+            "Cannot assign to `x`, since it is declared with `val`."
+        );
+        assert_eq!(diagnostic.line_information, LineInformation::new(16, 5));
+        assert_eq!(
+            diagnostic.labels[0].line_information,
+            LineInformation::new(0, 5)
+        );
+        assert_eq!(diagnostic.suggestions[0].replacement, "var");
+        assert_eq!(
+            diagnostic.suggestions[0].line_information,
+            LineInformation::new(0, 3)
+        );
+    }
+
+    #[test]
+    fn test_compound_assignment_and_closures() {
+        let input = "val total: int = 0;\n\
+                     fun add(n: int) { total += n; }\n\
+                     var count: int = 0;\n\
+                     fun increment() { count += 1; }";
+        assert_eq!(
+            check_messages(input),
+            vec!["Cannot assign to `total`, since it is declared with `val`."]
+        );
+    }
+
+    #[test]
+    fn test_compound_assignment_types() {
+        let input =
+            "var s: string = \"a\";\ns += \"b\";\ns -= \"c\";\nvar b: bool = true;\nb += 1;";
+        assert_eq!(
+            check_messages(input),
+            vec![
+                "Cannot apply `-` to `string` and `string`.",
+                "Cannot apply `+` to `bool` and `int`.",
+            ]
+        );
+    }
+}
@@ -0,0 +1,497 @@
+// Types of expressions and bindings as determined by the type checker.
+use crate::error_handling::LineInformation;
+use crate::frontend::ast::StmtId;
+use std::cell::OnceCell;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Type {
+    Int,
+    Float,
+    Bool,
+    String,
+    // The type of `nil`, which is also what functions without return type return.
+    Nil,
+    // `T?`, i.e. a value of the inner type or `nil`. Other types do not admit `nil`.
+    Nullable(Box<Type>),
+    Function {
+        parameters: Vec<Type>,
+        return_type: Box<Type>,
+    },
+    // An instance of a class. Instances of generic classes have the type arguments of the class.
+    Instance(Rc<ClassType>, Vec<Type>),
+    // A type parameter inside of the function or class declaring it.
+    Parameter(Rc<ParameterType>),
+    // The type of a generic function or of the constructor of a generic class. Every call
+    // instantiates the inner function type with its own type arguments.
+    Generic {
+        type_parameters: Vec<Rc<ParameterType>>,
+        function: Box<Type>,
+    },
+    // The type of expressions that could not be typed, e.g. because of an earlier error. It is
+    // compatible with every type, s.t. one error does not cause others.
+    Error,
+}
+
+impl Type {
+    // Whether a value of this type can be used where a value of the expected type is required.
+    pub fn is_assignable_to(&self, expected: &Type) -> bool {
+        match (self, expected) {
+            (Type::Error, _) | (_, Type::Error) => true,
+            (Type::Nil, Type::Nullable(_)) => true,
+            // Integers are promoted to floats. The converse would drop the fractional part and
+            // requires an explicit conversion.
+            (Type::Int, Type::Float) => true,
+            (Type::Nullable(actual), Type::Nullable(expected)) => actual.is_assignable_to(expected),
+            (actual, Type::Nullable(expected)) => actual.is_assignable_to(expected),
+            // Type parameters stand for any type that satisfies their bound.
+            (Type::Parameter(actual), expected) => {
+                matches!(expected, Type::Parameter(expected) if expected == actual)
+                    || actual
+                        .bound()
+                        .is_some_and(|bound| bound.is_assignable_to(expected))
+            }
+            // Generic classes are invariant in their type arguments, since their fields can be
+            // both read and written.
+            (
+                Type::Instance(actual, actual_arguments),
+                Type::Instance(expected, expected_arguments),
+            ) => {
+                actual.is_subclass_of(expected)
+                    && actual_arguments
+                        .iter()
+                        .zip(expected_arguments)
+                        .all(|(actual, expected)| {
+                            actual.is_assignable_to(expected) && expected.is_assignable_to(actual)
+                        })
+            }
+            // Generic functions are compared after renaming the type parameters of one to those
+            // of the other.
+            (
+                Type::Generic {
+                    type_parameters: actual_parameters,
+                    function: actual,
+                },
+                Type::Generic {
+                    type_parameters,
+                    function,
+                },
+            ) => {
+                let renaming: Vec<(Rc<ParameterType>, Type)> = actual_parameters
+                    .iter()
+                    .cloned()
+                    .zip(type_parameters.iter().cloned().map(Type::Parameter))
+                    .collect();
+                actual_parameters.len() == type_parameters.len()
+                    && actual.substitute(&renaming).is_assignable_to(function)
+            }
+            // Generic functions can be used as the functions they can be instantiated as.
+            (Type::Generic { .. }, Type::Function { .. }) => self
+                .instantiate_as(expected)
+                .is_some_and(|function| function.is_assignable_to(expected)),
+            // Functions can be used where they accept all expected arguments and return what is
+            // expected of them.
+            (
+                Type::Function {
+                    parameters: actual_parameters,
+                    return_type: actual_return_type,
+                },
+                Type::Function {
+                    parameters,
+                    return_type,
+                },
+            ) => {
+                actual_parameters.len() == parameters.len()
+                    && parameters
+                        .iter()
+                        .zip(actual_parameters)
+                        .all(|(expected, actual)| expected.is_assignable_to(actual))
+                    && actual_return_type.is_assignable_to(return_type)
+            }
+            (actual, expected) => actual == expected,
+        }
+    }
+
+    // Instantiate a generic function as the expected function type, inferring its type arguments
+    // from it. Returns `None` if an inferred type argument does not satisfy its bound.
+    pub fn instantiate_as(&self, expected: &Type) -> Option<Type> {
+        let Type::Generic {
+            type_parameters,
+            function,
+        } = self
+        else {
+            return Some(self.clone());
+        };
+        let mut inferred = vec![None; type_parameters.len()];
+        infer_type_arguments(function, expected, type_parameters, &mut inferred);
+        let mut arguments = vec![];
+        for (parameter, argument) in type_parameters.iter().zip(inferred) {
+            let Some(argument) = argument else {
+                continue;
+            };
+            if parameter
+                .bound()
+                .is_some_and(|bound| !argument.is_assignable_to(bound))
+            {
+                return None;
+            }
+            arguments.push((parameter.clone(), argument));
+        }
+        Some(function.substitute(&arguments))
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Type::Int | Type::Float)
+    }
+
+    // The type admitting `nil` in addition to the values of this type.
+    pub fn nullable(self) -> Type {
+        match self {
+            Type::Nil | Type::Nullable(_) | Type::Error => self,
+            inner => Type::Nullable(Box::new(inner)),
+        }
+    }
+
+    // Replace type parameters by the type arguments they are instantiated with.
+    pub fn substitute(&self, arguments: &[(Rc<ParameterType>, Type)]) -> Type {
+        let substitute_all = |types: &[Type]| -> Vec<Type> {
+            types.iter().map(|it| it.substitute(arguments)).collect()
+        };
+        match self {
+            Type::Parameter(parameter) => arguments
+                .iter()
+                .find(|(it, _)| it == parameter)
+                .map_or_else(|| self.clone(), |(_, argument)| argument.clone()),
+            Type::Nullable(inner) => inner.substitute(arguments).nullable(),
+            Type::Function {
+                parameters,
+                return_type,
+            } => Type::Function {
+                parameters: substitute_all(parameters),
+                return_type: Box::new(return_type.substitute(arguments)),
+            },
+            Type::Instance(class, type_arguments) => {
+                Type::Instance(class.clone(), substitute_all(type_arguments))
+            }
+            Type::Generic {
+                type_parameters,
+                function,
+            } => Type::Generic {
+                type_parameters: type_parameters.clone(),
+                function: Box::new(function.substitute(arguments)),
+            },
+            _ => self.clone(),
+        }
+    }
+
+    // Whether the type parameter occurs in this type.
+    pub fn mentions(&self, parameter: &ParameterType) -> bool {
+        match self {
+            Type::Parameter(it) => it.as_ref() == parameter,
+            Type::Nullable(inner) => inner.mentions(parameter),
+            Type::Function {
+                parameters,
+                return_type,
+            } => {
+                parameters.iter().any(|it| it.mentions(parameter))
+                    || return_type.mentions(parameter)
+            }
+            Type::Instance(_, arguments) => arguments.iter().any(|it| it.mentions(parameter)),
+            Type::Generic { function, .. } => function.mentions(parameter),
+            _ => false,
+        }
+    }
+}
+
+// A type parameter. Parameters are told apart by where they are declared, s.t. the `T`s of
+// different declarations are different types.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParameterType {
+    pub name: String,
+    // The name of the generic function or class.
+    pub owner: String,
+    pub declaration: LineInformation,
+    // Set once the bound is resolved, which for classes happens after all classes sharing a scope
+    // are declared, since bounds may refer to any of them.
+    pub bound: OnceCell<Type>,
+}
+
+impl ParameterType {
+    pub fn bound(&self) -> Option<&Type> {
+        self.bound.get()
+    }
+}
+
+// Classes are nominal types: two classes are told apart by their declaration, even if they have
+// the same name and members.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassType {
+    pub name: String,
+    pub declaration: StmtId,
+    pub superclass: Option<Rc<ClassType>>,
+    pub type_parameters: Vec<Rc<ParameterType>>,
+}
+
+impl ClassType {
+    // Whether this is the other class or inherits from it.
+    pub fn is_subclass_of(&self, other: &ClassType) -> bool {
+        self.declaration == other.declaration
+            || self
+                .superclass
+                .as_ref()
+                .is_some_and(|superclass| superclass.is_subclass_of(other))
+    }
+
+    // The type of `this` inside of the class, whose type arguments are its own type parameters.
+    pub fn this_type(self: &Rc<Self>) -> Type {
+        let arguments = self
+            .type_parameters
+            .iter()
+            .cloned()
+            .map(Type::Parameter)
+            .collect();
+        Type::Instance(self.clone(), arguments)
+    }
+}
+
+// Types are displayed as they are written in code.
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Int => write!(f, "int"),
+            Type::Float => write!(f, "float"),
+            Type::Bool => write!(f, "bool"),
+            Type::String => write!(f, "string"),
+            Type::Nil => write!(f, "nil"),
+            Type::Nullable(inner) => write!(f, "{inner}?"),
+            Type::Function {
+                parameters,
+                return_type,
+            } => write_function(f, "", parameters, return_type),
+            Type::Instance(class, arguments) if arguments.is_empty() => write!(f, "{}", class.name),
+            Type::Instance(class, arguments) => {
+                write!(f, "{}<{}>", class.name, join(arguments.iter()))
+            }
+            Type::Parameter(parameter) => write!(f, "{}", parameter.name),
+            Type::Generic {
+                type_parameters,
+                function,
+            } => {
+                let type_parameters = join(type_parameters.iter().map(|it| match it.bound() {
+                    Some(bound) => format!("{}: {bound}", it.name),
+                    None => it.name.clone(),
+                }));
+                match function.as_ref() {
+                    Type::Function {
+                        parameters,
+                        return_type,
+                    } => {
+                        write_function(f, &format!("<{type_parameters}>"), parameters, return_type)
+                    }
+                    function => write!(f, "<{type_parameters}>{function}"),
+                }
+            }
+            Type::Error => write!(f, "{{unknown}}"),
+        }
+    }
+}
+
+fn write_function(
+    f: &mut Formatter<'_>,
+    type_parameters: &str,
+    parameters: &[Type],
+    return_type: &Type,
+) -> std::fmt::Result {
+    write!(f, "fun{type_parameters}({})", join(parameters.iter()))?;
+    match return_type {
+        Type::Nil => Ok(()),
+        return_type => write!(f, ": {return_type}"),
+    }
+}
+
+fn join(items: impl Iterator<Item = impl Display>) -> String {
+    items
+        .map(|it| it.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Infer type arguments by matching the type of a parameter against the type of an argument. A
+// type parameter matched against several types is inferred as the most general of them.
+pub fn infer_type_arguments(
+    pattern: &Type,
+    actual: &Type,
+    type_parameters: &[Rc<ParameterType>],
+    inferred: &mut [Option<Type>],
+) {
+    match (pattern, actual) {
+        (_, Type::Error) => {}
+        (Type::Parameter(parameter), actual) => {
+            let Some(index) = type_parameters.iter().position(|it| it == parameter) else {
+                return;
+            };
+            inferred[index] = Some(match inferred[index].take() {
+                Some(previous) => most_general(previous, actual.clone()),
+                None => actual.clone(),
+            });
+        }
+        (Type::Nullable(_), Type::Nil) => {}
+        (Type::Nullable(pattern), Type::Nullable(actual)) => {
+            infer_type_arguments(pattern, actual, type_parameters, inferred)
+        }
+        (Type::Nullable(pattern), actual) => {
+            infer_type_arguments(pattern, actual, type_parameters, inferred)
+        }
+        (pattern, Type::Nullable(actual)) => {
+            infer_type_arguments(pattern, actual, type_parameters, inferred)
+        }
+        (
+            Type::Function {
+                parameters: pattern_parameters,
+                return_type: pattern_return_type,
+            },
+            Type::Function {
+                parameters,
+                return_type,
+            },
+        ) => {
+            for (pattern, actual) in pattern_parameters.iter().zip(parameters) {
+                infer_type_arguments(pattern, actual, type_parameters, inferred);
+            }
+            infer_type_arguments(pattern_return_type, return_type, type_parameters, inferred);
+        }
+        // Generic functions passed where a function is expected are instantiated as that function.
+        (Type::Function { .. }, Type::Generic { .. }) => {
+            if let Some(function) = actual.instantiate_as(pattern) {
+                infer_type_arguments(pattern, &function, type_parameters, inferred);
+            }
+        }
+        (Type::Instance(pattern_class, pattern_arguments), Type::Instance(class, arguments))
+            if pattern_class.declaration == class.declaration =>
+        {
+            for (pattern, actual) in pattern_arguments.iter().zip(arguments) {
+                infer_type_arguments(pattern, actual, type_parameters, inferred);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Arguments of unrelated types are left to be reported as mismatches.
+fn most_general(previous: Type, actual: Type) -> Type {
+    if actual.is_assignable_to(&previous) {
+        previous
+    } else if previous.is_assignable_to(&actual) {
+        actual
+    } else if previous == Type::Nil {
+        actual.nullable()
+    } else if actual == Type::Nil {
+        previous.nullable()
+    } else {
+        previous
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_handling::LineInformation;
+    use crate::frontend::ast::{Ast, StmtKind};
+
+    #[test]
+    fn test_display() {
+        let function = Type::Function {
+            parameters: vec![Type::Int, Type::String],
+            return_type: Box::new(Type::Bool),
+        };
+        assert_eq!(function.to_string(), "fun(int, string): bool");
+        let procedure = Type::Function {
+            parameters: vec![],
+            return_type: Box::new(Type::Nil),
+        };
+        assert_eq!(procedure.to_string(), "fun()");
+    }
+
+    #[test]
+    fn test_assignability() {
+        assert!(Type::Int.is_assignable_to(&Type::Int));
+        assert!(Type::Int.is_assignable_to(&Type::Float));
+        assert!(!Type::Float.is_assignable_to(&Type::Int));
+        assert!(!Type::Nil.is_assignable_to(&Type::String));
+        assert!(Type::Nil.is_assignable_to(&Type::String.nullable()));
+        assert!(Type::String.is_assignable_to(&Type::String.nullable()));
+        assert!(!Type::String.nullable().is_assignable_to(&Type::String));
+        assert!(Type::Error.is_assignable_to(&Type::Bool));
+        assert!(Type::Bool.is_assignable_to(&Type::Error));
+    }
+
+    #[test]
+    fn test_function_assignability() {
+        let function = |parameters: Vec<Type>, return_type: Type| Type::Function {
+            parameters,
+            return_type: Box::new(return_type),
+        };
+        let predicate = function(vec![Type::Int], Type::Bool);
+        assert!(predicate.is_assignable_to(&function(vec![Type::Int], Type::Bool)));
+        assert!(predicate.is_assignable_to(&function(vec![Type::Error], Type::Bool)));
+        assert!(!predicate.is_assignable_to(&function(vec![Type::Float], Type::Bool)));
+        assert!(!predicate.is_assignable_to(&function(vec![], Type::Bool)));
+        assert!(!predicate.is_assignable_to(&function(vec![Type::Int], Type::Nil)));
+        assert!(!function(vec![Type::Int], Type::Nil).is_assignable_to(&predicate));
+        assert!(function(vec![Type::Int], Type::Nil)
+            .is_assignable_to(&function(vec![Type::Int], Type::Bool.nullable())));
+    }
+
+    #[test]
+    fn test_generics() {
+        let parameter = |name: &str, offset: usize| {
+            Rc::new(ParameterType {
+                name: name.to_string(),
+                owner: "f".to_string(),
+                declaration: LineInformation::new(offset, 1),
+                bound: OnceCell::new(),
+            })
+        };
+        let generic = |parameter: &Rc<ParameterType>| Type::Generic {
+            type_parameters: vec![parameter.clone()],
+            function: Box::new(Type::Function {
+                parameters: vec![Type::Parameter(parameter.clone())],
+                return_type: Box::new(Type::Parameter(parameter.clone()).nullable()),
+            }),
+        };
+        let (t, u) = (parameter("T", 0), parameter("U", 1));
+        assert_eq!(generic(&t).to_string(), "fun<T>(T): T?");
+        assert!(generic(&t).is_assignable_to(&generic(&u)));
+        assert!(!Type::Parameter(t.clone()).is_assignable_to(&Type::Parameter(u.clone())));
+        assert!(!Type::Parameter(t.clone()).is_assignable_to(&Type::Int));
+        _ = t.bound.set(Type::Int);
+        assert!(Type::Parameter(t.clone()).is_assignable_to(&Type::Int));
+        assert_eq!(
+            generic(&u)
+                .substitute(&[(u.clone(), Type::String)])
+                .to_string(),
+            "fun<U>(string): string?"
+        );
+    }
+
+    #[test]
+    fn test_subclasses_are_assignable() {
+        let mut ast = Ast::new();
+        let mut class = |name: &str, superclass: Option<Rc<ClassType>>| {
+            Rc::new(ClassType {
+                name: name.to_string(),
+                declaration: ast.alloc_stmt(StmtKind::Error, LineInformation::new(0, 0)),
+                superclass,
+                type_parameters: vec![],
+            })
+        };
+        let animal = class("Animal", None);
+        let dog = class("Dog", Some(animal.clone()));
+        let other = class("Animal", None);
+        let instance = |class: &Rc<ClassType>| Type::Instance(class.clone(), vec![]);
+        assert!(instance(&dog).is_assignable_to(&instance(&animal)));
+        assert!(!instance(&animal).is_assignable_to(&instance(&dog)));
+        assert!(!instance(&other).is_assignable_to(&instance(&animal)));
+    }
+}
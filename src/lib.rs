@@ -0,0 +1,2 @@
+pub mod error_handling;
+pub mod frontend;
@@ -1,10 +1,17 @@
-mod error_handling;
-mod frontend;
-
-use crate::error_handling::ErrorHandler;
-use crate::frontend::scanner::{scan, TokenType};
-use clap::Parser;
-use std::{io, process};
+use clap::{Parser, ValueEnum};
+use rlox::error_handling::fix::{apply_suggestions, with_original_line_endings};
+use rlox::error_handling::ice::{self, Phase, ICE_EXIT_CODE};
+use rlox::error_handling::lint::{LintLevel, LintLevels};
+use rlox::error_handling::messages::{set_locale, Locale, Message, MessageId};
+use rlox::error_handling::render::SnippetRenderer;
+use rlox::error_handling::sink::{DiagnosticSink, JsonSink, TerminalSink};
+use rlox::error_handling::ErrorHandler;
+use rlox::frontend::ast::{json, sexpr, tree, Ast};
+use rlox::frontend::cst;
+use rlox::frontend::parser::parse;
+use rlox::frontend::scanner::{scan, TokenType};
+use rlox::frontend::typecheck;
+use std::{io, panic, process};
 
 #[derive(clap::Parser, Debug)]
 #[command(version, about = "A statically typed lox interpreter.")]
@@ -12,36 +19,244 @@ struct Args {
     /// The input file to parse and execute
     #[arg(short, long)]
     file: String,
+
+    /// Print an intermediate representation of the input to stdout
+    #[arg(long, value_enum, value_name = "WHAT")]
+    emit: Option<Emit>,
+
+    /// Format of `--emit ast`
+    #[arg(long, value_enum, default_value_t = AstFormat::Sexpr)]
+    ast_format: AstFormat,
+
+    /// Apply all machine-applicable suggestions to the input file in place
+    #[arg(long)]
+    fix: bool,
+
+    /// Language of diagnostics, e.g. `en` or `de` (defaults to `LANG`)
+    #[arg(long)]
+    lang: Option<String>,
+
+    /// Stop reporting diagnostics after this many errors
+    #[arg(long, value_name = "N")]
+    max_errors: Option<usize>,
+
+    /// How diagnostics are reported
+    #[arg(long, value_enum, default_value_t = DiagnosticsFormat::Terminal)]
+    diagnostics_format: DiagnosticsFormat,
+
+    /// Number of source lines shown before a reported error
+    #[arg(long, default_value_t = 0)]
+    context_before: usize,
+
+    /// Number of source lines shown after a reported error
+    #[arg(long, default_value_t = 0)]
+    context_after: usize,
+
+    /// Width of tab stops when rendering source lines
+    #[arg(long, default_value_t = 4)]
+    tab_width: usize,
+
+    /// Report the given lint as a warning (`warnings` addresses all warnings)
+    #[arg(short = 'W', value_name = "LINT")]
+    warn: Vec<String>,
+
+    /// Report the given lint as an error (`-D warnings` turns all warnings into errors)
+    #[arg(short = 'D', value_name = "LINT")]
+    deny: Vec<String>,
+
+    /// Do not report the given lint
+    #[arg(short = 'A', value_name = "LINT")]
+    allow: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Emit {
+    /// The scanned tokens, reported as notes
+    Tokens,
+    /// The syntax tree
+    Ast,
+    /// The lossless concrete syntax tree, including whitespace and comments
+    Cst,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum AstFormat {
+    /// Compact S-expressions, one line per declaration
+    Sexpr,
+    /// Indented tree with the span of every node
+    Tree,
+    /// Versioned JSON schema for tools
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum DiagnosticsFormat {
+    /// Source snippets for humans
+    Terminal,
+    /// One JSON object per line
+    Json,
 }
 
 fn main() {
     let args = Args::parse();
-    let file = read_file(args.file.as_str()).unwrap_or_else(|err| {
-        println!("Error: {}", err);
-        process::exit(1);
-    });
-    ErrorHandler::init_logging().expect("Logging could not be setup.");
-
-    let error_handler = ErrorHandler::new(&file);
-    let tokens = scan(&file, &error_handler).unwrap();
-
-    for token in tokens.iter() {
-        if token.token_type() == &TokenType::EOF {
-            continue;
-        }
-        error_handler.report_error(
-            &format!("{:?}", &token.token_type()),
-            token.line_information(),
+    set_locale(get_locale(&args).unwrap_or_else(|err| exit_with_error(&err)));
+    let original = read_file(args.file.as_str()).unwrap_or_else(|err| exit_with_error(&err));
+    let file = original.replace("\r\n", "\n");
+    let lint_levels = get_lint_levels(&args).unwrap_or_else(|err| exit_with_error(&err));
+
+    // Bugs in rlox are reported as internal errors instead of raw panics.
+    ice::install_panic_hook(&args.file, &file);
+    let exit_code = panic::catch_unwind(|| run(&args, &original, &file, lint_levels));
+    process::exit(exit_code.unwrap_or_else(|_| {
+        if let Some(report) = ice::take_report() {
+            eprintln!("{report}");
+        }
+        ICE_EXIT_CODE
+    }));
+}
+
+// Flushes the diagnostics reported before an internal error while the panic unwinds, s.t. they
+// are shown ahead of its report.
+struct FlushOnPanic<'a>(&'a ErrorHandler);
+
+impl Drop for FlushOnPanic<'_> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.0.flush();
+        }
+    }
+}
+
+// Process the input file and return the exit code. The file is processed with `\n` line endings,
+// fixes are applied to the original.
+fn run(args: &Args, original: &str, file: &str, lint_levels: LintLevels) -> i32 {
+    let error_handler = ErrorHandler::new(file)
+        .with_sink(get_sink(args))
+        .with_lint_levels(lint_levels)
+        .with_max_errors(args.max_errors);
+    let _flush_on_panic = FlushOnPanic(&error_handler);
+    ice::enter_phase(Phase::Scanning);
+    let scan_result = scan(file, &error_handler);
+
+    let mut succeeded = false;
+    if let Ok(tokens) = scan_result {
+        if args.emit == Some(Emit::Tokens) {
+            for token in tokens.iter() {
+                if token.token_type() == &TokenType::EOF {
+                    continue;
+                }
+                error_handler.report_note(
+                    &Message::new(MessageId::Token)
+                        .arg("token", format!("{:?}", token.token_type())),
+                    token.line_information(),
+                );
+            }
+        }
+        ice::enter_phase(Phase::Parsing);
+        let cst_tokens = (args.emit == Some(Emit::Cst)).then(|| tokens.clone());
+        let parse_result = parse(tokens, &error_handler);
+        if let Some(tokens) = cst_tokens {
+            // Without an AST the tree is flat, but it still contains the whole input.
+            let empty = Ast::new();
+            let ast = parse_result.as_ref().unwrap_or(&empty);
+            print!("{}", cst::build(file, &tokens, ast).debug_dump());
+        }
+        if let Ok(ast) = parse_result {
+            if args.emit == Some(Emit::Ast) {
+                print!("{}", format_ast(&ast, args.ast_format));
+            }
+            ice::enter_phase(Phase::TypeChecking);
+            succeeded = typecheck::check(&ast, &error_handler).is_ok();
+        }
+    }
+
+    ice::enter_phase(Phase::Reporting);
+    error_handler.flush();
+    if args.fix {
+        ice::enter_phase(Phase::Fixing);
+        let suggestions = with_original_line_endings(original, &error_handler.suggestions());
+        let (fixed, applied) = apply_suggestions(original, &suggestions);
+        if applied > 0 {
+            std::fs::write(&args.file, fixed).unwrap_or_else(|err| exit_with_error(&err));
+        }
+        eprintln!(
+            "{}",
+            Message::new(MessageId::FixesApplied).arg("count", applied)
         );
     }
+
+    eprintln!("{}", error_handler.summary());
+    match succeeded {
+        true => 0,
+        false => 1,
+    }
+}
+
+fn format_ast(ast: &Ast, format: AstFormat) -> String {
+    match format {
+        AstFormat::Sexpr => sexpr::print(ast),
+        AstFormat::Tree => tree::print(ast),
+        AstFormat::Json => format!("{:#}\n", json::to_json(ast)),
+    }
+}
+
+fn get_sink(args: &Args) -> Box<dyn DiagnosticSink> {
+    match args.diagnostics_format {
+        DiagnosticsFormat::Terminal => {
+            let renderer = SnippetRenderer::new()
+                .with_context_lines(args.context_before, args.context_after)
+                .with_tab_width(args.tab_width);
+            Box::new(TerminalSink::stderr(renderer))
+        }
+        DiagnosticsFormat::Json => Box::new(JsonSink::stderr()),
+    }
+}
+
+// An explicitly requested language has to exist, while unsupported values of `LANG` fall back
+// to English.
+fn get_locale(args: &Args) -> Result<Locale, String> {
+    match &args.lang {
+        Some(lang) => Locale::from_name(lang).ok_or_else(|| {
+            Message::new(MessageId::UnknownLanguage)
+                .arg("language", lang)
+                .to_string()
+        }),
+        None => Ok(std::env::var("LANG")
+            .ok()
+            .and_then(|lang| Locale::from_name(&lang))
+            .unwrap_or(Locale::English)),
+    }
+}
+
+fn exit_with_error(err: &dyn std::fmt::Display) -> ! {
+    println!("{}", Message::new(MessageId::CliError).arg("message", err));
+    process::exit(1);
+}
+
+fn get_lint_levels(args: &Args) -> Result<LintLevels, String> {
+    let mut lint_levels = LintLevels::new();
+    let requested = [
+        (&args.allow, LintLevel::Allow),
+        (&args.warn, LintLevel::Warn),
+        (&args.deny, LintLevel::Deny),
+    ];
+    for (names, level) in requested {
+        for name in names {
+            if !lint_levels.set(name, level) {
+                return Err(Message::new(MessageId::UnknownLint)
+                    .arg("name", name)
+                    .to_string());
+            }
+        }
+    }
+    Ok(lint_levels)
 }
 
 fn read_file(file: &str) -> io::Result<String> {
-    let result = std::fs::read_to_string(file)?.replace("\r\n", "\n");
+    let result = std::fs::read_to_string(file)?;
     if !result.is_ascii() {
-        return Err(io::Error::new(
-            io::ErrorKind::Other,
-            "Input file cannot contain non-ascii characters.",
+        return Err(io::Error::other(
+            Message::new(MessageId::InputNotAscii).to_string(),
         ));
     }
     Ok(result)